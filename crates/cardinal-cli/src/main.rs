@@ -19,6 +19,18 @@ enum Commands {
         /// Path to rules.toml file
         #[arg(short, long, default_value = "./rules.toml")]
         rules: String,
+        /// Show instance IDs of hidden cards (debugging)
+        #[arg(long)]
+        debug: bool,
+        /// Deck file for player 0 (defaults to a small test deck)
+        #[arg(long)]
+        deck0: Option<String>,
+        /// Deck file for player 1 (defaults to a small test deck)
+        #[arg(long)]
+        deck1: Option<String>,
+        /// Who plays player 1; without one they never act
+        #[arg(long, value_enum)]
+        opponent: Option<Opponent>,
     },
     /// Build a .ccpack file from a directory
     BuildPack {
@@ -26,6 +38,12 @@ enum Commands {
         input: String,
         /// Output .ccpack file path
         output: String,
+        /// Glob pattern for files to leave out (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Glob pattern for files to keep even if excluded by default (repeatable)
+        #[arg(long)]
+        include: Vec<String>,
     },
     /// List contents of a .ccpack file
     ListPack {
@@ -54,6 +72,110 @@ enum Commands {
         #[command(subcommand)]
         target: TestTarget,
     },
+    /// Inspect a game's rules
+    Rules {
+        #[command(subcommand)]
+        target: RulesTarget,
+    },
+    /// Browse card definitions
+    Cards {
+        #[command(subcommand)]
+        target: CardsTarget,
+    },
+    /// Generate an Ed25519 keypair for signing packs
+    Keygen {
+        /// Secret key output path (the public key is written to <out>.pub)
+        #[arg(short, long)]
+        out: String,
+    },
+    /// Sign and verify .ccpack files
+    Pack {
+        #[command(subcommand)]
+        target: PackTarget,
+    },
+    /// Measure engine throughput by playing random legal actions
+    Bench {
+        /// Path to rules.toml file
+        #[arg(short, long, default_value = "./rules.toml")]
+        rules: String,
+        /// Number of actions to apply
+        #[arg(long, default_value = "100000")]
+        actions: u64,
+        /// Seed for choosing the actions
+        #[arg(short, long, default_value = "42")]
+        seed: u64,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Opponent {
+    /// The engine's default bot
+    Bot,
+}
+
+#[derive(Subcommand)]
+enum CardsTarget {
+    /// List the cards in a pack, cards directory or cards file
+    List {
+        /// Path to a .ccpack file, cards directory or cards .toml file
+        source: String,
+        /// Only cards of this type
+        #[arg(long = "type")]
+        card_type: Option<String>,
+        /// Only cards with this keyword (repeatable; all must match)
+        #[arg(long)]
+        keyword: Vec<String>,
+        /// Only cards of this color (repeatable; all must match)
+        #[arg(long)]
+        color: Vec<String>,
+        /// Only cards whose name contains this text
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Print every card in a pack as a database for external tools
+    Export {
+        /// Path to .ccpack file
+        pack: String,
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: ExportFormat,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ExportFormat {
+    /// A JSON array of card definitions
+    Json,
+}
+
+#[derive(Subcommand)]
+enum PackTarget {
+    /// Sign a .ccpack file, writing <pack>.sig next to it
+    Sign {
+        /// Path to .ccpack file
+        pack: String,
+        /// Secret key file from `cardinal keygen`
+        #[arg(short, long)]
+        key: String,
+    },
+    /// Verify a .ccpack file against its <pack>.sig
+    Verify {
+        /// Path to .ccpack file
+        pack: String,
+        /// Public key file (<key>.pub)
+        #[arg(short, long)]
+        pubkey: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesTarget {
+    /// Summarize players, phases, zones, resources, keywords and cards
+    Info {
+        /// Path to rules.toml file
+        #[arg(short, long, default_value = "./rules.toml")]
+        rules: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -123,6 +245,20 @@ enum TestTarget {
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+        /// Keep decks in declared order instead of shuffling
+        #[arg(long)]
+        no_shuffle: bool,
+    },
+    /// Run the tests embedded in a card's definition
+    Card {
+        /// Card ID
+        card: String,
+        /// Path to rules.toml file
+        #[arg(short, long, default_value = "./rules.toml")]
+        rules: String,
+        /// Also print what the card's scripts logged
+        #[arg(short, long)]
+        verbose: bool,
     },
     /// Test loading a .ccpack file
     Pack {
@@ -138,11 +274,12 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Play { rules }) => {
-            run_game(&rules);
+        Some(Commands::Play { rules, debug, deck0, deck1, opponent }) => {
+            run_game(&rules, debug, [deck0, deck1], opponent);
         }
-        Some(Commands::BuildPack { input, output }) => {
-            if let Err(e) = cardinal::pack::build_pack(&input, &output) {
+        Some(Commands::BuildPack { input, output, exclude, include }) => {
+            let options = cardinal::pack::PackBuildOptions { exclude, include };
+            if let Err(e) = cardinal::pack::build_pack_with_options(&input, &output, &options) {
                 eprintln!("Error building pack: {}", e);
                 std::process::exit(1);
             }
@@ -162,20 +299,44 @@ fn main() {
         Some(Commands::Validate { target }) => {
             handle_validation(target);
         }
+        Some(Commands::Keygen { out }) => {
+            match cardinal::pack::generate_keypair(&out) {
+                Ok(pub_path) => {
+                    println!("✓ Secret key written to: {}", out);
+                    println!("✓ Public key written to: {}", pub_path.display());
+                }
+                Err(e) => {
+                    eprintln!("Error generating key: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Pack { target }) => {
+            handle_pack(target);
+        }
+        Some(Commands::Cards { target }) => {
+            handle_cards(target);
+        }
         Some(Commands::Compile { target }) => {
             handle_compilation(target);
         }
         Some(Commands::Test { target }) => {
             handle_testing(target);
         }
+        Some(Commands::Rules { target }) => {
+            handle_rules(target);
+        }
+        Some(Commands::Bench { rules, actions, seed }) => {
+            handle_bench(&rules, actions, seed);
+        }
         None => {
             // Default: run the game with default rules
-            run_game("./rules.toml");
+            run_game("./rules.toml", false, [None, None], None);
         }
     }
 }
 
-fn run_game(rules_path: &str) {
+fn run_game(rules_path: &str, debug: bool, decks: [Option<String>; 2], opponent: Option<Opponent>) {
     println!("Welcome to Cardinal - A Rules Engine TCG!");
     println!();
 
@@ -192,37 +353,73 @@ fn run_game(rules_path: &str) {
     println!("✓ Cards loaded: {}", rules.cards.len());
     println!();
 
-    // Create initial game state
-    let initial_state = GameState::from_ruleset(&rules);
-    println!("✓ Game state created");
-
-    // Create test decks (for demo)
-    let mut state = initial_state.clone();
-    populate_test_decks(&mut state, 5);
-    println!("✓ Test decks populated");
-
-    // Initialize the game
-    let state = cardinal::initialize_game(state, &rules, 42);
+    let mut engine = match new_engine(rules, &decks) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Failed to load deck: {}", e.0);
+            return;
+        }
+    };
     println!("✓ Game initialized");
     println!();
 
-    // Create game engine
-    let mut engine = GameEngine::new(rules, 42, state);
-    let mut display = GameDisplay::new();
+    let mut display = GameDisplay::new().with_debug(debug);
+    let bot = opponent.map(|Opponent::Bot| Bot::new());
 
     println!("═══════════════════════════════════════════════════════════");
     println!("Game starting! You are Player 0");
     println!("═══════════════════════════════════════════════════════════");
     println!();
 
-    // Game loop
     let stdin = io::stdin();
-    let reader = stdin.lock();
-    let mut lines = reader.lines();
+    play_game(&mut engine, &mut display, stdin.lock().lines(), bot.as_ref());
+
+    println!();
+    println!("Thanks for playing!");
+}
 
+/// Set up a game with each player's deck loaded from its file, or a small
+/// test deck where none is given
+fn new_engine(rules: Ruleset, decks: &[Option<String>; 2]) -> Result<GameEngine, cardinal::error::CardinalError> {
+    let mut state = GameState::from_ruleset(&rules);
+    for (player, deck) in decks.iter().enumerate() {
+        let cards = match deck {
+            Some(path) => cardinal::load_deck(path, &rules)?,
+            None => test_deck(player as u8, 5),
+        };
+        let deck_zone_id = format!("deck@{}", player);
+        if let Some(zone) = state.zones.iter_mut().find(|z| z.id.0 == deck_zone_id) {
+            zone.cards = cards;
+        }
+    }
+
+    let state = cardinal::initialize_game(state, &rules, 42);
+    Ok(GameEngine::new(rules, 42, state))
+}
+
+/// Player 0 reads commands from `lines` until the game ends or input runs
+/// out; `bot`, if any, plays player 1 whenever the game waits on them
+fn play_game(
+    engine: &mut GameEngine,
+    display: &mut GameDisplay,
+    mut lines: impl Iterator<Item = io::Result<String>>,
+    bot: Option<&Bot>,
+) {
+    let viewer = PlayerId(0);
     loop {
+        if let Some(bot) = bot {
+            run_bot(engine, display, bot, PlayerId(1));
+        }
+        if let Some(end) = &engine.state.ended {
+            match end.winner {
+                Some(winner) if winner == viewer => println!("You win! ({})", end.reason),
+                Some(_) => println!("You lose. ({})", end.reason),
+                None => println!("The game is a draw. ({})", end.reason),
+            }
+            break;
+        }
+
         // Render current game state
-        let viewer = PlayerId(0);
         println!("{}", display.render_game(&engine.state, &engine.cards, viewer));
         println!();
 
@@ -247,26 +444,26 @@ fn run_game(rules_path: &str) {
                         println!("You cannot play cards right now.");
                         continue;
                     }
-                    handle_play_card(&mut engine, &mut display, viewer);
+                    handle_play_card(engine, display, viewer, &mut lines);
                 }
                 "2" => {
-                    handle_view_hand(&engine, &display, viewer);
+                    handle_view_hand(engine, display, viewer);
                 }
                 "3" => {
-                    handle_view_field(&engine, viewer);
+                    handle_view_field(engine, viewer);
                 }
                 "4" => {
-                    handle_view_opponent_field(&engine, viewer);
+                    handle_view_opponent_field(engine, viewer);
                 }
                 "5" => {
-                    handle_view_log(&display);
+                    handle_view_log(display);
                 }
                 "6" => {
                     if !is_priority {
                         println!("You do not have priority.");
                         continue;
                     }
-                    handle_pass_priority(&mut engine, &mut display, viewer);
+                    handle_pass_priority(engine, display, viewer);
                 }
                 "7" => {
                     println!("You have conceded. Game over!");
@@ -282,27 +479,53 @@ fn run_game(rules_path: &str) {
             break;
         }
     }
-
-    println!();
-    println!("Thanks for playing!");
 }
 
-fn populate_test_decks(state: &mut GameState, num_cards: usize) {
-    let num_players = state.players.len() as u8;
-    for player_idx in 0..num_players {
-        let _player_id = PlayerId(player_idx);
-        let deck_zone_id = format!("deck@{}", player_idx);
-
-        if let Some(deck) = state.zones.iter_mut().find(|z| z.id.0 == deck_zone_id) {
-            for i in 0..num_cards {
-                let card_id = cardinal::ids::CardId((player_idx as u32 * 100) + i as u32);
-                deck.cards.push(card_id);
-            }
+/// Let the bot take actions for as long as the game is waiting on it
+fn run_bot(engine: &mut GameEngine, display: &mut GameDisplay, bot: &Bot, player: PlayerId) {
+    while engine.state.ended.is_none() {
+        let waiting = match &engine.state.pending_choice {
+            Some(_) => !engine.legal_actions(player).is_empty(),
+            None => engine.state.turn.priority_player == player,
+        };
+        if !waiting {
+            break;
         }
+        let Some(action) = bot.choose_action(engine, player) else {
+            break;
+        };
+        let message = match &action {
+            Action::PlayCard { card, .. } => match engine.cards.get(&engine.state.definition_of(*card).0) {
+                Some(def) => format!("Opponent played: {}", def.name),
+                None => format!("Opponent played: Card #{}", card.0),
+            },
+            Action::PassPriority => format!("Player {} passed priority", player.0),
+            _ => "Opponent made a choice".to_string(),
+        };
+        if engine.apply_action(player, action).is_err() {
+            break;
+        }
+        display.log(
+            engine.state.turn.number,
+            engine.state.turn.phase.0,
+            engine.state.turn.step.0,
+            message,
+        );
     }
 }
 
-fn handle_play_card(engine: &mut GameEngine, display: &mut GameDisplay, player: PlayerId) {
+fn test_deck(player: u8, num_cards: usize) -> Vec<cardinal::ids::CardId> {
+    (0..num_cards)
+        .map(|i| cardinal::ids::CardId((player as u32 * 100) + i as u32))
+        .collect()
+}
+
+fn handle_play_card(
+    engine: &mut GameEngine,
+    display: &mut GameDisplay,
+    player: PlayerId,
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+) {
     // Get hand zone
     let hand_zone_id = format!("hand@{}", player.0);
     let hand_cards: Vec<_> = engine.state.zones.iter()
@@ -317,7 +540,7 @@ fn handle_play_card(engine: &mut GameEngine, display: &mut GameDisplay, player:
 
     println!("Select a card to play:");
     for (idx, card_id) in hand_cards.iter().enumerate() {
-        if let Some(card_def) = engine.cards.get(&card_id.0) {
+        if let Some(card_def) = engine.cards.get(&engine.state.definition_of(*card_id).0) {
             println!("  [{}] {} ({})", idx + 1, card_def.name, card_def.card_type);
         } else {
             println!("  [{}] Card #{}", idx + 1, card_id.0);
@@ -327,61 +550,60 @@ fn handle_play_card(engine: &mut GameEngine, display: &mut GameDisplay, player:
     use std::io::Write;
     io::stdout().flush().unwrap();
 
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_ok() {
-        if let Ok(idx) = input.trim().parse::<usize>() {
-            if idx > 0 && idx <= hand_cards.len() {
-                let card_id = hand_cards[idx - 1];
-                let from_zone = cardinal::ids::ZoneId(Box::leak(hand_zone_id.into_boxed_str()));
+    if let Some(Ok(input)) = lines.next()
+        && let Ok(idx) = input.trim().parse::<usize>()
+        && idx > 0
+        && idx <= hand_cards.len()
+    {
+        let card_id = hand_cards[idx - 1];
+        let from_zone = cardinal::ids::ZoneId(Box::leak(hand_zone_id.into_boxed_str()));
 
-                match engine.apply_action(player, Action::PlayCard { card: card_id, from: from_zone }) {
-                    Ok(result) => {
-                        if let Some(card_def) = engine.cards.get(&card_id.0) {
+        match engine.apply_action(player, Action::PlayCard { card: card_id, from: from_zone }) {
+            Ok(result) => {
+                if let Some(card_def) = engine.cards.get(&engine.state.definition_of(card_id).0) {
+                    display.log(
+                        engine.state.turn.number,
+                        engine.state.turn.phase.0,
+                        engine.state.turn.step.0,
+                        format!("You played: {}", card_def.name),
+                    );
+                }
+                for event in &result.events {
+                    match event {
+                        Event::CardPlayed { .. } => {
                             display.log(
                                 engine.state.turn.number,
-                                &engine.state.turn.phase.0,
-                                &engine.state.turn.step.0,
-                                format!("You played: {}", card_def.name),
+                                engine.state.turn.phase.0,
+                                engine.state.turn.step.0,
+                                "Card entered play".to_string(),
                             );
                         }
-                        for event in &result.events {
-                            match event {
-                                Event::CardPlayed { .. } => {
-                                    display.log(
-                                        engine.state.turn.number,
-                                        &engine.state.turn.phase.0,
-                                        &engine.state.turn.step.0,
-                                        "Card entered play".to_string(),
-                                    );
-                                }
-                                Event::CardMoved { .. } => {
-                                    display.log(
-                                        engine.state.turn.number,
-                                        &engine.state.turn.phase.0,
-                                        &engine.state.turn.step.0,
-                                        "Card moved to field".to_string(),
-                                    );
-                                }
-                                Event::StackResolved { .. } => {
-                                    display.log(
-                                        engine.state.turn.number,
-                                        &engine.state.turn.phase.0,
-                                        &engine.state.turn.step.0,
-                                        "Stack item resolved".to_string(),
-                                    );
-                                }
-                                _ => {}
-                            }
+                        Event::CardMoved { .. } => {
+                            display.log(
+                                engine.state.turn.number,
+                                engine.state.turn.phase.0,
+                                engine.state.turn.step.0,
+                                "Card moved to field".to_string(),
+                            );
                         }
-                        println!("Card played!");
-                    }
-                    Err(e) => {
-                        println!("Cannot play card: {:?}", e);
+                        Event::StackResolved { .. } => {
+                            display.log(
+                                engine.state.turn.number,
+                                engine.state.turn.phase.0,
+                                engine.state.turn.step.0,
+                                "Stack item resolved".to_string(),
+                            );
+                        }
+                        _ => {}
                     }
                 }
-                return;
+                println!("Card played!");
+            }
+            Err(e) => {
+                println!("Cannot play card: {:?}", e);
             }
         }
+        return;
     }
 
     println!("Invalid selection.");
@@ -401,7 +623,7 @@ fn handle_view_hand(engine: &GameEngine, display: &GameDisplay, player: PlayerId
 
     println!();
     for (idx, card_id) in hand_cards.iter().enumerate() {
-        println!("{}", display.render_card_detail(&engine.cards, *card_id));
+        println!("{}", display.render_card_detail(&engine.state, &engine.cards, *card_id));
         if idx < hand_cards.len() - 1 {
             println!();
         }
@@ -410,21 +632,18 @@ fn handle_view_hand(engine: &GameEngine, display: &GameDisplay, player: PlayerId
 }
 
 fn handle_view_field(engine: &GameEngine, player: PlayerId) {
-    let field_zone_id = format!("field@{}", player.0);
-    let field = engine.state.zones.iter()
-        .find(|z| z.id.0 == field_zone_id);
+    // Everything the player controls, including cards taken from the opponent
+    let field = engine.state.controlled_cards(player);
 
     println!();
-    if let Some(zone) = field {
-        if zone.cards.is_empty() {
-            println!("Your field is empty!");
-        } else {
-            for (idx, card_id) in zone.cards.iter().enumerate() {
-                if let Some(card_def) = engine.cards.get(&card_id.0) {
-                    println!("[{}] {} ({})", idx + 1, card_def.name, card_def.card_type);
-                } else {
-                    println!("[{}] Card #{}", idx + 1, card_id.0);
-                }
+    if field.is_empty() {
+        println!("Your field is empty!");
+    } else {
+        for (idx, card_id) in field.iter().enumerate() {
+            if let Some(card_def) = engine.cards.get(&engine.state.definition_of(*card_id).0) {
+                println!("[{}] {} ({})", idx + 1, card_def.name, card_def.card_type);
+            } else {
+                println!("[{}] Card #{}", idx + 1, card_id.0);
             }
         }
     }
@@ -433,18 +652,14 @@ fn handle_view_field(engine: &GameEngine, player: PlayerId) {
 
 fn handle_view_opponent_field(engine: &GameEngine, player: PlayerId) {
     let opponent = if player.0 == 0 { PlayerId(1) } else { PlayerId(0) };
-    let field_zone_id = format!("field@{}", opponent.0);
-    let field = engine.state.zones.iter()
-        .find(|z| z.id.0 == field_zone_id);
+    let field = engine.state.controlled_cards(opponent);
 
     println!();
-    if let Some(zone) = field {
-        if zone.cards.is_empty() {
-            println!("Opponent field is empty!");
-        } else {
-            for (idx, _) in zone.cards.iter().enumerate() {
-                println!("[{}] Mystery Creature", idx + 1);
-            }
+    if field.is_empty() {
+        println!("Opponent field is empty!");
+    } else {
+        for (idx, _) in field.iter().enumerate() {
+            println!("[{}] Mystery Creature", idx + 1);
         }
     }
     println!();
@@ -461,9 +676,9 @@ fn handle_pass_priority(engine: &mut GameEngine, display: &mut GameDisplay, play
         Ok(_result) => {
             display.log(
                 engine.state.turn.number,
-                &engine.state.turn.phase.0,
-                &engine.state.turn.step.0,
-                format!("Player {:?} passed priority", player),
+                engine.state.turn.phase.0,
+                engine.state.turn.step.0,
+                format!("Player {} passed priority", player.0),
             );
             println!("You passed priority.");
         }
@@ -564,15 +779,168 @@ fn handle_compilation(target: CompileTarget) {
     }
 }
 
+fn handle_cards(target: CardsTarget) {
+    use cardinal::rules::card_loader::{load_cards_from_dir, load_cards_from_file, load_cards_from_pack};
+    use cardinal::rules::query::CardQuery;
+
+    match target {
+        CardsTarget::List { source, card_type, keyword, color, name } => {
+            let path = std::path::Path::new(&source);
+            let loaded = if source.ends_with(".ccpack") {
+                load_cards_from_pack(path)
+            } else if path.is_dir() {
+                load_cards_from_dir(path)
+            } else {
+                load_cards_from_file(path)
+            };
+            let cards = match loaded {
+                Ok(cards) => cards,
+                Err(e) => {
+                    eprintln!("Failed to load cards: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let query = CardQuery { card_type, keywords: keyword, colors: color, name_contains: name };
+            let matches = query.filter(&cards);
+            for card in &matches {
+                let keywords = if card.keywords.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", card.keywords.join(", "))
+                };
+                println!("{:>6}  {} ({}){}", card.id, card.name, card.card_type, keywords);
+            }
+            println!("{} of {} cards", matches.len(), cards.len());
+        }
+        CardsTarget::Export { pack, format: ExportFormat::Json } => {
+            match cardinal::rules::card_loader::export_card_database(&pack) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("Failed to export cards: {:#}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn handle_pack(target: PackTarget) {
+    match target {
+        PackTarget::Sign { pack, key } => {
+            match cardinal::pack::sign_pack(&pack, &key) {
+                Ok(sig_path) => println!("✓ Signature written to: {}", sig_path.display()),
+                Err(e) => {
+                    eprintln!("Error signing pack: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        PackTarget::Verify { pack, pubkey } => {
+            match cardinal::pack::verify_pack(&pack, &pubkey) {
+                Ok(()) => println!("✓ Signature valid: {}", pack),
+                Err(e) => {
+                    eprintln!("✗ Verification failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn handle_rules(target: RulesTarget) {
+    match target {
+        RulesTarget::Info { rules } => {
+            match load_game_config(&rules, None) {
+                Ok(ruleset) => println!("{}", ruleset.summary()),
+                Err(e) => {
+                    eprintln!("Failed to load rules: {}", e.0);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// Throughput measured by `run_bench`
+struct BenchReport {
+    actions: u64,
+    games: u64,
+    elapsed: std::time::Duration,
+}
+
+impl BenchReport {
+    fn actions_per_sec(&self) -> f64 {
+        self.actions as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Games are restarted after this many turns so a stalled game doesn't
+/// grow without bound
+const BENCH_MAX_TURNS: u32 = 100;
+
+/// Apply `actions` random legal actions, starting a new game whenever one
+/// ends or nobody can act
+fn run_bench(rules: &Ruleset, actions: u64, seed: u64) -> Result<BenchReport, cardinal::error::CardinalError> {
+    let mut rng = GameRng::new(seed);
+    let mut engine = new_engine(rules.clone(), &[None, None])?;
+    let mut games = 1;
+    let mut applied = 0;
+
+    let start = std::time::Instant::now();
+    while applied < actions {
+        let choice = engine.state.players.iter()
+            .map(|p| (p.id, engine.legal_actions(p.id)))
+            .find(|(_, legal)| !legal.is_empty());
+        let over = engine.state.ended.is_some() || engine.state.turn.number > BENCH_MAX_TURNS;
+        let Some((player, mut legal)) = choice.filter(|_| !over) else {
+            engine = new_engine(rules.clone(), &[None, None])?;
+            games += 1;
+            continue;
+        };
+
+        let action = legal.swap_remove(rng.generate::<u32>() as usize % legal.len());
+        engine.apply_action(player, action)?;
+        applied += 1;
+    }
+
+    Ok(BenchReport { actions: applied, games, elapsed: start.elapsed() })
+}
+
+fn handle_bench(rules_path: &str, actions: u64, seed: u64) {
+    let rules = match load_game_config(rules_path, None) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Failed to load rules: {}", e.0);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Running {} random actions...", actions);
+    match run_bench(&rules, actions, seed) {
+        Ok(report) => {
+            println!("  Actions:  {}", report.actions);
+            println!("  Games:    {}", report.games);
+            println!("  Elapsed:  {:.3}s", report.elapsed.as_secs_f64());
+            println!("  Rate:     {:.0} actions/sec", report.actions_per_sec());
+        }
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e.0);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn handle_testing(target: TestTarget) {
     use cardinal::testing::*;
 
     match target {
-        TestTarget::Game { rules, seed, hand_size, verbose } => {
+        TestTarget::Game { rules, seed, hand_size, verbose, no_shuffle } => {
             let options = TestOptions {
                 seed,
                 starting_hand_size: hand_size,
                 verbose,
+                shuffle: !no_shuffle,
             };
 
             match run_basic_test(&rules, options) {
@@ -585,6 +953,55 @@ fn handle_testing(target: TestTarget) {
                 }
             }
         }
+        TestTarget::Card { card, rules, verbose } => {
+            let ruleset = match load_game_config(&rules, None) {
+                Ok(ruleset) => ruleset,
+                Err(e) => {
+                    eprintln!("Failed to load rules: {}", e.0);
+                    std::process::exit(1);
+                }
+            };
+
+            let results = match run_card_tests(&ruleset, &card) {
+                Ok(results) => results,
+                Err(e) => {
+                    eprintln!("Test error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            if results.is_empty() {
+                println!("Card '{}' has no embedded tests", card);
+                return;
+            }
+
+            let failed = results.iter().filter(|r| !r.passed).count();
+            for result in &results {
+                if result.passed {
+                    println!("✓ {}", result.name);
+                } else {
+                    println!("✗ {}", result.name);
+                    println!("    expected: {}", result.expected.join(", "));
+                    println!("    actual:   {}", result.actual.join(", "));
+                    if let Some(error) = &result.error {
+                        println!("    error:    {}", error);
+                    }
+                }
+                if verbose {
+                    for message in &result.log {
+                        println!("    log:      {}", message);
+                    }
+                    for step in &result.trace {
+                        println!("    trace:    {}{}", "  ".repeat(step.depth()), step);
+                    }
+                }
+            }
+            println!("\n{} passed, {} failed", results.len() - failed, failed);
+
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
         TestTarget::Pack { pack, verbose } => {
             match test_pack_loading(&pack, verbose) {
                 Ok(summary) => {
@@ -599,3 +1016,36 @@ fn handle_testing(target: TestTarget) {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_game_against_bot_completes_a_turn() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let rules = cardinal::load_game_config(root.join("rules.toml"), None).expect("repo rules load");
+        let mut engine = new_engine(rules, &[None, None]).expect("test decks");
+        let mut display = GameDisplay::new();
+        let bot = Bot::new();
+
+        // Look around, try to play a card, then keep passing priority
+        let mut script = vec!["2", "3", "4", "5", "1", "1"];
+        script.extend(std::iter::repeat_n("6", 60));
+        let lines = script.into_iter().map(|line| Ok(line.to_string()));
+        play_game(&mut engine, &mut display, lines, Some(&bot));
+
+        assert!(engine.state.turn.number > 1, "the turn should have passed");
+        assert!(display.game_log.iter().any(|entry| entry.message.contains("Player 1 passed priority")));
+    }
+
+    #[test]
+    fn test_bench_reports_a_positive_rate() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let rules = cardinal::load_game_config(root.join("rules.toml"), None).expect("repo rules load");
+        let report = run_bench(&rules, 200, 7).expect("bench runs");
+        assert_eq!(report.actions, 200);
+        assert!(report.games >= 1);
+        assert!(report.actions_per_sec() > 0.0);
+    }
+}
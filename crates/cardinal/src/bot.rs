@@ -0,0 +1,109 @@
+use crate::{
+    engine::core::GameEngine,
+    engine::costs::{auto_pay, cost_of},
+    ids::{CardId, PlayerId},
+    model::action::{Action, TargetRef},
+    model::command::{ChoiceKind, PendingChoice},
+    rules::query::effective_stats,
+};
+
+/// How a bot answers `ChooseTarget` prompts
+pub trait TargetPolicy {
+    /// Pick one of `engine.legal_targets(choice)`, or `None` when there is none
+    fn choose_target(&self, engine: &GameEngine, choice: &PendingChoice) -> Option<TargetRef>;
+}
+
+/// Aims at the other side: an opponent when a player may be targeted,
+/// otherwise the opponents' creature with the lowest toughness. The bot's
+/// own player and cards are only picked when nothing else is legal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTargetPolicy;
+
+impl TargetPolicy for DefaultTargetPolicy {
+    fn choose_target(&self, engine: &GameEngine, choice: &PendingChoice) -> Option<TargetRef> {
+        let targets = engine.legal_targets(choice);
+        let chooser = engine.chooser(choice);
+
+        let opponent = targets.iter()
+            .find(|target| matches!(target, TargetRef::Player(p) if *p != chooser));
+        if let Some(target) = opponent {
+            return Some(target.clone());
+        }
+
+        let weakest_enemy = targets.iter()
+            .filter_map(|target| match target {
+                TargetRef::Card(card) if engine.state.controller_of(*card) != Some(chooser) => Some(*card),
+                _ => None,
+            })
+            .min_by_key(|card| toughness(engine, *card));
+        if let Some(card) = weakest_enemy {
+            return Some(TargetRef::Card(card));
+        }
+
+        targets.into_iter().next()
+    }
+}
+
+/// A computer player. Answers target prompts with its `TargetPolicy`, other
+/// choices with the first legal answer, and otherwise plays the costliest
+/// card in hand it can pay for (the first of them on a tie) or passes
+/// priority. Payments are worked out with `auto_pay`, after cost modifiers.
+#[derive(Debug, Clone, Default)]
+pub struct Bot<P: TargetPolicy = DefaultTargetPolicy> {
+    pub policy: P,
+}
+
+impl Bot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<P: TargetPolicy> Bot<P> {
+    /// A bot that answers target prompts with `policy`
+    pub fn with_policy(policy: P) -> Self {
+        Self { policy }
+    }
+
+    /// The action `player` takes next, or `None` if they can't act right now
+    pub fn choose_action(&self, engine: &GameEngine, player: PlayerId) -> Option<Action> {
+        let legal = engine.legal_actions(player);
+        if let Some(choice) = &engine.state.pending_choice
+            && matches!(choice.kind, ChoiceKind::ChooseTarget { .. })
+            && !legal.is_empty()
+        {
+            return self.policy.choose_target(engine, choice)
+                .map(|target| Action::ChooseTarget { choice_id: choice.id, target });
+        }
+
+        let mut best: Option<(i32, &Action)> = None;
+        for action in &legal {
+            if let Action::PlayCard { card, .. } = action
+                && let Some(mana) = affordable_mana(engine, player, *card)
+                && best.is_none_or(|(most, _)| mana > most)
+            {
+                best = Some((mana, action));
+            }
+        }
+        best.map(|(_, action)| action).or_else(|| legal.first()).cloned()
+    }
+}
+
+/// How much mana `card` costs `player` right now, or `None` if `auto_pay`
+/// can't cover it. Cards without a definition are free.
+fn affordable_mana(engine: &GameEngine, player: PlayerId, card: CardId) -> Option<i32> {
+    let cost = cost_of(engine, player, card).unwrap_or_default();
+    auto_pay(engine, player, &cost).ok()?;
+    Some(cost.generic + cost.symbols.values().sum::<i32>())
+}
+
+/// Current toughness (see `effective_stats`). Cards without one sort last.
+fn toughness(engine: &GameEngine, card: CardId) -> i32 {
+    let has_toughness = engine.state.card_instances.get(&card)
+        .is_some_and(|i| i.stats.contains_key("toughness"))
+        || engine.cards.get(&engine.state.definition_of(card).0).is_some_and(|def| def.stats.contains_key("toughness"));
+    if !has_toughness {
+        return i32::MAX;
+    }
+    effective_stats(card, &engine.cards, &engine.state).1
+}
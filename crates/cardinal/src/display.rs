@@ -3,6 +3,7 @@ use crate::{
     state::gamestate::GameState,
     ids::{PlayerId, CardId},
     engine::cards::CardRegistry,
+    rules::query::effective_stats,
 };
 
 /// Game log entry for tracking what happened
@@ -14,15 +15,51 @@ pub struct LogEntry {
     pub message: String,
 }
 
+/// Label used for face-down cards unless configured otherwise
+pub const DEFAULT_HIDDEN_CARD_LABEL: &str = "Mystery Card";
+
 /// Complete game display with formatting
 pub struct GameDisplay {
     pub game_log: Vec<LogEntry>,
+    /// Text shown in place of a hidden card (a "card back")
+    pub hidden_card_label: String,
+    /// Show instance IDs on hidden cards (debugging only; leaks hidden information)
+    pub show_hidden_ids: bool,
+}
+
+impl Default for GameDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameDisplay {
     pub fn new() -> Self {
         Self {
             game_log: Vec::new(),
+            hidden_card_label: DEFAULT_HIDDEN_CARD_LABEL.to_string(),
+            show_hidden_ids: false,
+        }
+    }
+
+    /// Use a custom label for hidden cards
+    pub fn with_hidden_card_label(mut self, label: impl Into<String>) -> Self {
+        self.hidden_card_label = label.into();
+        self
+    }
+
+    /// Enable or disable debug mode, which reveals instance IDs of hidden cards
+    pub fn with_debug(mut self, show_hidden_ids: bool) -> Self {
+        self.show_hidden_ids = show_hidden_ids;
+        self
+    }
+
+    /// Text for a card the viewer isn't allowed to see
+    fn hidden_card_text(&self, card_id: CardId) -> String {
+        if self.show_hidden_ids {
+            format!("{} (#{})", self.hidden_card_label, card_id.0)
+        } else {
+            self.hidden_card_label.clone()
         }
     }
 
@@ -71,6 +108,12 @@ impl GameDisplay {
         ));
         output.push('\n');
 
+        // Opponent hand: card backs, except what they revealed this turn
+        if state.zones.iter().any(|z| z.id.0 == format!("hand@{}", opponent.0)) {
+            output.push_str(&self.render_opponent_hand(state, cards, opponent, viewer));
+            output.push('\n');
+        }
+
         // Stack
         output.push_str(&self.render_stack(state));
         output.push('\n');
@@ -138,19 +181,30 @@ impl GameDisplay {
     ) -> String {
         let zone_id_str = format!("{}@{}", zone_name, player.0);
         let zone = state.zones.iter().find(|z| z.id.0 == zone_id_str);
+        // A field shows what the player controls, including cards they
+        // took control of from another player's field
+        let zone_cards = zone.map(|z| if zone_name == "field" {
+            state.controlled_cards(player)
+        } else {
+            z.cards.clone()
+        });
 
         let mut output = String::new();
         output.push_str(&format!("{}\n", display_name.bold().cyan()));
 
-        if let Some(zone) = zone {
-            if zone.cards.is_empty() {
+        if let Some(zone_cards) = zone_cards {
+            if zone_cards.is_empty() {
                 output.push_str("  (empty)\n");
             } else {
-                for (idx, card_id) in zone.cards.iter().enumerate() {
+                for (idx, card_id) in zone_cards.iter().enumerate() {
                     if hide_cards {
-                        output.push_str(&format!("  [{}] Mystery Card\n", idx + 1));
-                    } else if let Some(card_def) = cards.get(&card_id.0) {
-                        let card_str = format!("[{}] {} ({})", idx + 1, card_def.name, card_def.card_type);
+                        output.push_str(&format!("  [{}] {}\n", idx + 1, self.hidden_card_text(*card_id)));
+                    } else if let Some(card_def) = cards.get(&state.definition_of(*card_id).0) {
+                        let mut card_str = format!("[{}] {} ({})", idx + 1, card_def.name, card_def.card_type);
+                        if card_def.stats.contains_key("power") || card_def.stats.contains_key("toughness") {
+                            let (power, toughness) = effective_stats(*card_id, cards, state);
+                            card_str.push_str(&format!(" {}/{}", power, toughness));
+                        }
                         output.push_str(&format!("  {}\n", card_str.yellow()));
                     } else {
                         output.push_str(&format!("  [{}] Card #{}\n", idx + 1, card_id.0));
@@ -181,7 +235,7 @@ impl GameDisplay {
                 output.push_str("  (empty)\n");
             } else {
                 for (idx, card_id) in hand.cards.iter().enumerate() {
-                    if let Some(card_def) = cards.get(&card_id.0) {
+                    if let Some(card_def) = cards.get(&state.definition_of(*card_id).0) {
                         let cost_str = card_def.cost.as_deref().unwrap_or("—");
                         let card_str = format!(
                             "[{}] {} ({}) [{}]",
@@ -198,6 +252,40 @@ impl GameDisplay {
         output
     }
 
+    fn render_opponent_hand(
+        &self,
+        state: &GameState,
+        cards: &CardRegistry,
+        opponent: PlayerId,
+        viewer: PlayerId,
+    ) -> String {
+        let mut output = String::new();
+        output.push_str(&format!("{}\n", "Opponent Hand".bold().cyan()));
+
+        let hand_id = format!("hand@{}", opponent.0);
+        let hand = state.zones.iter()
+            .find(|z| z.id.0 == hand_id)
+            .map(|z| z.cards.as_slice())
+            .unwrap_or(&[]);
+        if hand.is_empty() {
+            output.push_str("  (empty)\n");
+        }
+        for (idx, (card_id, seen)) in hand.iter().zip(state.hand_view(opponent, viewer)).enumerate() {
+            match seen {
+                None => output.push_str(&format!("  [{}] {}\n", idx + 1, self.hidden_card_text(*card_id))),
+                Some(_) => match cards.get(&state.definition_of(*card_id).0) {
+                    Some(card_def) => {
+                        let card_str = format!("[{}] {} ({}) (revealed)", idx + 1, card_def.name, card_def.card_type);
+                        output.push_str(&format!("  {}\n", card_str.yellow()));
+                    }
+                    None => output.push_str(&format!("  [{}] Card #{} (revealed)\n", idx + 1, card_id.0)),
+                },
+            }
+        }
+
+        output
+    }
+
     fn render_stack(&self, state: &GameState) -> String {
         let mut output = String::new();
         output.push_str(&format!("{}\n", "Stack".bold().cyan()));
@@ -213,6 +301,8 @@ impl GameDisplay {
                 let effect_str = match &item.effect {
                     crate::model::command::EffectRef::Builtin(name) => name.to_string(),
                     crate::model::command::EffectRef::Scripted(name) => name.clone(),
+                    crate::model::command::EffectRef::Sequence(effects) => format!("sequence of {}", effects.len()),
+                    crate::model::command::EffectRef::Conditional(condition, _) => format!("if {}", condition),
                 };
                 
                 output.push_str(&format!(
@@ -279,11 +369,11 @@ impl GameDisplay {
         output
     }
 
-    /// Render a single card in detail
-    pub fn render_card_detail(&self, cards: &CardRegistry, card_id: CardId) -> String {
+    /// Render a single card in detail, as the definition it uses in `state`
+    pub fn render_card_detail(&self, state: &GameState, cards: &CardRegistry, card_id: CardId) -> String {
         let mut output = String::new();
 
-        if let Some(card_def) = cards.get(&card_id.0) {
+        if let Some(card_def) = cards.get(&state.definition_of(card_id).0) {
             output.push_str(&format!("{}", "┌─────────────────────────┐\n".bright_black()));
             output.push_str(&format!(
                 "{} {} [{}] {}\n",
@@ -334,3 +424,88 @@ impl GameDisplay {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{PhaseId, StepId, ZoneId};
+    use crate::state::gamestate::{PlayerState, TurnState, ZoneState};
+    use std::collections::BTreeMap;
+
+    fn state_with_opponent_card(card: CardId) -> GameState {
+        let zone = |id: &'static str, owner: u8, cards: Vec<CardId>| ZoneState {
+            id: ZoneId(id),
+            owner: Some(PlayerId(owner)),
+            cards,
+        };
+        GameState {
+            turn: TurnState {
+                number: 1,
+                active_player: PlayerId(0),
+                priority_player: PlayerId(0),
+                phase: PhaseId("main"),
+                step: StepId("main"),
+                priority_passes: 0,
+            },
+            players: (0..2)
+                .map(|i| PlayerState { id: PlayerId(i), life: 20, resources: BTreeMap::new(), draws_this_turn: 0, plays_this_turn: BTreeMap::new(), revealed: Vec::new(), revealed_to: Vec::new(), conceded: false })
+                .collect(),
+            zones: vec![
+                zone("field@0", 0, vec![]),
+                zone("field@1", 1, vec![card]),
+                zone("hand@0", 0, vec![]),
+            ],
+            stack: vec![],
+            pending_choice: None,
+            ended: None,
+            card_instances: BTreeMap::new(),
+            damage_shields: Vec::new(),
+            last_coin_flip: None,
+            rng: crate::util::rng::GameRng::new(0),
+            resource_caps: BTreeMap::new(),
+            max_hand_size: None,
+            extra_turns: Vec::new(),
+            skips: Vec::new(),
+            end_of_turn: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_hidden_card_label_is_configurable() {
+        let state = state_with_opponent_card(CardId(77));
+        let cards = CardRegistry::new();
+
+        let default_output = GameDisplay::new().render_game(&state, &cards, PlayerId(0));
+        assert!(default_output.contains(DEFAULT_HIDDEN_CARD_LABEL));
+
+        let custom = GameDisplay::new().with_hidden_card_label("Face-down");
+        let output = custom.render_game(&state, &cards, PlayerId(0));
+        assert!(output.contains("Face-down"));
+        assert!(!output.contains(DEFAULT_HIDDEN_CARD_LABEL));
+    }
+
+    #[test]
+    fn test_revealed_opponent_hand_is_shown() {
+        let mut state = state_with_opponent_card(CardId(77));
+        state.zones.push(ZoneState { id: ZoneId("hand@1"), owner: Some(PlayerId(1)), cards: vec![CardId(5), CardId(6)] });
+        state.players[1].revealed = vec![CardId(6)];
+        let cards = CardRegistry::new();
+
+        let output = GameDisplay::new().render_game(&state, &cards, PlayerId(0));
+        assert!(output.contains("Opponent Hand"));
+        assert!(output.contains("Card #6 (revealed)"));
+        assert!(!output.contains("Card #5"));
+    }
+
+    #[test]
+    fn test_debug_mode_reveals_hidden_ids() {
+        let state = state_with_opponent_card(CardId(77));
+        let cards = CardRegistry::new();
+
+        let play = GameDisplay::new().render_game(&state, &cards, PlayerId(0));
+        assert!(!play.contains("#77"));
+
+        let debug = GameDisplay::new().with_debug(true).render_game(&state, &cards, PlayerId(0));
+        assert!(debug.contains("#77"));
+    }
+}
@@ -0,0 +1,110 @@
+//! Lookahead bots, built on `with_snapshot` rollouts
+
+use crate::{
+    bot::{Bot, DefaultTargetPolicy, TargetPolicy},
+    engine::core::GameEngine,
+    ids::PlayerId,
+    model::action::Action,
+    rules::query::effective_stats,
+};
+
+/// A bot that looks `depth` actions ahead before acting. Every action
+/// legal for whoever is to act is tried in a `with_snapshot` rollout, the
+/// bot's own turns maximising and everyone else's minimising `evaluate`.
+///
+/// The greedy `Bot`'s pick is tried first and only replaced by a strictly
+/// better one, so depth 0 plays exactly like `Bot` and a deeper search never
+/// prefers an action it scores the same. The search only reads the engine,
+/// including its RNG, so the same game and seed always give the same action.
+/// The engine's undo history is set aside for the whole search, so the
+/// rollouts neither record nor copy it.
+#[derive(Debug, Clone, Default)]
+pub struct SearchBot<P: TargetPolicy = DefaultTargetPolicy> {
+    pub greedy: Bot<P>,
+    /// How many actions ahead to look, counting every player's
+    pub depth: u32,
+}
+
+impl SearchBot {
+    pub fn new(depth: u32) -> Self {
+        Self { greedy: Bot::new(), depth }
+    }
+}
+
+impl<P: TargetPolicy> SearchBot<P> {
+    /// The action `player` takes next, or `None` if they can't act right
+    /// now. The engine is back as it was when this returns.
+    pub fn choose_action(&self, engine: &mut GameEngine, player: PlayerId) -> Option<Action> {
+        let greedy = self.greedy.choose_action(engine, player)?;
+        if self.depth == 0 {
+            return Some(greedy);
+        }
+        engine.without_history(|engine| self.search_from(engine, player, greedy))
+    }
+
+    /// The best of `greedy` and every other legal action for `player`, or
+    /// `None` if `greedy` fails
+    fn search_from(&self, engine: &mut GameEngine, player: PlayerId, greedy: Action) -> Option<Action> {
+        let mut best = (self.score_after(engine, player, player, &greedy, self.depth)?, greedy);
+        for action in engine.legal_actions(player) {
+            if let Some(score) = self.score_after(engine, player, player, &action, self.depth)
+                && score > best.0
+            {
+                best = (score, action);
+            }
+        }
+        Some(best.1)
+    }
+
+    /// `evaluate` for `player` once `actor` takes `action` and the search
+    /// goes `depth - 1` actions further; `None` if the action fails
+    fn score_after(&self, engine: &mut GameEngine, player: PlayerId, actor: PlayerId, action: &Action, depth: u32) -> Option<i64> {
+        engine.with_snapshot(|engine| {
+            engine.apply_action(actor, action.clone()).ok()?;
+            Some(self.search(engine, player, depth - 1))
+        })
+    }
+
+    fn search(&self, engine: &mut GameEngine, player: PlayerId, depth: u32) -> i64 {
+        if depth == 0 || engine.state.ended.is_some() {
+            return evaluate(engine, player);
+        }
+        let actor = next_actor(engine);
+        let scores: Vec<i64> = engine.legal_actions(actor).iter()
+            .filter_map(|action| self.score_after(engine, player, actor, action, depth))
+            .collect();
+        let best = if actor == player { scores.into_iter().max() } else { scores.into_iter().min() };
+        best.unwrap_or_else(|| evaluate(engine, player))
+    }
+}
+
+/// Who the game is waiting on: whoever answers the pending choice, otherwise
+/// the priority player
+fn next_actor(engine: &GameEngine) -> PlayerId {
+    match &engine.state.pending_choice {
+        Some(choice) => engine.chooser(choice),
+        None => engine.state.turn.priority_player,
+    }
+}
+
+/// How good the game looks for `player`: a win or loss outright, otherwise
+/// their life and board presence (one per card on their field plus its
+/// power) less every opponent's
+pub fn evaluate(engine: &GameEngine, player: PlayerId) -> i64 {
+    if let Some(end) = &engine.state.ended {
+        return match end.winner {
+            Some(winner) if winner == player => i64::MAX,
+            Some(_) => i64::MIN,
+            None => 0,
+        };
+    }
+    engine.state.players.iter()
+        .map(|p| {
+            let board: i64 = engine.state.controlled_cards(p.id).into_iter()
+                .map(|card| 1 + effective_stats(card, &engine.cards, &engine.state).0 as i64)
+                .sum();
+            let standing = p.life as i64 + board;
+            if p.id == player { standing } else { -standing }
+        })
+        .sum()
+}
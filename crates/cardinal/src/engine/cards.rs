@@ -1,8 +1,12 @@
 use std::collections::{HashMap, HashSet};
 use crate::{
     ids::CardId,
-    rules::schema::{CardDef, Ruleset},
-    model::command::{Command, StackItem, EffectRef},
+    model::action::TargetRef,
+    rules::schema::{CardAbility, CardDef, EffectSpec, Ruleset},
+    model::command::{AllowedTargets, ChoiceKind, Command, EffectRef, PendingChoice, StackItem},
+    rules::query::{evaluate_amount, evaluate_condition},
+    state::gamestate::GameState,
+    engine::effect_executor::BuiltinRegistry,
 };
 
 /// Maps card IDs to their definitions for O(1) lookup during gameplay
@@ -23,7 +27,8 @@ pub fn build_registry(cards: &[CardDef]) -> CardRegistry {
 }
 
 /// Build a card registry from card definitions with ruleset validation
-/// This validates that cards only reference keywords defined in the ruleset
+/// This validates that cards only reference keywords and colors defined in the ruleset,
+/// and that the engine can execute every effect of their abilities (see [`effect_errors`])
 pub fn build_validated_registry(cards: &[CardDef], ruleset: &Ruleset) -> Result<CardRegistry, String> {
     let mut registry = HashMap::new();
     
@@ -31,6 +36,9 @@ pub fn build_validated_registry(cards: &[CardDef], ruleset: &Ruleset) -> Result<
     let valid_keywords: HashSet<String> = ruleset.keywords.iter()
         .map(|k| k.id.clone())
         .collect();
+    let valid_colors: HashSet<&str> = ruleset.colors.iter()
+        .map(|c| c.id.as_str())
+        .collect();
     
     for card_def in cards {
         // Validate keywords - each keyword must exist in ruleset
@@ -41,7 +49,7 @@ pub fn build_validated_registry(cards: &[CardDef], ruleset: &Ruleset) -> Result<
                 let keyword_list = if sorted_keywords.len() > 10 {
                     format!("{:?} and {} more", &sorted_keywords[..10], sorted_keywords.len() - 10)
                 } else {
-                    format!("{}", sorted_keywords.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", "))
+                    sorted_keywords.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
                 };
                 
                 return Err(format!(
@@ -53,6 +61,23 @@ pub fn build_validated_registry(cards: &[CardDef], ruleset: &Ruleset) -> Result<
                 ));
             }
         }
+
+        // Validate colors the same way
+        if let Some(color) = card_def.colors.iter().find(|c| !valid_colors.contains(c.as_str())) {
+            let mut sorted_colors: Vec<_> = valid_colors.iter().copied().collect();
+            sorted_colors.sort();
+            return Err(format!(
+                "Card '{}' (ID: {}) references undefined color '{}'. Valid colors: {}",
+                card_def.name,
+                card_def.id,
+                color,
+                sorted_colors.join(", ")
+            ));
+        }
+
+        if let Some(error) = effect_errors(card_def, ruleset, &BuiltinRegistry::default()).into_iter().next() {
+            return Err(error);
+        }
         
         // Parse card ID as u32 if it's numeric, otherwise skip
         if let Ok(card_id) = card_def.id.parse::<u32>() {
@@ -63,35 +88,190 @@ pub fn build_validated_registry(cards: &[CardDef], ruleset: &Ruleset) -> Result<
     Ok(registry)
 }
 
+/// Describe each effect of `card`'s abilities that the engine can't execute:
+/// an unknown effect kind, or params it can't build a command from (e.g. an
+/// unparseable `target`), and each ability or effect condition it can't
+/// evaluate. Such effects are otherwise dropped silently when the ability
+/// triggers, and such conditions never hold. Effects are built against a
+/// fresh game of `ruleset`, with `builtins` the custom builtins the card's
+/// game will have registered.
+pub fn effect_errors(card: &CardDef, ruleset: &Ruleset, builtins: &BuiltinRegistry) -> Vec<String> {
+    let mut state = GameState::from_ruleset(ruleset);
+    // So `coin_flip()` reads a flip instead of failing
+    state.last_coin_flip = Some(crate::model::event::CoinFlip::Heads);
+    let controller = crate::ids::PlayerId(0);
+    let source = CardId(card.id.parse().unwrap_or(0));
+
+    let bad_effects = card.abilities.iter().enumerate()
+        .flat_map(|(index, ability)| ability.effect_specs().into_iter().map(move |spec| (index, spec)))
+        .filter(|(_, spec)| {
+            let bad_expression = spec.params.values()
+                .filter(|value| is_expression(value))
+                .any(|value| evaluate_amount(value, &state, controller, &CardRegistry::new()).is_err());
+            bad_expression || spec_to_ref(source, spec, controller, &state, builtins).is_none()
+        })
+        .map(|(index, spec)| format!(
+            "Card '{}' (ID: {}) ability {} has an effect the engine can't execute: '{}' (unknown effect or invalid params)",
+            card.name, card.id, index, spec.effect
+        ));
+
+    let bad_conditions = card.abilities.iter().enumerate()
+        .flat_map(|(index, ability)| {
+            let effect_conditions = ability.effect_specs().into_iter().filter_map(|spec| spec.condition.clone());
+            ability.condition.clone().into_iter().chain(effect_conditions).map(move |condition| (index, condition))
+        })
+        .filter_map(|(index, condition)| {
+            let error = evaluate_condition(&condition, &state, controller).err()?;
+            Some(format!(
+                "Card '{}' (ID: {}) ability {} has a condition the engine can't evaluate: '{}' ({})",
+                card.name, card.id, index, condition, error.0
+            ))
+        });
+
+    bad_effects.chain(bad_conditions).collect()
+}
+
+/// The params a built-in effect kind reads. Anything else in an ability's
+/// params is ignored by that effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectParams {
+    /// Params without a sensible default (see BUILTIN_EFFECTS.md)
+    pub required: &'static [&'static str],
+    /// Params with a default, plus `duration`, which every effect takes
+    pub optional: &'static [&'static str],
+}
+
+impl EffectParams {
+    const fn new(required: &'static [&'static str], optional: &'static [&'static str]) -> Self {
+        Self { required, optional }
+    }
+
+    fn accepts(&self, param: &str) -> bool {
+        param == "duration" || self.required.contains(&param) || self.optional.contains(&param)
+    }
+}
+
+/// The params `effect_kind` expects, or `None` for scripted, custom and
+/// unknown effects
+pub fn effect_params(effect_kind: &str) -> Option<EffectParams> {
+    let params = match effect_kind {
+        "damage" | "gain_life" => EffectParams::new(&["amount"], &["target"]),
+        "draw" | "lose_life" => EffectParams::new(&["amount"], &["target", "player"]),
+        "mill" => EffectParams::new(&[], &["amount", "target"]),
+        "pump" => EffectParams::new(&[], &["power", "toughness"]),
+        "set_life" => EffectParams::new(&["amount"], &["player"]),
+        "set_stats" => EffectParams::new(&[], &["card", "power", "toughness"]),
+        "grant_keyword" | "remove_keyword" => EffectParams::new(&["keyword"], &["card"]),
+        "gain_resource" | "spend_resource" | "set_resource" => EffectParams::new(&["amount"], &["resource", "player"]),
+        "add_counter" | "remove_counter" => EffectParams::new(&["amount"], &["counter_type", "card"]),
+        "create_token" => EffectParams::new(&[], &["token_type", "zone", "player"]),
+        "move_card" => EffectParams::new(&[], &["card", "from_zone", "to_zone"]),
+        "move_all" => EffectParams::new(&[], &["from_zone", "to_zone", "filter", "target"]),
+        "search" => EffectParams::new(&[], &["max", "zone", "to_zone", "filter", "target"]),
+        "cascade" => EffectParams::new(&[], &["cast", "filter", "target"]),
+        "gain_control" | "tap" | "untap" | "shuffle_into_deck" => EffectParams::new(&[], &["card", "target"]),
+        "transform" => EffectParams::new(&["into"], &["card"]),
+        "discard" => EffectParams::new(&[], &["amount", "mode", "target"]),
+        "discard_chosen_from" => EffectParams::new(&["target"], &[]),
+        "prevent_damage" => EffectParams::new(&[], &["amount", "target", "card", "combat_only"]),
+        "coin_flip" | "reveal_hand" | "extra_turn" => EffectParams::new(&[], &["target"]),
+        "skip_next" => EffectParams::new(&["step"], &["target"]),
+        "counter" => EffectParams::new(&[], &["stack_item"]),
+        _ => return None,
+    };
+    Some(params)
+}
+
+/// Describe each effect of `card`'s abilities whose params don't match
+/// [`effect_params`]: a required param left out (so a default is used) or a
+/// param the effect doesn't read, which is usually a typo
+pub fn param_warnings(card: &CardDef) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (index, ability) in card.abilities.iter().enumerate() {
+        for spec in ability.effect_specs() {
+            let Some(expected) = effect_params(&spec.effect) else {
+                continue;
+            };
+            for param in expected.required.iter().filter(|p| !spec.params.contains_key(**p)) {
+                warnings.push(format!(
+                    "Card '{}' (ID: {}) ability {} effect '{}' is missing required param '{}'",
+                    card.name, card.id, index, spec.effect, param
+                ));
+            }
+            let mut unexpected: Vec<_> = spec.params.keys().filter(|p| !expected.accepts(p)).collect();
+            unexpected.sort();
+            for param in unexpected {
+                let mut known: Vec<_> = expected.required.iter().chain(expected.optional).copied().collect();
+                known.push("duration");
+                warnings.push(format!(
+                    "Card '{}' (ID: {}) ability {} effect '{}' has unexpected param '{}' (expected: {})",
+                    card.name, card.id, index, spec.effect, param, known.join(", ")
+                ));
+            }
+        }
+    }
+    warnings
+}
+
 /// Get a card definition by ID
 pub fn get_card(registry: &CardRegistry, card_id: CardId) -> Option<&CardDef> {
     registry.get(&card_id.0)
 }
 
 /// Generate commands from a card's abilities when an event matches a trigger
+#[allow(clippy::too_many_arguments)]
 pub fn generate_ability_commands(
     card_id: CardId,
     event_trigger: &str,
     controller: crate::ids::PlayerId,
     registry: &CardRegistry,
+    builtins: &BuiltinRegistry,
+    state: &GameState,
+    next_stack_id: &mut u32,
+    next_choice_id: &mut u32,
+) -> Vec<Command> {
+    generate_ability_commands_with_vars(
+        card_id, event_trigger, controller, registry, builtins, state, next_stack_id, next_choice_id, &[],
+    )
+}
+
+/// Same as [`generate_ability_commands`], with each `{name}` in the effect
+/// params replaced by the value `vars` gives for `name`, e.g. the `{delta}`
+/// of a life change trigger
+#[allow(clippy::too_many_arguments)]
+pub fn generate_ability_commands_with_vars(
+    card_id: CardId,
+    event_trigger: &str,
+    controller: crate::ids::PlayerId,
+    registry: &CardRegistry,
+    builtins: &BuiltinRegistry,
+    state: &GameState,
     next_stack_id: &mut u32,
+    next_choice_id: &mut u32,
+    vars: &[(&str, String)],
 ) -> Vec<Command> {
     let mut commands = Vec::new();
     
-    if let Some(card_def) = get_card(registry, card_id) {
+    if let Some(card_def) = get_card(registry, state.definition_of(card_id)) {
         for ability in &card_def.abilities {
-            // Only fire if the trigger matches
-            if ability.trigger == event_trigger {
-                // Generate a command for this ability effect
-                if let Some(cmd) = effect_to_command(
+            // Only fire if the trigger matches and any condition holds.
+            // A condition that fails to parse never fires; `effect_errors` reports it.
+            let condition_met = ability.condition.as_deref()
+                .map(|c| evaluate_condition(c, state, controller).unwrap_or(false))
+                .unwrap_or(true);
+
+            if ability.trigger == event_trigger && condition_met {
+                // Generate a command for this ability's effect(s)
+                commands.extend(ability_to_commands(
                     card_id,
-                    &ability.effect,
-                    &ability.params,
+                    ability,
                     controller,
+                    state,
+                    builtins,
                     next_stack_id,
-                ) {
-                    commands.push(cmd);
-                }
+                    next_choice_id,
+                    vars,
+                ));
             }
         }
     }
@@ -99,17 +279,348 @@ pub fn generate_ability_commands(
     commands
 }
 
-/// Convert a card ability effect into an engine Command
-fn effect_to_command(
+/// Convert a card ability into a single stack push
+///
+/// An ability with one effect pushes that effect directly. An ability whose
+/// `effects` list holds several entries pushes one `EffectRef::Sequence`, so
+/// the parts resolve together and in declared order (separate stack items
+/// would resolve last-in, first-out). Effects that can't be compiled are
+/// dropped; if none remain the ability produces no command.
+///
+/// An effect with its own `condition` is wrapped in `EffectRef::Conditional`
+/// and checked as it resolves, after the effects before it.
+///
+/// If any effect has a `target = "choose:..."` param, a `RequestChoice` for
+/// the controller follows the push. The stack item waits for that choice and
+/// every `_target` placeholder in it is bound to the chosen target.
+#[allow(clippy::too_many_arguments)]
+fn ability_to_commands(
     source: CardId,
-    effect_kind: &str,
-    params: &std::collections::HashMap<String, String>,
+    ability: &CardAbility,
     controller: crate::ids::PlayerId,
+    state: &GameState,
+    builtins: &BuiltinRegistry,
     stack_id: &mut u32,
-) -> Option<Command> {
+    choice_id: &mut u32,
+    vars: &[(&str, String)],
+) -> Vec<Command> {
+    let specs = ability_specs(ability, vars);
+    let Some(effect) = ability_effect(source, &specs, controller, state, builtins) else {
+        return Vec::new();
+    };
+
     let id = *stack_id;
     *stack_id += 1;
 
+    let mut commands = vec![Command::PushStack {
+        item: StackItem {
+            id,
+            source: Some(source),
+            controller,
+            effect,
+        },
+    }];
+
+    let chosen = specs.iter().find_map(|spec| match resolve_target(&spec.params, controller, state) {
+        TargetSpec::Choose(allowed) => Some(allowed),
+        _ => None,
+    });
+    if let Some(allowed) = chosen {
+        let prompt = match allowed {
+            AllowedTargets::AnyCreatureOnField => "Choose a target creature",
+            AllowedTargets::AnyPlayer => "Choose a target player",
+            AllowedTargets::CardInZone(_) => "Choose a card",
+            AllowedTargets::OwnCreatureOnField { .. } => "Choose a creature you control",
+        };
+        commands.push(Command::RequestChoice {
+            player: controller,
+            choice: PendingChoice {
+                id: *choice_id,
+                prompt: prompt.to_string(),
+                kind: ChoiceKind::ChooseTarget { allowed },
+                stack_item: Some(id),
+            },
+        });
+        *choice_id += 1;
+    }
+
+    commands
+}
+
+/// The ability's effect specs with each `{name}` in their params replaced by
+/// the value `vars` gives for `name`
+fn ability_specs(ability: &CardAbility, vars: &[(&str, String)]) -> Vec<EffectSpec> {
+    let mut specs = ability.effect_specs();
+    for spec in &mut specs {
+        for value in spec.params.values_mut() {
+            for (name, var) in vars {
+                *value = value.replace(&format!("{{{}}}", name), var);
+            }
+        }
+    }
+    specs
+}
+
+/// The single effect a list of specs resolves as: one effect directly, or an
+/// `EffectRef::Sequence` of several. Effects that can't be compiled are
+/// dropped; `None` if none remain.
+fn ability_effect(
+    source: CardId,
+    specs: &[EffectSpec],
+    controller: crate::ids::PlayerId,
+    state: &GameState,
+    builtins: &BuiltinRegistry,
+) -> Option<EffectRef> {
+    let mut refs: Vec<EffectRef> = specs
+        .iter()
+        .filter_map(|spec| {
+            let effect = spec_to_ref(source, spec, controller, state, builtins)?;
+            Some(match &spec.condition {
+                Some(condition) => EffectRef::Conditional(condition.clone(), Box::new(effect)),
+                None => effect,
+            })
+        })
+        .collect();
+
+    match refs.len() {
+        0 => None,
+        1 => Some(refs.remove(0)),
+        _ => Some(EffectRef::Sequence(refs)),
+    }
+}
+
+/// The effect of `card`'s mana ability at index `ability`, for resolving it
+/// on the spot. `None` if the card has no such ability, it isn't a mana
+/// ability or none of its effects compile.
+pub fn mana_ability_effect(
+    card: CardId,
+    ability: usize,
+    controller: crate::ids::PlayerId,
+    registry: &CardRegistry,
+    builtins: &BuiltinRegistry,
+    state: &GameState,
+) -> Option<EffectRef> {
+    let ability = get_card(registry, state.definition_of(card))?
+        .abilities.get(ability)
+        .filter(|a| a.mana_ability)?;
+    ability_effect(card, &ability_specs(ability, &[]), controller, state, builtins)
+}
+
+/// Stands in for an expression param while the effect is built; the
+/// `+ index` keeps several expressions in one spec apart
+const EXPRESSION_SENTINEL: i32 = 1_999_999_000;
+
+/// Stands in for the player of a `player`/`target` = "each" or
+/// "all_opponents" param while the effect is built
+const EACH_PLAYER_SENTINEL: u8 = 255;
+
+/// Whether a param value is an expression evaluated as the effect resolves
+/// (see [`evaluate_amount`]) rather than a literal
+fn is_expression(value: &str) -> bool {
+    let value = value.trim();
+    value.contains('(') && value.ends_with(')')
+}
+
+/// Build a spec's effect like [`effect_to_ref`]. Params written as
+/// expressions, e.g. `amount = "count(creatures_controlled_by(controller))"`,
+/// are left in the builtin as `{expression}` placeholders, which the
+/// executor evaluates when the effect resolves. `None` if an expression
+/// param doesn't end up in the builtin (e.g. in a scripted effect).
+///
+/// A `player` or `target` param of "each" or "all_opponents" builds the
+/// effect for one stand-in player as `..._player_each`, wrapped in
+/// `each_player_`/`each_opponent_` so the executor runs it once per matching
+/// player. `None` if the effect has no player to stand in for.
+fn spec_to_ref(
+    source: CardId,
+    spec: &EffectSpec,
+    controller: crate::ids::PlayerId,
+    state: &GameState,
+    builtins: &BuiltinRegistry,
+) -> Option<EffectRef> {
+    let mut params = spec.params.clone();
+    let mut each = None;
+    for key in ["player", "target"] {
+        if let Some(value) = params.get_mut(key) {
+            let scope = match value.as_str() {
+                "each" => "each_player_",
+                "all_opponents" => "each_opponent_",
+                _ => continue,
+            };
+            each = Some(scope);
+            *value = EACH_PLAYER_SENTINEL.to_string();
+        }
+    }
+    let mut placeholders = Vec::new();
+    for (index, value) in params.values_mut().enumerate() {
+        if is_expression(value) {
+            let sentinel = (EXPRESSION_SENTINEL + index as i32).to_string();
+            placeholders.push((sentinel.clone(), format!("{{{}}}", value.trim())));
+            *value = sentinel;
+        }
+    }
+
+    let effect = effect_to_ref(source, &spec.effect, &params, controller, state, builtins)?;
+    let until_end_of_turn = match params.get("duration").map(|d| d.as_str()) {
+        None => false,
+        Some("end_of_turn") => true,
+        Some(_) => return None,
+    };
+    if placeholders.is_empty() && !until_end_of_turn && each.is_none() {
+        return Some(effect);
+    }
+    let EffectRef::Builtin(effect_str) = effect else {
+        return None;
+    };
+    let mut effect_str = effect_str.to_string();
+    for (sentinel, placeholder) in placeholders {
+        if !effect_str.contains(&sentinel) {
+            return None;
+        }
+        effect_str = effect_str.replace(&sentinel, &placeholder);
+    }
+    if let Some(scope) = each {
+        let sentinel = format!("_player_{}", EACH_PLAYER_SENTINEL);
+        if !effect_str.contains(&sentinel) {
+            return None;
+        }
+        effect_str = format!("{}{}", scope, effect_str.replace(&sentinel, "_player_each"));
+    }
+    // Undone by a cleanup at end of turn (see `GameState::end_of_turn`)
+    if until_end_of_turn {
+        effect_str = format!("until_end_of_turn_{}", effect_str);
+    }
+    Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+}
+
+/// A resolved `target` ability param
+enum TargetSpec {
+    /// No `target` param; the effect uses its own defaults
+    Unspecified,
+    Player(crate::ids::PlayerId),
+    /// Target picked by the controller when the ability triggers
+    Choose(AllowedTargets),
+    /// The `target` param didn't parse
+    Invalid,
+}
+
+/// Resolve a `target` param ("self", "opponent", a player ID, "choose:creature", "choose:player")
+fn resolve_target(
+    params: &HashMap<String, String>,
+    controller: crate::ids::PlayerId,
+    state: &GameState,
+) -> TargetSpec {
+    let Some(target) = params.get("target") else {
+        return TargetSpec::Unspecified;
+    };
+
+    match target.as_str() {
+        "self" | "you" | "controller" => TargetSpec::Player(controller),
+        "opponent" => crate::rules::query::opponents_of(state, controller)
+            .first()
+            .map(|p| TargetSpec::Player(*p))
+            .unwrap_or(TargetSpec::Invalid),
+        "choose:creature" => TargetSpec::Choose(AllowedTargets::AnyCreatureOnField),
+        "choose:player" => TargetSpec::Choose(AllowedTargets::AnyPlayer),
+        other => other
+            .parse::<u8>()
+            .map(|p| TargetSpec::Player(crate::ids::PlayerId(p)))
+            .unwrap_or(TargetSpec::Invalid),
+    }
+}
+
+/// Bind a chosen target into every `_target` placeholder of an effect
+pub fn bind_effect_target(effect: &EffectRef, target: &TargetRef) -> EffectRef {
+    match effect {
+        EffectRef::Builtin(effect_str) => match effect_str.strip_suffix("_target") {
+            Some(base) => {
+                let bound = match target {
+                    TargetRef::Player(player) => format!("{}_player_{}", base, player.0),
+                    TargetRef::Card(card) => format!("{}_card_{}", base, card.0),
+                };
+                EffectRef::Builtin(Box::leak(bound.into_boxed_str()))
+            }
+            None => effect.clone(),
+        },
+        EffectRef::Scripted(_) => effect.clone(),
+        EffectRef::Sequence(effects) => {
+            EffectRef::Sequence(effects.iter().map(|e| bind_effect_target(e, target)).collect())
+        }
+        EffectRef::Conditional(condition, effect) => {
+            EffectRef::Conditional(condition.clone(), Box::new(bind_effect_target(effect, target)))
+        }
+    }
+}
+
+/// Bind the cards picked for a `ChooseCards` choice into every `_target`
+/// placeholder of an effect, as `_cards_{id},{id},...`
+pub fn bind_chosen_cards(effect: &EffectRef, cards: &[CardId]) -> EffectRef {
+    match effect {
+        EffectRef::Builtin(effect_str) => match effect_str.strip_suffix("_target") {
+            Some(base) => {
+                let ids: Vec<String> = cards.iter().map(|card| card.0.to_string()).collect();
+                let bound = format!("{}_cards_{}", base, ids.join(","));
+                EffectRef::Builtin(Box::leak(bound.into_boxed_str()))
+            }
+            None => effect.clone(),
+        },
+        EffectRef::Scripted(_) => effect.clone(),
+        EffectRef::Sequence(effects) => {
+            EffectRef::Sequence(effects.iter().map(|e| bind_chosen_cards(e, cards)).collect())
+        }
+        EffectRef::Conditional(condition, effect) => {
+            EffectRef::Conditional(condition.clone(), Box::new(bind_chosen_cards(effect, cards)))
+        }
+    }
+}
+
+/// Follow every move onto a field zone with the moved card's entry modifiers
+///
+/// A card declaring `enters_tapped` gets a `SetTapped`, and one declaring
+/// `enters_with` gets an `AddCounter`, right after its `MoveCard` so the
+/// card is never observable on the field without them. Cards go by the
+/// definition they use in `state`.
+pub fn with_entry_modifiers(commands: Vec<Command>, registry: &CardRegistry, state: &GameState) -> Vec<Command> {
+    let mut result = Vec::with_capacity(commands.len());
+    for command in commands {
+        let entering = match &command {
+            Command::MoveCard { card, to, .. } if to.0.starts_with("field") => {
+                registry.get(&state.definition_of(*card).0).map(|def| (*card, def))
+            }
+            _ => None,
+        };
+        result.push(command);
+
+        if let Some((card, def)) = entering {
+            if def.enters_tapped {
+                result.push(Command::SetTapped { card, tapped: true });
+            }
+            if let Some(entry) = &def.enters_with {
+                result.push(Command::AddCounter {
+                    card,
+                    counter_type: entry.counter.clone(),
+                    amount: entry.amount,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Convert a single card ability effect into an `EffectRef`
+fn effect_to_ref(
+    source: CardId,
+    effect_kind: &str,
+    params: &std::collections::HashMap<String, String>,
+    controller: crate::ids::PlayerId,
+    state: &GameState,
+    builtins: &BuiltinRegistry,
+) -> Option<EffectRef> {
+    let target = resolve_target(params, controller, state);
+    if matches!(target, TargetSpec::Invalid) {
+        return None;
+    }
+
     // Check if this is a scripted effect (indicated by "script:" prefix)
     if effect_kind.starts_with("script:") {
         let script_name = effect_kind.strip_prefix("script:").unwrap_or(effect_kind);
@@ -119,14 +630,7 @@ fn effect_to_command(
             return None;
         }
         
-        return Some(Command::PushStack {
-            item: StackItem {
-                id,
-                source: Some(source),
-                controller,
-                effect: EffectRef::Scripted(script_name.to_string()),
-            },
-        });
+        return Some(EffectRef::Scripted(script_name.to_string()));
     }
 
     match effect_kind {
@@ -135,48 +639,57 @@ fn effect_to_command(
                 .and_then(|s| s.parse::<i32>().ok())
                 .unwrap_or(1);
             
-            let effect_str = Box::leak(format!("damage_{}", amount).into_boxed_str());
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("damage_{}_player_{}", amount, player.0),
+                TargetSpec::Choose(_) => format!("damage_{}_target", amount),
+                _ => format!("damage_{}", amount),
+            };
+            let effect_str = Box::leak(effect_str.into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "draw" => {
             let amount = params.get("amount")
                 .and_then(|s| s.parse::<u32>().ok())
                 .unwrap_or(1);
             
-            let effect_str = Box::leak(format!("draw_{}", amount).into_boxed_str());
-            
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("draw_{}_player_{}", amount, player.0),
+                TargetSpec::Choose(_) => format!("draw_{}_target", amount),
+                _ => match params.get("player").and_then(|s| s.parse::<u8>().ok()) {
+                    Some(player) => format!("draw_{}_player_{}", amount, player),
+                    None => format!("draw_{}", amount),
                 },
-            })
+            };
+            let effect_str = Box::leak(effect_str.into_boxed_str());
+            
+            Some(EffectRef::Builtin(effect_str))
+        }
+        "mill" => {
+            let amount = params.get("amount")
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(1);
+
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("mill_{}_player_{}", amount, player.0),
+                TargetSpec::Choose(_) => format!("mill_{}_target", amount),
+                _ => format!("mill_{}", amount),
+            };
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
         }
         "gain_life" => {
             let amount = params.get("amount")
                 .and_then(|s| s.parse::<i32>().ok())
                 .unwrap_or(1);
             
-            let effect_str = Box::leak(format!("gain_life_{}", amount).into_boxed_str());
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("gain_life_{}_player_{}", amount, player.0),
+                TargetSpec::Choose(_) => format!("gain_life_{}_target", amount),
+                _ => format!("gain_life_{}", amount),
+            };
+            let effect_str = Box::leak(effect_str.into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "pump" => {
             let power = params.get("power")
@@ -188,33 +701,25 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("pump_{}_{}", power, toughness).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "lose_life" => {
             let amount = params.get("amount")
                 .and_then(|s| s.parse::<i32>().ok())
                 .unwrap_or(1);
-            let player = params.get("player")
-                .and_then(|s| s.parse::<u8>().ok())
-                .unwrap_or(controller.0);
-            
-            let effect_str = Box::leak(format!("lose_life_{}_player_{}", amount, player).into_boxed_str());
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("lose_life_{}_player_{}", amount, player.0),
+                TargetSpec::Choose(_) => format!("lose_life_{}_target", amount),
+                _ => {
+                    let player = params.get("player")
+                        .and_then(|s| s.parse::<u8>().ok())
+                        .unwrap_or(controller.0);
+                    format!("lose_life_{}_player_{}", amount, player)
+                }
+            };
+            let effect_str = Box::leak(effect_str.into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "set_life" => {
             let amount = params.get("amount")
@@ -226,14 +731,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("set_life_{}_player_{}", amount, player).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "set_stats" => {
             let power = params.get("power")
@@ -248,14 +746,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("set_stats_{}_{}_{}", card, power, toughness).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "grant_keyword" => {
             let keyword = params.get("keyword")
@@ -271,14 +762,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("grant_keyword_{}_{}", card, keyword).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "remove_keyword" => {
             let keyword = params.get("keyword")
@@ -294,14 +778,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("remove_keyword_{}_{}", card, keyword).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "gain_resource" => {
             let resource = params.get("resource")
@@ -316,14 +793,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("gain_resource_{}_{}_{}", player, resource, amount).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "spend_resource" => {
             let resource = params.get("resource")
@@ -338,14 +808,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("spend_resource_{}_{}_{}", player, resource, amount).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "set_resource" => {
             let resource = params.get("resource")
@@ -360,14 +823,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("set_resource_{}_{}_{}", player, resource, amount).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "add_counter" => {
             let counter_type = params.get("counter_type")
@@ -382,14 +838,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("add_counter_{}_{}_{}", card, counter_type, amount).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "remove_counter" => {
             let counter_type = params.get("counter_type")
@@ -404,14 +853,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("remove_counter_{}_{}_{}", card, counter_type, amount).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "create_token" => {
             let token_type = params.get("token_type")
@@ -426,14 +868,7 @@ fn effect_to_command(
             
             let effect_str = Box::leak(format!("create_token_{}_{}_{}", player, token_type, zone).into_boxed_str());
             
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
-                },
-            })
+            Some(EffectRef::Builtin(effect_str))
         }
         "move_card" => {
             let card = params.get("card")
@@ -447,15 +882,225 @@ fn effect_to_command(
                 .unwrap_or("field");
             
             let effect_str = Box::leak(format!("move_card_{}_{}_{}", card, from_zone, to_zone).into_boxed_str());
-            
-            Some(Command::PushStack {
-                item: StackItem {
-                    id,
-                    source: Some(source),
-                    controller,
-                    effect: EffectRef::Builtin(effect_str),
+
+            Some(EffectRef::Builtin(effect_str))
+        }
+        "move_all" => {
+            let from_zone = params.get("from_zone")
+                .map(|s| s.as_str())
+                .unwrap_or("field");
+            let to_zone = params.get("to_zone")
+                .map(|s| s.as_str())
+                .unwrap_or("graveyard");
+            // Normalize the filter so the executor reads back exactly what was checked here
+            let filter = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .ok()?
+                .to_string();
+
+            // Without a target every player's zones are swept
+            let mut effect_str = format!("move_all_{}_to_{}", from_zone, to_zone);
+            match target {
+                TargetSpec::Player(player) => effect_str.push_str(&format!("_player_{}", player.0)),
+                TargetSpec::Unspecified => {}
+                _ => return None,
+            }
+            if !filter.is_empty() {
+                effect_str.push_str(&format!("_where_{}", filter));
+            }
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "search" => {
+            let max = params.get("max")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1);
+            let zone = params.get("zone")
+                .map(|s| s.as_str())
+                .unwrap_or("deck");
+            let to_zone = params.get("to_zone")
+                .map(|s| s.as_str())
+                .unwrap_or("hand");
+            let filter = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .ok()?
+                .to_string();
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+
+            let mut effect_str = format!("search_{}_player_{}_{}_to_{}", max, player.0, zone, to_zone);
+            if !filter.is_empty() {
+                effect_str.push_str(&format!("_where_{}", filter));
+            }
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "cascade" => {
+            let cast = params.get("cast").map(|s| s.as_str()).unwrap_or("true") != "false";
+            let filter = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .ok()?
+                .to_string();
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+
+            let mut effect_str = format!("cascade_player_{}_{}", player.0, if cast { "cast" } else { "hand" });
+            if !filter.is_empty() {
+                effect_str.push_str(&format!("_where_{}", filter));
+            }
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "gain_control" => {
+            let card = match target {
+                TargetSpec::Choose(AllowedTargets::AnyCreatureOnField) => "target".to_string(),
+                TargetSpec::Unspecified => params.get("card")?.parse::<u32>().ok()?.to_string(),
+                _ => return None,
+            };
+            let effect_str = format!("gain_control_player_{}_{}", controller.0, card);
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "tap" | "untap" => {
+            let effect_str = match target {
+                TargetSpec::Choose(AllowedTargets::AnyCreatureOnField) => format!("{}_target", effect_kind),
+                TargetSpec::Unspecified => {
+                    let card = params.get("card")
+                        .and_then(|s| s.parse::<u32>().ok())
+                        .unwrap_or(source.0);
+                    format!("{}_{}", effect_kind, card)
+                }
+                _ => return None,
+            };
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "transform" => {
+            let card = params.get("card")
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(source.0);
+            let into = params.get("into")?.parse::<u32>().ok()?;
+
+            let effect_str = format!("transform_{}_into_{}", card, into);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "discard" => {
+            let amount = params.get("amount")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(1);
+            let player = match target {
+                TargetSpec::Player(player) => player.0,
+                TargetSpec::Unspecified => controller.0,
+                _ => return None,
+            };
+            let mode = match params.get("mode").map(|s| s.as_str()) {
+                Some("random") => "random",
+                Some("choose") | None => "choose",
+                Some(_) => return None,
+            };
+
+            let effect_str = format!("discard_{}_{}_player_{}", mode, amount, player);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "discard_chosen_from" => {
+            let TargetSpec::Player(player) = target else {
+                return None;
+            };
+
+            let effect_str = format!("discard_chosen_from_player_{}", player.0);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "prevent_damage" => {
+            let amount = params.get("amount")
+                .and_then(|s| s.parse::<i32>().ok())
+                .unwrap_or(1);
+            let kind = match params.get("combat_only").map(|s| s.as_str()) {
+                Some("true") => "prevent_combat_damage",
+                _ => "prevent_damage",
+            };
+
+            let effect_str = match target {
+                TargetSpec::Player(player) => format!("{}_{}_player_{}", kind, amount, player.0),
+                TargetSpec::Choose(_) => format!("{}_{}_target", kind, amount),
+                _ => match params.get("card").and_then(|s| s.parse::<u32>().ok()) {
+                    Some(card) => format!("{}_{}_card_{}", kind, amount, card),
+                    None => format!("{}_{}", kind, amount),
                 },
-            })
+            };
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "coin_flip" => {
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+
+            let effect_str = format!("coin_flip_player_{}", player.0);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "reveal_hand" => {
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+
+            let effect_str = format!("reveal_hand_player_{}", player.0);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "extra_turn" => {
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+
+            let effect_str = format!("extra_turn_player_{}", player.0);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "skip_next" => {
+            let player = match target {
+                TargetSpec::Player(player) => player,
+                TargetSpec::Unspecified => controller,
+                _ => return None,
+            };
+            let step = params.get("step").filter(|s| !s.is_empty())?;
+
+            let effect_str = format!("skip_next_{}_player_{}", step, player.0);
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "counter" => {
+            // Without an explicit item, counter whatever this ability responded to
+            let effect_str = match params.get("stack_item") {
+                Some(id) => format!("counter_{}", id.parse::<u32>().ok()?),
+                None => "counter_top".to_string(),
+            };
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        "shuffle_into_deck" => {
+            let effect_str = match target {
+                TargetSpec::Choose(AllowedTargets::AnyCreatureOnField) => "shuffle_into_deck_target".to_string(),
+                TargetSpec::Unspecified => {
+                    let card = params.get("card")
+                        .and_then(|s| s.parse::<u32>().ok())
+                        .unwrap_or(source.0);
+                    format!("shuffle_into_deck_{}", card)
+                }
+                // Players can't be shuffled into a deck
+                _ => return None,
+            };
+
+            Some(EffectRef::Builtin(Box::leak(effect_str.into_boxed_str())))
+        }
+        // Effects an embedder registered, written out in full (e.g. "poison_3")
+        custom if builtins.handles(custom) => {
+            Some(EffectRef::Builtin(Box::leak(custom.to_string().into_boxed_str())))
         }
         _ => {
             // Unknown effect type - skip
@@ -492,7 +1137,7 @@ pub fn parse_card_stat_i32(card_def: &CardDef, stat_key: &str) -> Result<i32, St
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rules::schema::{Keyword, Ruleset, GameInfo, PlayerRules, TurnStructure};
+    use crate::rules::schema::{ColorDef, Keyword, Ruleset, GameInfo, PlayerRules, TurnStructure};
     
     fn minimal_ruleset() -> Ruleset {
         Ruleset {
@@ -513,12 +1158,19 @@ mod tests {
                 max_deck_size: 60,
                 mulligan_rule: "none".to_string(),
                 first_player_rule: "random".to_string(),
+                second_player_extra_cards: 0,
+                second_player_starting_resources: HashMap::new(),
+                allow_lethal_life_payment: false,
             },
             zones: vec![],
             resources: vec![],
             turn: TurnStructure {
                 priority_system: true,
                 skip_first_turn_draw_for_first_player: false,
+                stack_responses: false,
+                max_draws_per_turn: None,
+                excess_draw_policy: Default::default(),
+                play_limits: Default::default(),
                 phases: vec![],
             },
             actions: vec![],
@@ -540,6 +1192,7 @@ mod tests {
                     description: "Can be played at instant speed".to_string(),
                 },
             ],
+            colors: vec![ColorDef { id: "red".to_string(), name: "Red".to_string() }],
             win_conditions: vec![],
             loss_conditions: vec![],
             cards: vec![],
@@ -558,7 +1211,14 @@ mod tests {
             abilities: vec![],
             script_path: None,
             keywords: vec!["flying".to_string()],
+            colors: vec![],
             stats: std::collections::HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
         
         let result = build_validated_registry(&[card.clone()], &ruleset);
@@ -582,13 +1242,102 @@ mod tests {
             abilities: vec![],
             script_path: None,
             keywords: vec!["invalid_keyword".to_string()],
+            colors: vec![],
             stats: std::collections::HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
         
         let result = build_validated_registry(&[card], &ruleset);
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("undefined keyword"));
     }
+
+    #[test]
+    fn test_validate_unknown_effect() {
+        let ruleset = minimal_ruleset();
+        let card: CardDef = toml::from_str(r#"
+            id = "1"
+            name = "Test Card"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "draw"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "teleport"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "count(creatures_controlled_by(self))" }
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "count(lands_controlled_by(self))" }
+        "#).unwrap();
+
+        let errors = effect_errors(&card, &ruleset, &BuiltinRegistry::default());
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[1].contains("ability 3"), "{}", errors[1]);
+        assert!(errors[0].contains("ability 1") && errors[0].contains("'teleport'"), "{}", errors[0]);
+        let err = build_validated_registry(&[card], &ruleset).unwrap_err();
+        assert!(err.contains("'teleport'"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_malformed_conditions() {
+        let ruleset = minimal_ruleset();
+        let card: CardDef = toml::from_str(r#"
+            id = "1"
+            name = "Test Card"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            condition = "life_of(opponent) <= 10 and coin_flip() == heads"
+            effect = "draw"
+
+            [[abilities]]
+            trigger = "on_play"
+            condition = "lif_of(opponent) <= 10"
+            effect = "draw"
+
+            [[abilities]]
+            trigger = "on_play"
+
+            [[abilities.effects]]
+            effect = "draw"
+            condition = "hand_size(self) = 0"
+        "#).unwrap();
+
+        let errors = effect_errors(&card, &ruleset, &BuiltinRegistry::default());
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("'Test Card'") && errors[0].contains("ability 1") && errors[0].contains("'lif_of"), "{}", errors[0]);
+        assert!(errors[1].contains("ability 2") && errors[1].contains("no comparison operator"), "{}", errors[1]);
+        let err = build_validated_registry(&[card], &ruleset).unwrap_err();
+        assert!(err.contains("can't evaluate"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_card_colors() {
+        let ruleset = minimal_ruleset();
+        let mut card: CardDef = toml::from_str(
+            "id = \"1\"\nname = \"Test Card\"\ncard_type = \"creature\"\ncolors = [\"red\"]",
+        ).unwrap();
+        assert!(build_validated_registry(&[card.clone()], &ruleset).is_ok());
+
+        card.colors.push("purple".to_string());
+        let err = build_validated_registry(&[card], &ruleset).unwrap_err();
+        assert!(err.contains("undefined color 'purple'"), "{}", err);
+    }
     
     #[test]
     fn test_card_has_keyword() {
@@ -601,7 +1350,14 @@ mod tests {
             abilities: vec![],
             script_path: None,
             keywords: vec!["flying".to_string(), "quick".to_string()],
+            colors: vec![],
             stats: std::collections::HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
         
         assert!(card_has_keyword(&card, "flying"));
@@ -624,7 +1380,14 @@ mod tests {
             abilities: vec![],
             script_path: None,
             keywords: vec![],
+            colors: vec![],
             stats,
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
         
         assert_eq!(get_card_stat(&card, "power"), Some(&"3".to_string()));
@@ -648,7 +1411,14 @@ mod tests {
             abilities: vec![],
             script_path: None,
             keywords: vec![],
+            colors: vec![],
             stats,
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
         
         // Valid stat
@@ -662,4 +1432,262 @@ mod tests {
         assert!(parse_card_stat_i32(&card, "invalid").is_err());
         assert!(parse_card_stat_i32(&card, "invalid").unwrap_err().contains("invalid integer value"));
     }
+
+    #[test]
+    fn test_multi_effect_ability_resolves_in_declared_order() {
+        let card: CardDef = toml::from_str(r#"
+            id = "42"
+            name = "Searing Insight"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+
+            [[abilities.effects]]
+            effect = "damage"
+            params = { amount = "2" }
+
+            [[abilities.effects]]
+            effect = "gain_life"
+            params = { amount = "3" }
+        "#).unwrap();
+
+        let registry = build_registry(&[card]);
+        let state = crate::state::gamestate::GameState::from_ruleset(&minimal_ruleset());
+        let mut next_stack_id = 1;
+        let mut next_choice_id = 1;
+        let commands = generate_ability_commands(
+            CardId(42),
+            "on_play",
+            crate::ids::PlayerId(0),
+            &registry,
+            &BuiltinRegistry::default(),
+            &state,
+            &mut next_stack_id,
+            &mut next_choice_id,
+        );
+
+        // One stack item holding both effects, in order
+        assert_eq!(commands.len(), 1);
+        let effect = match &commands[0] {
+            Command::PushStack { item } => item.effect.clone(),
+            other => panic!("Expected PushStack, got {:?}", other),
+        };
+        match &effect {
+            EffectRef::Sequence(parts) => {
+                assert!(matches!(parts.as_slice(),
+                    [EffectRef::Builtin("damage_2"), EffectRef::Builtin("gain_life_3")]));
+            }
+            other => panic!("Expected Sequence, got {:?}", other),
+        }
+
+        // Resolving produces the effects' commands in declared order
+        let resolved = crate::engine::effect_executor::execute_effect(
+            &effect,
+            Some(CardId(42)),
+            crate::ids::PlayerId(0),
+            &state,
+            None,
+        ).unwrap();
+        assert_eq!(resolved.len(), 2);
+        assert!(matches!(resolved[0], Command::DealDamage { amount: 2, .. }));
+        assert!(matches!(resolved[1], Command::ChangeLife { delta: 3, .. }));
+    }
+
+    #[test]
+    fn test_single_effect_ability_is_not_wrapped() {
+        let card: CardDef = toml::from_str(r#"
+            id = "43"
+            name = "Spark"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "1" }
+        "#).unwrap();
+
+        let registry = build_registry(&[card]);
+        let state = crate::state::gamestate::GameState::from_ruleset(&minimal_ruleset());
+        let mut next_stack_id = 1;
+        let mut next_choice_id = 1;
+        let commands = generate_ability_commands(
+            CardId(43),
+            "on_play",
+            crate::ids::PlayerId(0),
+            &registry,
+            &BuiltinRegistry::default(),
+            &state,
+            &mut next_stack_id,
+            &mut next_choice_id,
+        );
+
+        assert_eq!(commands.len(), 1);
+        assert!(matches!(&commands[0],
+            Command::PushStack { item } if matches!(item.effect, EffectRef::Builtin("damage_1"))));
+    }
+
+    #[test]
+    fn test_ability_condition_gates_trigger() {
+        let card: CardDef = toml::from_str(r#"
+            id = "44"
+            name = "Finishing Blow"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "3" }
+            condition = "life_of(opponent) <= 10"
+        "#).unwrap();
+        let registry = build_registry(&[card]);
+        let mut state = crate::state::gamestate::GameState::from_ruleset(&minimal_ruleset());
+        let mut next_stack_id = 1;
+        let mut next_choice_id = 1;
+
+        // Opponent at 20 life: condition fails, nothing fires
+        let commands = generate_ability_commands(
+            CardId(44), "on_play", crate::ids::PlayerId(0), &registry, &BuiltinRegistry::default(), &state, &mut next_stack_id, &mut next_choice_id,
+        );
+        assert!(commands.is_empty());
+
+        // Opponent at 10 life: condition holds
+        state.players[1].life = 10;
+        let commands = generate_ability_commands(
+            CardId(44), "on_play", crate::ids::PlayerId(0), &registry, &BuiltinRegistry::default(), &state, &mut next_stack_id, &mut next_choice_id,
+        );
+        assert_eq!(commands.len(), 1);
+    }
+
+    #[test]
+    fn test_target_opponent_damages_opponent() {
+        let card: CardDef = toml::from_str(r#"
+            id = "45"
+            name = "Shock"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "2", target = "opponent" }
+        "#).unwrap();
+        let registry = build_registry(&[card]);
+        let state = crate::state::gamestate::GameState::from_ruleset(&minimal_ruleset());
+        let mut next_stack_id = 1;
+        let mut next_choice_id = 1;
+
+        let commands = generate_ability_commands(
+            CardId(45), "on_play", crate::ids::PlayerId(0), &registry, &BuiltinRegistry::default(), &state, &mut next_stack_id, &mut next_choice_id,
+        );
+        assert_eq!(commands.len(), 1);
+        let effect = match &commands[0] {
+            Command::PushStack { item } => item.effect.clone(),
+            other => panic!("Expected PushStack, got {:?}", other),
+        };
+
+        let resolved = crate::engine::effect_executor::execute_effect(
+            &effect, Some(CardId(45)), crate::ids::PlayerId(0), &state, None,
+        ).unwrap();
+        assert!(matches!(resolved.as_slice(),
+            [Command::DealDamage { target: TargetRef::Player(crate::ids::PlayerId(1)), amount: 2, .. }]));
+    }
+
+    #[test]
+    fn test_target_choose_creature_requests_choice() {
+        let card: CardDef = toml::from_str(r#"
+            id = "46"
+            name = "Pinpoint Bolt"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "3", target = "choose:creature" }
+        "#).unwrap();
+        let registry = build_registry(&[card]);
+        let state = crate::state::gamestate::GameState::from_ruleset(&minimal_ruleset());
+        let mut next_stack_id = 7;
+        let mut next_choice_id = 1;
+
+        let commands = generate_ability_commands(
+            CardId(46), "on_play", crate::ids::PlayerId(0), &registry, &BuiltinRegistry::default(), &state, &mut next_stack_id, &mut next_choice_id,
+        );
+        assert_eq!(commands.len(), 2);
+        let effect = match &commands[0] {
+            Command::PushStack { item } => item.effect.clone(),
+            other => panic!("Expected PushStack, got {:?}", other),
+        };
+        match &commands[1] {
+            Command::RequestChoice { player, choice } => {
+                assert_eq!(*player, crate::ids::PlayerId(0));
+                assert_eq!(choice.stack_item, Some(7));
+                assert!(matches!(choice.kind,
+                    ChoiceKind::ChooseTarget { allowed: AllowedTargets::AnyCreatureOnField }));
+            }
+            other => panic!("Expected RequestChoice, got {:?}", other),
+        }
+        assert_eq!(next_choice_id, 2);
+
+        // Binding the chosen creature makes the effect resolvable
+        let bound = bind_effect_target(&effect, &TargetRef::Card(CardId(99)));
+        assert!(matches!(bound, EffectRef::Builtin("damage_3_card_99")));
+    }
+
+    #[test]
+    fn test_each_player_and_all_opponents_run_once_per_player() {
+        let cards: Vec<CardDef> = [
+            r#"
+            id = "47"
+            name = "Howling Mine"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "draw"
+            params = { amount = "1", player = "each" }
+            "#,
+            r#"
+            id = "48"
+            name = "Soul Siphon"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "lose_life"
+            params = { amount = "2", target = "all_opponents" }
+            "#,
+        ].iter().map(|card| toml::from_str(card).unwrap()).collect();
+        let registry = build_registry(&cards);
+        let mut rules = minimal_ruleset();
+        rules.players.min_players = 3;
+        rules.players.max_players = 3;
+        let state = crate::state::gamestate::GameState::from_ruleset(&rules);
+        let resolve = |card: u32| {
+            let commands = generate_ability_commands(
+                CardId(card), "on_play", crate::ids::PlayerId(1), &registry, &BuiltinRegistry::default(), &state, &mut 1, &mut 1,
+            );
+            let effect = match &commands[0] {
+                Command::PushStack { item } => item.effect.clone(),
+                other => panic!("Expected PushStack, got {:?}", other),
+            };
+            crate::engine::effect_executor::execute_effect(&effect, Some(CardId(card)), crate::ids::PlayerId(1), &state, None)
+                .unwrap()
+        };
+
+        let draws: Vec<u8> = resolve(47).iter()
+            .map(|c| match c {
+                Command::DrawCards { player, count: 1 } => player.0,
+                other => panic!("Expected DrawCards, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(draws, vec![1, 2, 0]);
+
+        let losses: Vec<u8> = resolve(48).iter()
+            .map(|c| match c {
+                Command::ChangeLife { player, delta: -2 } => player.0,
+                other => panic!("Expected ChangeLife, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(losses, vec![2, 0]);
+    }
 }
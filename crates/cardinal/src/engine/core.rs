@@ -1,38 +1,132 @@
 use crate::{
-    error::{EngineError, LegalityError},
-    ids::PlayerId,
-    model::action::Action,
+    error::{CardinalError, EngineError, LegalityError},
+    ids::{CardId, PlayerId},
+    model::action::{Action, TargetRef},
+    model::command::{ChoiceKind, Command, PendingChoice, StackItem, PLACEHOLDER_ID},
     model::event::Event,
-    rules::schema::Ruleset,
+    rules::schema::{EmptiesAt, ExcessDrawPolicy, Ruleset},
     state::gamestate::GameState,
     engine::scripting::RhaiEngine,
+    engine::effect_executor::BuiltinRegistry,
 };
+use serde::{Deserialize, Serialize};
 
 pub struct GameEngine {
     pub rules: Ruleset,
     pub state: GameState,
     pub cards: crate::engine::cards::CardRegistry,
     pub scripting: RhaiEngine,
+    /// Builtin effects the embedder added (see [`BuiltinRegistry::register`])
+    pub builtins: BuiltinRegistry,
     seed: u64,
+    pub(crate) next_choice_id: u32,
+    pub(crate) next_stack_id: u32,
+    /// What `timeout` does when a player's clock runs out
+    pub on_timeout: TimeoutPolicy,
+    /// What `apply_action` does when it leaves a game nobody can act in
+    pub on_deadlock: DeadlockPolicy,
+    /// Actions applied and events emitted so far, for `result`
+    action_count: u32,
+    event_count: u32,
+    /// Where the recorded history starts from, taken when the first step
+    /// after it is applied, and every step since, for `undo`
+    history_origin: Option<Box<HistoryOrigin>>,
+    history: Vec<HistoryStep>,
+    /// Whether applied steps are added to the history (see `without_history`)
+    recording: bool,
+}
+
+/// The engine as it was before the first step of its history
+#[derive(Clone)]
+struct HistoryOrigin {
+    state: GameState,
     next_choice_id: u32,
     next_stack_id: u32,
+    action_count: u32,
+    event_count: u32,
+}
+
+/// One successful call that changed the game, replayed by `undo`
+#[derive(Debug, Clone)]
+enum HistoryStep {
+    Action(PlayerId, Action),
+    ResolveTop,
 }
 
 pub struct StepResult {
     pub events: Vec<Event>,
 }
 
+/// The outcome of a finished game, for tournament or ladder records
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    /// `None` for a draw
+    pub winner: Option<PlayerId>,
+    pub reason: String,
+    /// The turn the game ended on
+    pub turns: u32,
+    /// Each player's life total at the end, in seat order
+    pub final_life: Vec<i32>,
+    /// Actions applied, including the one that ended the game
+    pub actions: u32,
+    /// Events emitted, including those of the opening sequence
+    pub events: u32,
+    /// The seed the game was played with
+    pub seed: u64,
+}
+
+/// How the engine acts for a player whose clock has expired
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeoutPolicy {
+    /// Pass priority on the player's behalf
+    #[default]
+    AutoPass,
+    /// Concede the game for the player
+    AutoConcede,
+}
+
+/// How the engine handles a game that hasn't ended but in which no player
+/// has a legal action (see `check_invariants`), which points at a gap in the
+/// rules, e.g. a target prompt nothing can answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadlockPolicy {
+    /// Skip the unanswerable choice, dropping the stack item waiting on it,
+    /// and carry on
+    #[default]
+    Lenient,
+    /// Fail the action that led there. The action stays applied so the
+    /// stuck state can be inspected.
+    Strict,
+}
+
 impl GameEngine {
     pub fn new(rules: Ruleset, seed: u64, initial_state: GameState) -> Self {
         let cards = crate::engine::cards::build_registry(&rules.cards);
         let scripting = RhaiEngine::new();
-        Self { rules, state: initial_state, cards, scripting, seed, next_choice_id: 1, next_stack_id: 1 }
+        Self {
+            rules,
+            state: initial_state,
+            cards,
+            scripting,
+            builtins: BuiltinRegistry::new(),
+            seed,
+            next_choice_id: 1,
+            next_stack_id: 1,
+            on_timeout: TimeoutPolicy::default(),
+            on_deadlock: DeadlockPolicy::default(),
+            action_count: 0,
+            event_count: 0,
+            history_origin: None,
+            history: Vec::new(),
+            recording: true,
+        }
     }
 
     /// Build a GameEngine directly from a `Ruleset`. This will create a minimal GameState
     /// via `GameState::from_ruleset`.
     pub fn from_ruleset(rules: Ruleset, seed: u64) -> Self {
-        let initial = GameState::from_ruleset(&rules);
+        let mut initial = GameState::from_ruleset(&rules);
+        initial.rng = crate::util::rng::GameRng::new(seed);
         let cards = crate::engine::cards::build_registry(&rules.cards);
         let scripting = RhaiEngine::new();
         
@@ -41,13 +135,168 @@ impl GameEngine {
         // initialization step at a higher level (e.g., in cardinal-cli or a web frontend).
         // This keeps file I/O out of the engine core.
         
-        Self { rules, state: initial, cards, scripting, seed, next_choice_id: 1, next_stack_id: 1 }
+        Self {
+            rules,
+            state: initial,
+            cards,
+            scripting,
+            builtins: BuiltinRegistry::new(),
+            seed,
+            next_choice_id: 1,
+            next_stack_id: 1,
+            on_timeout: TimeoutPolicy::default(),
+            on_deadlock: DeadlockPolicy::default(),
+            action_count: 0,
+            event_count: 0,
+            history_origin: None,
+            history: Vec::new(),
+            recording: true,
+        }
+    }
+
+    /// Set the policy `timeout` applies
+    pub fn with_timeout_policy(mut self, policy: TimeoutPolicy) -> Self {
+        self.on_timeout = policy;
+        self
+    }
+
+    /// Set the policy `apply_action` applies to a deadlocked game
+    pub fn with_deadlock_policy(mut self, policy: DeadlockPolicy) -> Self {
+        self.on_deadlock = policy;
+        self
+    }
+
+    /// Called by a server when `player`'s clock expires; applies `on_timeout`
+    /// as if the player had acted. Nobody may pass while a choice is pending,
+    /// so auto-pass gives the chooser's first legal answer instead (see
+    /// `legal_actions`). Otherwise it fails if the player doesn't currently
+    /// hold priority.
+    pub fn timeout(&mut self, player: PlayerId) -> Result<StepResult, EngineError> {
+        let action = match self.on_timeout {
+            TimeoutPolicy::AutoPass => self.state.pending_choice.as_ref()
+                .filter(|choice| self.chooser(choice) == player)
+                .and_then(|_| self.legal_actions(player).into_iter().next())
+                .filter(|action| !matches!(action, Action::ActivateAbility { .. }))
+                .unwrap_or(Action::PassPriority),
+            TimeoutPolicy::AutoConcede => Action::Concede,
+        };
+        self.apply_action(player, action)
+    }
+
+    /// The seed this engine was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Run the opening sequence (shuffle, first player, starting hands) on the
+    /// engine's state using its seed, returning the setup events
+    pub fn start_game(&mut self) -> StepResult {
+        let (state, events) = crate::engine::init::initialize_game_with_events(
+            self.state.clone(),
+            &self.rules,
+            self.seed,
+        );
+        self.state = state;
+        self.event_count += events.len() as u32;
+        StepResult { events }
     }
 
-    pub fn legal_actions(&self, _player: PlayerId) -> Vec<Action> {
-        // Start simple: implement legality later in engine/legality.rs
-        // Return only actions that make sense (PassPriority, PlayCard if allowed, etc).
-        vec![Action::PassPriority]
+    /// The result of the game once it has ended, `None` while it's still going
+    pub fn result(&self) -> Option<GameResult> {
+        let end = self.state.ended.as_ref()?;
+        Some(GameResult {
+            winner: end.winner,
+            reason: end.reason.clone(),
+            turns: self.state.turn.number,
+            final_life: self.state.players.iter().map(|p| p.life).collect(),
+            actions: self.action_count,
+            events: self.event_count,
+            seed: self.seed,
+        })
+    }
+
+    /// Passing priority, playing cards from hand and activating mana
+    /// abilities that `player` may do right now
+    ///
+    /// While a choice is pending only mana abilities and answers are legal:
+    /// the player answering it gets every valid answer (each legal target,
+    /// each order of the triggers to order, or each selection of cards)
+    /// followed by their mana abilities, and everyone else gets none.
+    pub fn legal_actions(&self, player: PlayerId) -> Vec<Action> {
+        if let Some(choice) = &self.state.pending_choice {
+            if player != self.chooser(choice) {
+                return Vec::new();
+            }
+            let answers: Vec<Action> = match &choice.kind {
+                ChoiceKind::ChooseTarget { .. } => self.legal_targets(choice).into_iter()
+                    .map(|target| Action::ChooseTarget { choice_id: choice.id, target })
+                    .collect(),
+                ChoiceKind::OrderTriggers { items } => permutations(items).into_iter()
+                    .map(|order| Action::OrderTriggers { choice_id: choice.id, order })
+                    .collect(),
+                ChoiceKind::ChooseCards { zone, filter, max } => {
+                    let matching: Vec<CardId> = self.state.zones.iter()
+                        .filter(|z| z.id == *zone)
+                        .flat_map(|z| z.cards.iter().copied())
+                        .filter(|card| filter.matches_card(*card, &self.cards, &self.state))
+                        .collect();
+                    selections(&matching, *max).into_iter()
+                        .map(|cards| Action::ChooseCards { choice_id: choice.id, cards })
+                        .collect()
+                }
+            };
+            return answers.into_iter().chain(self.mana_activations(player)).collect();
+        }
+
+        let hand_id = format!("hand@{}", player.0);
+        let plays = self.state.zones.iter()
+            .filter(|z| z.id.0 == hand_id)
+            .flat_map(|z| z.cards.iter().map(|card| Action::PlayCard { card: *card, from: z.id.clone() }));
+
+        std::iter::once(Action::PassPriority)
+            .chain(plays)
+            .filter(|action| crate::engine::legality::validate(self, player, action).is_ok())
+            .chain(self.mana_activations(player))
+            .collect()
+    }
+
+    /// The mana abilities `player` can activate right now, in field order
+    fn mana_activations(&self, player: PlayerId) -> Vec<Action> {
+        self.state.controlled_cards(player).into_iter()
+            .flat_map(|card| {
+                let abilities = self.cards.get(&self.state.definition_of(card).0)
+                    .map_or(0, |def| def.abilities.len());
+                (0..abilities).map(move |ability| Action::ActivateAbility { card, ability })
+            })
+            .filter(|action| crate::engine::legality::validate(self, player, action).is_ok())
+            .collect()
+    }
+
+    /// Every player and card that would be accepted as the answer to `choice` right now
+    ///
+    /// Protection is judged from the side of whoever chooses: the controller
+    /// of the stack item waiting on the choice, or the priority player if
+    /// no stack item is. Players come first, then cards in zone order.
+    pub fn legal_targets(&self, choice: &PendingChoice) -> Vec<TargetRef> {
+        let chooser = self.chooser(choice);
+        let players = self.state.players.iter().map(|p| TargetRef::Player(p.id));
+        let cards = self.state.zones.iter().flat_map(|z| z.cards.iter().map(|card| TargetRef::Card(*card)));
+        players.chain(cards)
+            .filter(|target| crate::engine::legality::validate_target(self, chooser, &choice.kind, target).is_ok())
+            .collect()
+    }
+
+    /// The player who answers `choice`: the active player for a trigger
+    /// ordering, otherwise the controller of the stack item waiting on it, or
+    /// the priority player if no stack item is
+    pub(crate) fn chooser(&self, choice: &PendingChoice) -> PlayerId {
+        if let ChoiceKind::OrderTriggers { .. } = choice.kind {
+            return self.state.turn.active_player;
+        }
+        choice.stack_item
+            .and_then(|id| self.state.stack.iter().find(|item| item.id == id))
+            .map(|item| item.controller)
+            .unwrap_or(self.state.turn.priority_player)
     }
 
     /// Generate the next unique stack item ID
@@ -67,8 +316,15 @@ impl GameEngine {
     pub fn apply_action(&mut self, player: PlayerId, action: Action) -> Result<StepResult, EngineError> {
         // 1) validate
         self.validate_action(player, &action)?;
+        let origin = self.current_origin();
+        let step = HistoryStep::Action(player, action.clone());
 
         // 2) apply (reducer)
+        let stack_before = self.state.stack.len();
+        let answered_choice = matches!(
+            action,
+            Action::ChooseTarget { .. } | Action::OrderTriggers { .. } | Action::ChooseCards { .. }
+        );
         let mut events = crate::engine::reducer::apply(self, player, action)?;
 
         // 3) post-step checks (win/loss, auto-resolve stack, advance phase)
@@ -76,26 +332,289 @@ impl GameEngine {
         // Check for win/loss conditions
         self.check_game_end(&mut events);
         
-        // Auto-resolve stack if it has items and no pending choice
-        self.auto_resolve_stack(&mut events);
+        if self.rules.turn.stack_responses {
+            // A new or newly targeted stack item opens a response window;
+            // otherwise resolve the top item once everyone has passed
+            if self.state.stack.len() > stack_before || answered_choice {
+                self.open_response_window(player);
+            } else {
+                self.resolve_if_all_passed(&mut events);
+            }
+        } else {
+            // Auto-resolve stack if it has items and no pending choice
+            self.auto_resolve_stack(&mut events);
+        }
+
+        // Resolving may have ended the game too
+        self.check_game_end(&mut events);
         
         // Advance to next phase/step if appropriate
         self.advance_phase_if_ready(&mut events);
 
+        // Nobody being able to act means the rules have a gap
+        let deadlock = self.settle_deadlock(&mut events);
+
+        self.action_count += 1;
+        self.event_count += events.len() as u32;
+        self.record_step(origin, step);
+        deadlock?;
         Ok(StepResult { events })
     }
 
+    /// Fails when the game is still going but no player has a legal action
+    /// (conceding aside), so play can't continue
+    pub fn check_invariants(&self) -> Result<(), EngineError> {
+        if self.state.ended.is_some() || self.state.players.iter().any(|p| !self.legal_actions(p.id).is_empty()) {
+            return Ok(());
+        }
+        Err(CardinalError(match &self.state.pending_choice {
+            Some(choice) => format!("No player can act: choice {} (\"{}\") has no legal answer", choice.id, choice.prompt),
+            None => "No player can act and the game hasn't ended".to_string(),
+        }))
+    }
+
+    /// The stack, bottom first: the last item resolves next
+    pub fn stack(&self) -> &[StackItem] {
+        &self.state.stack
+    }
+
+    /// The game as `viewer` sees it, with the stack labelled for display
+    pub fn view(&self, viewer: PlayerId) -> crate::state::view::GameStateView {
+        crate::state::view::GameStateView::new(self, viewer)
+    }
+
+    /// Resolve just the top stack item, whatever the priority state, for
+    /// clients that step through the stack themselves.
+    ///
+    /// Fails if the stack is empty or a choice is pending, since the top item
+    /// may be waiting on it.
+    pub fn resolve_top(&mut self) -> Result<StepResult, EngineError> {
+        if self.state.stack.is_empty() {
+            return Err(CardinalError("The stack is empty".to_string()));
+        }
+        if self.state.pending_choice.is_some() {
+            return Err(CardinalError("A choice is pending and must be answered first".to_string()));
+        }
+        let origin = self.current_origin();
+
+        let mut events = Vec::new();
+        self.resolve_top_item(&mut events);
+        self.check_game_end(&mut events);
+        self.event_count += events.len() as u32;
+        self.record_step(origin, HistoryStep::ResolveTop);
+        Ok(StepResult { events })
+    }
+
+    /// Pass priority for every player when none of them could do anything
+    /// but pass, resolving the top of the stack or moving to the next step;
+    /// for solitaire play and simulations that would otherwise pass by hand.
+    ///
+    /// A player could act if, holding priority, they would have a legal card
+    /// play. Mana abilities don't count, since making mana on its own changes
+    /// nothing. Returns `None`, changing nothing, if someone could act, a
+    /// choice is pending or the game is over.
+    pub fn advance_if_no_responses(&mut self) -> Result<Option<StepResult>, EngineError> {
+        if self.state.ended.is_some() || self.state.pending_choice.is_some() {
+            return Ok(None);
+        }
+        let holder = self.state.turn.priority_player;
+        let players: Vec<PlayerId> = self.state.players.iter().map(|p| p.id).collect();
+        let mut can_respond = false;
+        for player in players {
+            // Quick cards are only legal with priority, so ask as if each had it
+            self.state.turn.priority_player = player;
+            can_respond |= self.legal_actions(player).iter().any(|a| matches!(a, Action::PlayCard { .. }));
+        }
+        self.state.turn.priority_player = holder;
+        if can_respond {
+            return Ok(None);
+        }
+
+        // Pass around until the round completes and the engine moves on
+        let mut events = Vec::new();
+        for _ in 0..self.state.players.len() {
+            let result = self.apply_action(self.state.turn.priority_player, Action::PassPriority)?;
+            events.extend(result.events);
+            if self.state.turn.priority_passes == 0 || self.state.ended.is_some() || self.state.pending_choice.is_some() {
+                break;
+            }
+        }
+        Ok(Some(StepResult { events }))
+    }
+
+    /// Apply a sequence of actions in order, returning each step's result.
+    ///
+    /// Stops at the first illegal action and returns its error; the steps
+    /// before it stay applied. Wrap the call in `with_snapshot` to discard
+    /// the rollout afterwards.
+    pub fn rollout(&mut self, actions: &[(PlayerId, Action)]) -> Result<Vec<StepResult>, EngineError> {
+        actions.iter()
+            .map(|(player, action)| self.apply_action(*player, action.clone()))
+            .collect()
+    }
+
+    /// Run `f` against this engine, then restore the game state (and ID and
+    /// action counters) to what they were before, whatever `f` did
+    pub fn with_snapshot<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let state = self.state.clone();
+        let next_choice_id = self.next_choice_id;
+        let next_stack_id = self.next_stack_id;
+        let (action_count, event_count) = (self.action_count, self.event_count);
+        let (history_origin, history) = (self.history_origin.clone(), self.history.clone());
+
+        let result = f(self);
+
+        self.state = state;
+        self.next_choice_id = next_choice_id;
+        self.next_stack_id = next_stack_id;
+        self.action_count = action_count;
+        self.event_count = event_count;
+        self.history_origin = history_origin;
+        self.history = history;
+        result
+    }
+
+    /// Run `f` with the undo history set aside and nothing it does recorded,
+    /// then put the history back as it was. Snapshots taken inside `f` then
+    /// don't copy the history, which keeps deep rollouts cheap; `undo` inside
+    /// `f` has nothing to take back.
+    pub fn without_history<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let history_origin = self.history_origin.take();
+        let history = std::mem::take(&mut self.history);
+        let recording = std::mem::replace(&mut self.recording, false);
+
+        let result = f(self);
+
+        self.history_origin = history_origin;
+        self.history = history;
+        self.recording = recording;
+        result
+    }
+
+    /// Take back the last applied action, along with any `resolve_top` calls
+    /// made after it.
+    ///
+    /// The engine returns to the state it had before the first recorded
+    /// action and replays the rest, so the RNG ends up exactly where it was.
+    /// Changes made to `state` directly between actions aren't recorded and
+    /// are lost. Fails if there is no action to undo.
+    pub fn undo(&mut self) -> Result<(), EngineError> {
+        let Some(last) = self.history.iter().rposition(|step| matches!(step, HistoryStep::Action(..))) else {
+            return Err(CardinalError("There is no action to undo".to_string()));
+        };
+        let Some(origin) = self.history_origin.take() else {
+            return Err(CardinalError("There is no action to undo".to_string()));
+        };
+
+        let mut replay = std::mem::take(&mut self.history);
+        replay.truncate(last);
+
+        self.state = origin.state.clone();
+        self.next_choice_id = origin.next_choice_id;
+        self.next_stack_id = origin.next_stack_id;
+        self.action_count = origin.action_count;
+        self.event_count = origin.event_count;
+        self.history_origin = Some(origin);
+
+        for step in replay {
+            match step {
+                HistoryStep::Action(player, action) => self.apply_action(player, action)?,
+                HistoryStep::ResolveTop => self.resolve_top()?,
+            };
+        }
+        Ok(())
+    }
+
+    /// Whether `undo` has an action to take back
+    pub fn can_undo(&self) -> bool {
+        self.history.iter().any(|step| matches!(step, HistoryStep::Action(..)))
+    }
+
+    /// The engine as it is now, if the history is empty and this is where
+    /// the next recorded step starts from
+    fn current_origin(&self) -> Option<Box<HistoryOrigin>> {
+        (self.recording && self.history.is_empty()).then(|| Box::new(HistoryOrigin {
+            state: self.state.clone(),
+            next_choice_id: self.next_choice_id,
+            next_stack_id: self.next_stack_id,
+            action_count: self.action_count,
+            event_count: self.event_count,
+        }))
+    }
+
+    /// Add a successful `step` to the history, starting it at `origin` if it was empty
+    fn record_step(&mut self, origin: Option<Box<HistoryOrigin>>, step: HistoryStep) {
+        if !self.recording {
+            return;
+        }
+        if origin.is_some() {
+            self.history_origin = origin;
+        }
+        self.history.push(step);
+    }
+
+    /// End the game if a player has lost. These state-based actions happen
+    /// simultaneously for every player: all who lose at once lose together,
+    /// and players are named and picked as winner active player first, then
+    /// in turn order, the same order simultaneous triggers use (see
+    /// `triggers::order_simultaneous`).
     fn check_game_end(&mut self, events: &mut Vec<Event>) {
-        // Check if any player has <= 0 life (loses)
-        let losers: Vec<PlayerId> = self.state.players.iter()
-            .filter(|p| p.life <= 0)
+        if self.state.ended.is_some() {
+            return;
+        }
+
+        let active = self.state.turn.active_player;
+        let num_players = self.state.players.len().max(1) as u8;
+        let mut seats: Vec<&crate::state::gamestate::PlayerState> = self.state.players.iter().collect();
+        seats.sort_by_key(|p| (p.id.0 + num_players - active.0) % num_players);
+        let names = |players: &[PlayerId]| players.iter()
+            .map(|p| p.0.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Once all but one player have conceded, the one left wins
+        let remaining: Vec<PlayerId> = seats.iter()
+            .filter(|p| !p.conceded)
             .map(|p| p.id)
             .collect();
+        if remaining.len() < seats.len() && remaining.len() <= 1 {
+            let winner = remaining.first().copied();
+            let conceded: Vec<PlayerId> = seats.iter()
+                .filter(|p| p.conceded)
+                .map(|p| p.id)
+                .collect();
+            let reason = format!("Player {} conceded", names(&conceded));
 
-        if !losers.is_empty() {
-            // Determine winner: last player with > 0 life
-            let winner = self.state.players.iter()
-                .find(|p| p.life > 0)
+            self.state.ended = Some(crate::state::gamestate::GameEnd { winner, reason: reason.clone() });
+            events.push(Event::GameEnded { winner, reason });
+            return;
+        }
+
+        // Drawing past the per-turn limit loses under the `lose` policy
+        if let Some(max) = self.rules.turn.max_draws_per_turn
+            && self.rules.turn.excess_draw_policy == ExcessDrawPolicy::Lose
+        {
+            let losers: Vec<PlayerId> = seats.iter()
+                .filter(|p| p.draws_this_turn > max)
+                .map(|p| p.id)
+                .collect();
+            if !losers.is_empty() {
+                let winner = seats.iter()
+                    .find(|p| !losers.contains(&p.id) && !p.conceded)
+                    .map(|p| p.id);
+                let reason = format!("Player {} drew more than {} cards in a turn", names(&losers), max);
+
+                self.state.ended = Some(crate::state::gamestate::GameEnd { winner, reason: reason.clone() });
+                events.push(Event::GameEnded { winner, reason });
+                return;
+            }
+        }
+
+        // Check if any player has <= 0 life (loses)
+        if seats.iter().any(|p| p.life <= 0) {
+            // Determine winner: the first player with > 0 life who hasn't conceded
+            let winner = seats.iter()
+                .find(|p| p.life > 0 && !p.conceded)
                 .map(|p| p.id);
             
             self.state.ended = Some(crate::state::gamestate::GameEnd {
@@ -106,33 +625,153 @@ impl GameEngine {
         }
     }
 
+    /// Apply `on_deadlock` while `check_invariants` fails: strict returns the
+    /// error; lenient skips the pending choice and plays on, and only fails
+    /// if there is no choice to skip
+    fn settle_deadlock(&mut self, events: &mut Vec<Event>) -> Result<(), EngineError> {
+        while let Err(error) = self.check_invariants() {
+            if self.on_deadlock == DeadlockPolicy::Strict {
+                return Err(error);
+            }
+            let Some(choice) = self.state.pending_choice.take() else {
+                return Err(error);
+            };
+            if let Some(id) = choice.stack_item
+                && self.state.stack.iter().any(|item| item.id == id)
+            {
+                events.extend(crate::engine::events::commit_commands(
+                    &mut self.state,
+                    &[Command::RemoveStackItem { id }],
+                ));
+            }
+            events.push(Event::ChoiceSkipped { choice_id: choice.id });
+
+            if self.rules.turn.stack_responses {
+                self.resolve_if_all_passed(events);
+            } else {
+                self.auto_resolve_stack(events);
+            }
+            self.check_game_end(events);
+            self.advance_phase_if_ready(events);
+        }
+        Ok(())
+    }
+
     fn auto_resolve_stack(&mut self, events: &mut Vec<Event>) {
         // If the stack has items and there's no pending choice, resolve the top item
         while !self.state.stack.is_empty() && self.state.pending_choice.is_none() {
-            if let Some(item) = self.state.stack.pop() {
-                let item_id = item.id;
-                
-                // Execute the effect and apply resulting commands
-                match crate::engine::effect_executor::execute_effect(
-                    &item.effect,
-                    item.source,
-                    item.controller,
-                    &self.state,
-                    Some(&self.scripting),
-                ) {
-                    Ok(commands) => {
-                        // Apply the commands and collect their events
-                        let effect_events = crate::engine::events::commit_commands(&mut self.state, &commands);
+            self.resolve_top_item(events);
+        }
+    }
+
+    /// After `player` puts something on the stack, priority moves to the next
+    /// player. The pusher counts as having passed, so the item resolves once
+    /// everyone else passes too.
+    fn open_response_window(&mut self, player: PlayerId) {
+        let num_players = self.state.players.len() as u8;
+        self.state.turn.priority_player = PlayerId((player.0 + 1) % num_players);
+        self.state.turn.priority_passes = 1;
+    }
+
+    /// With stack responses on, resolve the top stack item once all players have
+    /// passed in succession, then hand priority back to the active player
+    fn resolve_if_all_passed(&mut self, events: &mut Vec<Event>) {
+        let num_players = self.state.players.len() as u32;
+        if self.state.stack.is_empty()
+            || self.state.pending_choice.is_some()
+            || self.state.turn.priority_passes < num_players
+        {
+            return;
+        }
+
+        self.resolve_top_item(events);
+        self.state.turn.priority_passes = 0;
+        self.state.turn.priority_player = self.state.turn.active_player;
+    }
+
+    fn resolve_top_item(&mut self, events: &mut Vec<Event>) {
+        if let Some(item) = self.state.stack.pop() {
+            let item_id = item.id;
+            
+            // Execute the effect and apply resulting commands
+            match crate::engine::effect_executor::execute_effect_with_cards(
+                &item.effect,
+                item.source,
+                item.controller,
+                &self.state,
+                Some(&self.scripting),
+                Some(&self.cards),
+                Some(&self.builtins),
+            ) {
+                Ok(commands) => {
+                    // Apply the commands and collect their events
+                    let commands = crate::engine::cards::with_entry_modifiers(commands, &self.cards, &self.state);
+                    let mut commands = self.limit_draws(commands);
+                    self.assign_placeholder_ids(&mut commands);
+                    // All or nothing: an effect that fails partway changes nothing
+                    if let Ok(effect_events) = crate::engine::events::try_commit_commands(&mut self.state, &commands) {
+                        let trigger_events = crate::engine::triggers::fire_triggers(self, &effect_events);
                         events.extend(effect_events);
+                        events.extend(trigger_events);
                     }
-                    Err(_err) => {
-                        // Effect execution failed; silently continue resolving the stack.
-                        // Future: emit a dedicated Event to report the failure to callers
+                }
+                Err(_err) => {
+                    // Effect execution failed; silently continue resolving the stack.
+                    // Future: emit a dedicated Event to report the failure to callers
+                }
+            }
+            
+            // Emit StackResolved event after executing effect
+            events.push(Event::StackResolved { item_id });
+        }
+    }
+
+    /// Cut draws down to what each player has left under `max_draws_per_turn`
+    ///
+    /// Only under `ExcessDrawPolicy::Ignore`; with `Lose` the draws happen and
+    /// `check_game_end` ends the game for whoever went over.
+    fn limit_draws(&self, commands: Vec<Command>) -> Vec<Command> {
+        let Some(max) = self.rules.turn.max_draws_per_turn else {
+            return commands;
+        };
+        if self.rules.turn.excess_draw_policy != ExcessDrawPolicy::Ignore {
+            return commands;
+        }
+
+        let mut remaining: std::collections::HashMap<PlayerId, u32> = self.state.players.iter()
+            .map(|p| (p.id, max.saturating_sub(p.draws_this_turn)))
+            .collect();
+        commands.into_iter()
+            .filter_map(|command| match command {
+                Command::DrawCards { player, count } => {
+                    let left = remaining.entry(player).or_insert(0);
+                    let count = count.min(*left);
+                    *left -= count;
+                    (count > 0).then_some(Command::DrawCards { player, count })
+                }
+                other => Some(other),
+            })
+            .collect()
+    }
+
+    /// Effects can't allocate IDs, so stack items and choices they create carry
+    /// `PLACEHOLDER_ID`; give each a real one, keeping a choice pointed at the
+    /// stack item created alongside it
+    fn assign_placeholder_ids(&mut self, commands: &mut [Command]) {
+        let mut pushed = None;
+        for command in commands.iter_mut() {
+            match command {
+                Command::PushStack { item } if item.id == PLACEHOLDER_ID => {
+                    item.id = self.next_stack_id();
+                    pushed = Some(item.id);
+                }
+                Command::RequestChoice { choice, .. } if choice.id == PLACEHOLDER_ID => {
+                    choice.id = self.next_choice_id();
+                    if choice.stack_item == Some(PLACEHOLDER_ID) {
+                        choice.stack_item = pushed;
                     }
                 }
-                
-                // Emit StackResolved event after executing effect
-                events.push(Event::StackResolved { item_id });
+                _ => {}
             }
         }
     }
@@ -161,6 +800,15 @@ impl GameEngine {
         self.state.turn.priority_passes = 0;
         self.state.turn.priority_player = self.state.turn.active_player;
 
+        self.step_forward(events);
+        self.enter_step(events);
+    }
+
+    /// Move from the current step to the next one, rolling over into the
+    /// next phase or the next turn as needed
+    fn step_forward(&mut self, events: &mut Vec<Event>) {
+        let num_players = self.state.players.len() as u32;
+
         // Find current phase index
         let current_phase_idx = self.rules.turn.phases.iter()
             .position(|p| p.id.as_str() == self.state.turn.phase.0)
@@ -172,6 +820,11 @@ impl GameEngine {
             .position(|s| s.id.as_str() == self.state.turn.step.0)
             .unwrap_or(0);
 
+        // Unspent resources that empty at this boundary are lost
+        let phase_ended = current_step_idx + 1 >= current_phase.steps.len();
+        self.empty_resources(phase_ended, events);
+        let current_phase = &self.rules.turn.phases[current_phase_idx];
+
         // Try to advance to next step in current phase
         if current_step_idx + 1 < current_phase.steps.len() {
             let next_step = &current_phase.steps[current_step_idx + 1];
@@ -208,8 +861,13 @@ impl GameEngine {
             return;
         }
 
-        // End of turn: cycle back to first phase and advance turn number
+        // End of turn: "until end of turn" effects wear off, then cycle back
+        // to the first phase and advance the turn number
         if let Some(first_phase) = self.rules.turn.phases.first() {
+            let cleanups = std::mem::take(&mut self.state.end_of_turn);
+            events.extend(crate::engine::events::commit_commands(&mut self.state, &cleanups));
+            events.extend(crate::engine::events::end_temporary_effects(&mut self.state));
+
             let phase_box: Box<str> = first_phase.id.clone().into_boxed_str();
             let phase_static: &'static str = Box::leak(phase_box);
             self.state.turn.phase = crate::ids::PhaseId(phase_static);
@@ -223,10 +881,20 @@ impl GameEngine {
             }
 
             self.state.turn.number += 1;
+            for player in &mut self.state.players {
+                player.draws_this_turn = 0;
+                player.plays_this_turn.clear();
+                player.revealed.clear();
+                player.revealed_to.clear();
+            }
 
-            // Rotate active player and give them priority
-            let next_player_idx = (self.state.turn.active_player.0 + 1) % num_players as u8;
-            self.state.turn.active_player = crate::ids::PlayerId(next_player_idx);
+            // The next queued extra turn comes first; otherwise rotate the
+            // active player. Either way they get priority.
+            self.state.turn.active_player = if self.state.extra_turns.is_empty() {
+                crate::ids::PlayerId((self.state.turn.active_player.0 + 1) % num_players as u8)
+            } else {
+                self.state.extra_turns.remove(0)
+            };
             self.state.turn.priority_player = self.state.turn.active_player;
 
             events.push(Event::PhaseAdvanced {
@@ -236,7 +904,94 @@ impl GameEngine {
         }
     }
 
+    /// Settle on the step just entered: pass over any the active player has a
+    /// skip for (using the skip up), then make the step's draws
+    fn enter_step(&mut self, events: &mut Vec<Event>) {
+        loop {
+            let active = self.state.turn.active_player;
+            let Some(phase) = self.rules.turn.phases.iter().find(|p| p.id == self.state.turn.phase.0) else {
+                return;
+            };
+            let at_phase_start = phase.steps.first().is_none_or(|s| s.id == self.state.turn.step.0);
+
+            let skip = self.state.skips.iter().position(|(player, id)| {
+                *player == active
+                    && (id == self.state.turn.step.0 || (at_phase_start && *id == phase.id))
+            });
+            let Some(index) = skip else { break };
+            let (player, id) = self.state.skips.remove(index);
+
+            // Skipping a phase jumps past all of its steps
+            if id == phase.id
+                && let Some(last) = phase.steps.last()
+            {
+                self.state.turn.step = crate::ids::StepId(Box::leak(last.id.clone().into_boxed_str()));
+            }
+            events.push(Event::Skipped { player, id });
+            self.step_forward(events);
+        }
+
+        let draws = self.rules.turn.phases.iter()
+            .find(|p| p.id == self.state.turn.phase.0)
+            .and_then(|p| p.steps.iter().find(|s| s.id == self.state.turn.step.0))
+            .map_or(0, |s| s.draws);
+        if draws > 0 {
+            let command = Command::DrawCards { player: self.state.turn.active_player, count: draws };
+            events.extend(crate::engine::events::commit_commands(&mut self.state, &[command]));
+        }
+    }
+
+    /// Zero the resources that empty when a step ends, and when `phase_ended`
+    /// also those that empty when a phase ends
+    fn empty_resources(&mut self, phase_ended: bool, events: &mut Vec<Event>) {
+        let commands: Vec<Command> = self.rules.resources.iter()
+            .filter(|r| match r.empties_at {
+                EmptiesAt::Step => true,
+                EmptiesAt::Phase => phase_ended,
+                EmptiesAt::Never => false,
+            })
+            .flat_map(|r| self.state.players.iter()
+                .filter(|p| p.resources.get(&r.id).is_some_and(|amount| *amount != 0))
+                .map(|p| Command::SetResource { player: p.id, resource: r.id.clone(), amount: 0 }))
+            .collect();
+        events.extend(crate::engine::events::commit_commands(&mut self.state, &commands));
+    }
+
     fn validate_action(&self, player: PlayerId, action: &Action) -> Result<(), LegalityError> {
         crate::engine::legality::validate(self, player, action)
     }
 }
+
+/// Every ordering of `items`
+fn permutations(items: &[u32]) -> Vec<Vec<u32>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut orders = Vec::new();
+    for (i, first) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(i);
+        for mut order in permutations(&rest) {
+            order.insert(0, *first);
+            orders.push(order);
+        }
+    }
+    orders
+}
+
+/// Every selection of at most `max` of `cards`, keeping their order
+fn selections(cards: &[CardId], max: usize) -> Vec<Vec<CardId>> {
+    let mut result = vec![Vec::new()];
+    for card in cards {
+        let extended: Vec<Vec<CardId>> = result.iter()
+            .filter(|picked| picked.len() < max)
+            .map(|picked| {
+                let mut picked = picked.clone();
+                picked.push(*card);
+                picked
+            })
+            .collect();
+        result.extend(extended);
+    }
+    result
+}
@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use crate::{
+    engine::core::GameEngine,
+    error::{CardinalError, EngineError},
+    ids::{CardId, PlayerId},
+    model::command::Command,
+};
+
+/// A parsed cost string such as "2R" or "R 2 life": a generic amount plus
+/// specific symbols, and any life to pay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cost {
+    /// Amount payable with any mana
+    pub generic: i32,
+    /// Specific requirements keyed by symbol (e.g. "R" => 1)
+    pub symbols: BTreeMap<String, i32>,
+    /// Life the player pays
+    pub life: i32,
+}
+
+impl Cost {
+    /// Parse a cost string. Digits form the generic amount ("10" is ten),
+    /// unless followed by "life" ("2 life" pays two life), and each other
+    /// letter is one mana of that symbol; whitespace and braces are ignored.
+    pub fn parse(cost: &str) -> Result<Cost, EngineError> {
+        let mut parsed = Cost::default();
+        let chars: Vec<char> = cost.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_ascii_digit() {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let amount = chars[start..i].iter().collect::<String>().parse::<i32>()
+                    .map_err(|_| CardinalError(format!("Cost '{}' is too large", cost)))?;
+                let mut word = i;
+                while word < chars.len() && chars[word].is_whitespace() {
+                    word += 1;
+                }
+                let next: String = chars[word..chars.len().min(word + 4)].iter().collect();
+                if next.eq_ignore_ascii_case("life") {
+                    parsed.life += amount;
+                    i = word + 4;
+                } else {
+                    parsed.generic += amount;
+                }
+                continue;
+            }
+            if c.is_ascii_alphabetic() {
+                *parsed.symbols.entry(c.to_ascii_uppercase().to_string()).or_insert(0) += 1;
+            } else if !(c.is_whitespace() || c == '{' || c == '}') {
+                return Err(CardinalError(format!("Invalid character '{}' in cost '{}'", c, cost)));
+            }
+            i += 1;
+        }
+        Ok(parsed)
+    }
+
+    pub fn is_free(&self) -> bool {
+        self.generic == 0 && self.symbols.values().all(|n| *n == 0) && self.life == 0
+    }
+}
+
+/// Something that can put mana toward a cost
+enum Source {
+    /// A resource pool whose mana is of `symbol`
+    Pool { resource: String, symbol: String, available: i32 },
+    /// An untapped permanent that produces `produces` when tapped
+    Permanent { card: CardId, produces: Cost },
+}
+
+/// Work out how `player` can pay `cost`, greedily, from their resource pools
+/// and by tapping their untapped mana-producing permanents.
+///
+/// Resources pay mana costs when the ruleset gives them a `symbol`; they pay
+/// that symbol or generic. Permanents pay what their card's `produces` says.
+/// Specific symbols are covered first, pools before permanents, then the
+/// generic amount from whatever is left. Mana a tapped permanent makes beyond
+/// what the cost needs is lost.
+///
+/// Life is paid from the player's life total; see [`pay_life`].
+///
+/// Returns the `SpendResource`/`SetTapped`/`ChangeLife` commands that pay the
+/// cost, or an error naming the shortfall if the player can't cover it.
+/// Nothing is applied.
+pub fn auto_pay(engine: &GameEngine, player: PlayerId, cost: &Cost) -> Result<Vec<Command>, EngineError> {
+    auto_pay_without(engine, player, cost, None)
+}
+
+/// [`auto_pay`] without tapping `exclude`, e.g. a card being tapped for its own ability
+fn auto_pay_without(
+    engine: &GameEngine,
+    player: PlayerId,
+    cost: &Cost,
+    exclude: Option<CardId>,
+) -> Result<Vec<Command>, EngineError> {
+    let life = pay_life(engine, player, cost.life)
+        .map_err(|e| CardinalError(format!("Cannot pay {}: {}", describe(cost), e.0)))?;
+    let mut sources = mana_sources(engine, player);
+    sources.retain(|source| !matches!(source, Source::Permanent { card, .. } if Some(*card) == exclude));
+    let mut spent: BTreeMap<String, i32> = BTreeMap::new();
+    let mut tapped: Vec<CardId> = Vec::new();
+    // Mana left over from permanents tapped earlier in this payment
+    let mut floating: BTreeMap<String, i32> = BTreeMap::new();
+
+    for (symbol, needed) in &cost.symbols {
+        let mut needed = *needed;
+        needed -= take(&mut floating, symbol, needed);
+        for source in sources.iter_mut() {
+            if needed == 0 {
+                break;
+            }
+            match source {
+                Source::Pool { resource, symbol: s, available } if s == symbol && *available > 0 => {
+                    let used = needed.min(*available);
+                    *available -= used;
+                    *spent.entry(resource.clone()).or_insert(0) += used;
+                    needed -= used;
+                }
+                Source::Permanent { card, produces } if !tapped.contains(card) && produces.symbols.contains_key(symbol) => {
+                    tapped.push(*card);
+                    add_mana(&mut floating, produces);
+                    needed -= take(&mut floating, symbol, needed);
+                }
+                _ => {}
+            }
+        }
+        if needed > 0 {
+            return Err(CardinalError(format!("Cannot pay {}: short {} {}", describe(cost), needed, symbol)));
+        }
+    }
+
+    let mut needed = cost.generic;
+    let leftovers: Vec<String> = floating.keys().cloned().collect();
+    for symbol in leftovers {
+        needed -= take(&mut floating, &symbol, needed);
+    }
+    for source in sources.iter_mut() {
+        if needed == 0 {
+            break;
+        }
+        match source {
+            Source::Pool { resource, available, .. } if *available > 0 => {
+                let used = needed.min(*available);
+                *available -= used;
+                *spent.entry(resource.clone()).or_insert(0) += used;
+                needed -= used;
+            }
+            Source::Permanent { card, produces } if !tapped.contains(card) && !produces.is_free() => {
+                tapped.push(*card);
+                let made = produces.generic + produces.symbols.values().sum::<i32>();
+                needed -= needed.min(made);
+            }
+            _ => {}
+        }
+    }
+    if needed > 0 {
+        return Err(CardinalError(format!("Cannot pay {}: short {} generic", describe(cost), needed)));
+    }
+
+    let mut commands: Vec<Command> = spent.into_iter()
+        .map(|(resource, amount)| Command::SpendResource { player, resource, amount })
+        .collect();
+    commands.extend(tapped.into_iter().map(|card| Command::SetTapped { card, tapped: true }));
+    commands.extend(life);
+    Ok(commands)
+}
+
+/// The command that pays `amount` life for `player`, if any. Paying life
+/// that would leave the player at zero or less is refused unless the
+/// ruleset's `allow_lethal_life_payment` is set.
+pub fn pay_life(engine: &GameEngine, player: PlayerId, amount: i32) -> Result<Option<Command>, EngineError> {
+    if amount <= 0 {
+        return Ok(None);
+    }
+    let life = engine.state.players.iter()
+        .find(|p| p.id == player)
+        .map(|p| p.life)
+        .ok_or_else(|| CardinalError(format!("Player {} does not exist", player.0)))?;
+    if life - amount <= 0 && !engine.rules.players.allow_lethal_life_payment {
+        return Err(CardinalError(format!("paying {} life with {} left would be lethal", amount, life)));
+    }
+    Ok(Some(Command::ChangeLife { player, delta: -amount }))
+}
+
+/// What `card` costs `player` right now: its printed cost adjusted by the
+/// cost modifiers of permanents on the player's field. Modifiers only touch
+/// the generic part, which never drops below zero. Cards without a cost are free.
+pub fn cost_of(engine: &GameEngine, player: PlayerId, card: CardId) -> Result<Cost, EngineError> {
+    let def = engine.cards.get(&engine.state.definition_of(card).0)
+        .ok_or_else(|| CardinalError(format!("Card {} has no definition", card.0)))?;
+    let mut cost = match &def.cost {
+        Some(printed) => Cost::parse(printed)?,
+        None => Cost::default(),
+    };
+
+    let delta: i32 = engine.state.controlled_cards(player).into_iter()
+        .filter_map(|permanent| engine.cards.get(&engine.state.definition_of(permanent).0))
+        .flat_map(|source| source.cost_modifiers.iter())
+        .filter(|m| m.card_type.as_ref().is_none_or(|t| *t == def.card_type))
+        .map(|m| m.amount)
+        .sum();
+    cost.generic = (cost.generic + delta).max(0);
+
+    Ok(cost)
+}
+
+/// The commands that pay for `player` playing `card`, after cost modifiers.
+/// Fails without changing anything if the player can't cover it.
+pub fn pay_cost(engine: &GameEngine, player: PlayerId, card: CardId) -> Result<Vec<Command>, EngineError> {
+    auto_pay(engine, player, &cost_of(engine, player, card)?)
+}
+
+/// The commands that pay the `cost` of `card`'s ability at index `ability`
+/// when `player` activates it. The card itself is tapped by the activation,
+/// so it never pays. Abilities without a cost are free.
+pub fn pay_ability_cost(engine: &GameEngine, player: PlayerId, card: CardId, ability: usize) -> Result<Vec<Command>, EngineError> {
+    let cost = engine.cards.get(&engine.state.definition_of(card).0)
+        .and_then(|def| def.abilities.get(ability))
+        .and_then(|a| a.cost.as_deref());
+    match cost {
+        Some(cost) => auto_pay_without(engine, player, &Cost::parse(cost)?, Some(card)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Whether `player` could pay for `card` right now
+pub fn can_afford(engine: &GameEngine, player: PlayerId, card: CardId) -> bool {
+    pay_cost(engine, player, card).is_ok()
+}
+
+/// The player's mana sources: symbol-bearing resource pools in ruleset order,
+/// then untapped permanents they control, lowest card ID first.
+fn mana_sources(engine: &GameEngine, player: PlayerId) -> Vec<Source> {
+    let mut sources = Vec::new();
+
+    if let Some(state) = engine.state.players.iter().find(|p| p.id == player) {
+        for def in &engine.rules.resources {
+            if let Some(symbol) = &def.symbol {
+                let available = state.resources.get(&def.id).copied().unwrap_or(0);
+                sources.push(Source::Pool {
+                    resource: def.id.clone(),
+                    symbol: symbol.to_ascii_uppercase(),
+                    available,
+                });
+            }
+        }
+    }
+
+    let mut permanents: Vec<CardId> = engine.state.controlled_cards(player).into_iter()
+        .filter(|card| !engine.state.card_instances.get(card).is_some_and(|i| i.tapped))
+        .collect();
+    permanents.sort_by_key(|c| c.0);
+    for card in permanents {
+        let produces = engine.cards.get(&engine.state.definition_of(card).0)
+            .and_then(|def| def.produces.as_deref())
+            .and_then(|p| Cost::parse(p).ok());
+        if let Some(produces) = produces {
+            sources.push(Source::Permanent { card, produces });
+        }
+    }
+
+    sources
+}
+
+fn add_mana(pool: &mut BTreeMap<String, i32>, mana: &Cost) {
+    for (symbol, n) in &mana.symbols {
+        *pool.entry(symbol.clone()).or_insert(0) += n;
+    }
+    if mana.generic > 0 {
+        // Generic mana only pays generic costs; "" never matches a symbol
+        *pool.entry(String::new()).or_insert(0) += mana.generic;
+    }
+}
+
+/// Take up to `wanted` of `symbol` from the pool, returning how much was taken
+fn take(pool: &mut BTreeMap<String, i32>, symbol: &str, wanted: i32) -> i32 {
+    let Some(available) = pool.get_mut(symbol) else {
+        return 0;
+    };
+    let used = wanted.min(*available);
+    *available -= used;
+    used
+}
+
+fn describe(cost: &Cost) -> String {
+    let mut text = if cost.generic > 0 || (cost.symbols.is_empty() && cost.life == 0) {
+        cost.generic.to_string()
+    } else {
+        String::new()
+    };
+    for (symbol, n) in &cost.symbols {
+        text.push_str(&symbol.repeat(*n as usize));
+    }
+    if cost.life > 0 {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&format!("{} life", cost.life));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cost() {
+        let cost = Cost::parse("2RR").unwrap();
+        assert_eq!(cost.generic, 2);
+        assert_eq!(cost.symbols.get("R"), Some(&2));
+
+        assert_eq!(Cost::parse("10").unwrap().generic, 10);
+        assert_eq!(Cost::parse("{1}{u}").unwrap(), Cost::parse("1U").unwrap());
+        assert!(Cost::parse("").unwrap().is_free());
+        assert!(Cost::parse("2+R").is_err());
+
+        let cost = Cost::parse("1R 2 life").unwrap();
+        assert_eq!((cost.generic, cost.life), (1, 2));
+        assert_eq!(cost.symbols.get("R"), Some(&1));
+        assert_eq!(Cost::parse("{3LIFE}").unwrap().life, 3);
+        assert!(!Cost::parse("1 life").unwrap().is_free());
+    }
+
+    #[test]
+    fn test_describe_roundtrips() {
+        for text in ["2R", "0", "UU", "3", "2 life", "1R 4 life"] {
+            assert_eq!(describe(&Cost::parse(text).unwrap()), text);
+        }
+    }
+}
@@ -1,10 +1,77 @@
 use crate::{
     ids::{CardId, PlayerId, ZoneId},
-    model::command::{Command, EffectRef},
+    model::action::TargetRef,
+    model::command::{AllowedTargets, ChoiceKind, Command, Duration, EffectRef, PendingChoice, StackItem, ZonePosition, PLACEHOLDER_ID},
     state::gamestate::GameState,
-    engine::scripting::{RhaiEngine, ScriptContext},
+    engine::cards::CardRegistry,
+    engine::scripting::{CardSnapshot, RhaiEngine, ScriptContext},
     error::CardinalError,
+    rules::query::CardQuery,
 };
+use std::sync::Arc;
+
+/// What a custom builtin effect builds its commands from
+pub struct BuiltinArgs<'a> {
+    /// The effect string after the registered prefix and its `_`, e.g.
+    /// "3" for "poison_3" (empty for a bare "poison")
+    pub args: &'a str,
+    pub source: Option<CardId>,
+    pub controller: PlayerId,
+    pub state: &'a GameState,
+}
+
+type BuiltinHandler = Arc<dyn Fn(&BuiltinArgs) -> Result<Vec<Command>, CardinalError> + Send + Sync>;
+
+/// Custom builtins by prefix, in registration order. A `GameEngine` keeps
+/// one next to its scripts; effects are executed against it the same way
+/// they are against the card registry.
+#[derive(Clone, Default)]
+pub struct BuiltinRegistry {
+    builtins: Vec<(String, BuiltinHandler)>,
+}
+
+impl BuiltinRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a builtin effect to the vocabulary without editing the engine.
+    ///
+    /// Effect strings equal to `prefix` or starting with `prefix_` are handed
+    /// to `handler`, ahead of the engine's own builtins, so a prefix can also
+    /// replace one of those. Cards use it as `effect = "poison_3"`.
+    /// Registering a prefix again replaces its handler.
+    pub fn register<F>(&mut self, prefix: &str, handler: F)
+    where
+        F: Fn(&BuiltinArgs) -> Result<Vec<Command>, CardinalError> + Send + Sync + 'static,
+    {
+        self.builtins.retain(|(p, _)| p != prefix);
+        self.builtins.push((prefix.to_string(), Arc::new(handler)));
+    }
+
+    /// The handler registered for `effect_str` and the args after its prefix.
+    /// The longest matching prefix wins.
+    fn find<'a>(&self, effect_str: &'a str) -> Option<(&BuiltinHandler, &'a str)> {
+        self.builtins.iter()
+            .filter_map(|(prefix, handler)| {
+                let rest = effect_str.strip_prefix(prefix.as_str())?;
+                let args = if rest.is_empty() { rest } else { rest.strip_prefix('_')? };
+                Some((prefix.len(), handler, args))
+            })
+            .max_by_key(|(len, _, _)| *len)
+            .map(|(_, handler, args)| (handler, args))
+    }
+
+    /// Whether `effect_str` is handled by a custom builtin
+    pub fn handles(&self, effect_str: &str) -> bool {
+        self.find(effect_str).is_some()
+    }
+}
+
+/// Whether `effect_str` is handled by one of `builtins`, if there are any
+fn is_custom_builtin(builtins: Option<&BuiltinRegistry>, effect_str: &str) -> bool {
+    builtins.is_some_and(|b| b.handles(effect_str))
+}
 
 /// Execute an effect and return commands to apply its results
 /// This handles three types of effects:
@@ -15,37 +82,432 @@ pub fn execute_effect(
     effect: &EffectRef,
     source: Option<CardId>,
     controller: PlayerId,
-    _state: &GameState,
+    state: &GameState,
+    scripting: Option<&RhaiEngine>,
+) -> Result<Vec<Command>, CardinalError> {
+    execute_effect_with_cards(effect, source, controller, state, scripting, None, None)
+}
+
+/// Same as [`execute_effect`], with the card definitions that effects
+/// filtering cards by type or keyword (`move_all`) match against, and the
+/// custom builtins registered on the engine. Without them such effects fail.
+pub fn execute_effect_with_cards(
+    effect: &EffectRef,
+    source: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
+    scripting: Option<&RhaiEngine>,
+    cards: Option<&CardRegistry>,
+    builtins: Option<&BuiltinRegistry>,
+) -> Result<Vec<Command>, CardinalError> {
+    execute_effect_logged(effect, source, controller, state, scripting, cards, builtins, &mut Vec::new())
+}
+
+/// Same as [`execute_effect_with_cards`], also appending the messages
+/// scripts pass to `log` to `log`
+#[allow(clippy::too_many_arguments)]
+pub fn execute_effect_logged(
+    effect: &EffectRef,
+    source: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
+    scripting: Option<&RhaiEngine>,
+    cards: Option<&CardRegistry>,
+    builtins: Option<&BuiltinRegistry>,
+    log: &mut Vec<String>,
+) -> Result<Vec<Command>, CardinalError> {
+    let mut recorder = Recorder { log: std::mem::take(log), trace: None };
+    let result = run_effect(effect, source, controller, state, scripting, cards, builtins, &mut recorder, 0);
+    *log = recorder.log;
+    result
+}
+
+/// One decision made while working out an effect's commands. `depth` is how
+/// far the effect is nested inside sequences, conditions and each-player
+/// effects.
+#[derive(Debug, Clone)]
+pub enum TraceStep {
+    /// An effect about to be executed
+    Effect { depth: usize, effect: String },
+    /// A builtin rewritten using the state, e.g. a placeholder replaced by
+    /// its value or an each-player effect bound to one player
+    Resolved { depth: usize, from: String, to: String },
+    /// A condition and whether it held
+    Condition { depth: usize, condition: String, met: bool },
+    /// The player or card a builtin acts on
+    Target { depth: usize, target: TargetRef },
+    /// A command an effect produced
+    Produced { depth: usize, command: Command },
+    /// A message a script passed to `log`
+    Log { depth: usize, message: String },
+}
+
+impl TraceStep {
+    pub fn depth(&self) -> usize {
+        match self {
+            TraceStep::Effect { depth, .. }
+            | TraceStep::Resolved { depth, .. }
+            | TraceStep::Condition { depth, .. }
+            | TraceStep::Target { depth, .. }
+            | TraceStep::Produced { depth, .. }
+            | TraceStep::Log { depth, .. } => *depth,
+        }
+    }
+}
+
+impl std::fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceStep::Effect { effect, .. } => write!(f, "effect {}", effect),
+            TraceStep::Resolved { from, to, .. } => write!(f, "resolved {} -> {}", from, to),
+            TraceStep::Condition { condition, met, .. } => {
+                write!(f, "condition {} is {}", condition, if *met { "met" } else { "not met" })
+            }
+            TraceStep::Target { target: TargetRef::Player(player), .. } => write!(f, "target player {}", player.0),
+            TraceStep::Target { target: TargetRef::Card(card), .. } => write!(f, "target card {}", card.0),
+            TraceStep::Produced { command, .. } => write!(f, "produced {:?}", command),
+            TraceStep::Log { message, .. } => write!(f, "log {}", message),
+        }
+    }
+}
+
+/// What [`execute_effect_traced`] returns
+#[derive(Debug, Clone, Default)]
+pub struct TracedEffect {
+    pub commands: Vec<Command>,
+    /// Messages the effect's scripts passed to `log`
+    pub log: Vec<String>,
+    /// The decisions made along the way, in order; empty unless asked for
+    pub trace: Vec<TraceStep>,
+}
+
+/// Same as [`execute_effect_logged`], returning the log with the commands
+/// and, when `trace` is set, each decision made to produce them: the effects
+/// executed, params resolved from the state, targets and the commands each
+/// one produced. For debugging a card that doesn't do what it should.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_effect_traced(
+    effect: &EffectRef,
+    source: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
+    scripting: Option<&RhaiEngine>,
+    cards: Option<&CardRegistry>,
+    builtins: Option<&BuiltinRegistry>,
+    trace: bool,
+) -> Result<TracedEffect, CardinalError> {
+    let mut recorder = Recorder { log: Vec::new(), trace: trace.then(Vec::new) };
+    let commands = run_effect(effect, source, controller, state, scripting, cards, builtins, &mut recorder, 0)?;
+    Ok(TracedEffect { commands, log: recorder.log, trace: recorder.trace.unwrap_or_default() })
+}
+
+/// Where [`run_effect`] puts script log messages and, if tracing, its steps
+struct Recorder {
+    log: Vec<String>,
+    trace: Option<Vec<TraceStep>>,
+}
+
+impl Recorder {
+    fn record(&mut self, step: impl FnOnce() -> TraceStep) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(step());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_effect(
+    effect: &EffectRef,
+    source: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
     scripting: Option<&RhaiEngine>,
+    cards: Option<&CardRegistry>,
+    builtins: Option<&BuiltinRegistry>,
+    out: &mut Recorder,
+    depth: usize,
 ) -> Result<Vec<Command>, CardinalError> {
+    out.record(|| TraceStep::Effect { depth, effect: describe_effect(effect) });
     match effect {
-        EffectRef::Builtin(effect_str) => execute_builtin_effect(effect_str, controller),
+        // These need the state to find where the card currently is
+        EffectRef::Builtin(effect_str) if effect_str.contains('{') => {
+            let resolved = evaluate_placeholders(effect_str, controller, state, cards)?;
+            out.record(|| TraceStep::Resolved { depth, from: effect_str.to_string(), to: resolved.clone() });
+            let effect = EffectRef::Builtin(Box::leak(resolved.into_boxed_str()));
+            run_effect(&effect, source, controller, state, scripting, cards, builtins, out, depth + 1)
+        }
+        EffectRef::Builtin(effect_str) if !is_custom_builtin(builtins, effect_str) && effect_str.starts_with("until_end_of_turn_") => {
+            let inner = &effect_str["until_end_of_turn_".len()..];
+            let effect = EffectRef::Builtin(Box::leak(inner.to_string().into_boxed_str()));
+            run_effect(&effect, source, controller, state, scripting, cards, builtins, out, depth + 1)
+                .map(until_end_of_turn)
+        }
+        EffectRef::Builtin(effect_str) => {
+            if !is_custom_builtin(builtins, effect_str)
+                && let Some((inner, players)) = each_player_effect(effect_str, controller, state)
+            {
+                let mut commands = Vec::new();
+                for player in players {
+                    let bound = inner.replace("_player_each", &format!("_player_{}", player.0));
+                    out.record(|| TraceStep::Resolved { depth, from: effect_str.to_string(), to: bound.clone() });
+                    let effect = EffectRef::Builtin(Box::leak(bound.into_boxed_str()));
+                    commands.extend(run_effect(&effect, source, controller, state, scripting, cards, builtins, out, depth + 1)?);
+                }
+                return Ok(commands);
+            }
+
+            let commands = builtin_commands(effect_str, source, controller, state, cards, builtins)?;
+            if let Some(target) = builtin_target(effect_str) {
+                out.record(|| TraceStep::Target { depth, target });
+            }
+            for command in &commands {
+                out.record(|| TraceStep::Produced { depth, command: command.clone() });
+            }
+            Ok(commands)
+        }
         EffectRef::Scripted(script_name) => {
             if let Some(engine) = scripting {
-                execute_scripted_effect(script_name, source, controller, engine)
+                let mut log = Vec::new();
+                let commands = execute_scripted_effect(script_name, source, controller, state, engine, cards, &mut log)?;
+                for message in &log {
+                    out.record(|| TraceStep::Log { depth, message: message.clone() });
+                }
+                out.log.extend(log);
+                for command in &commands {
+                    out.record(|| TraceStep::Produced { depth, command: command.clone() });
+                }
+                Ok(commands)
             } else {
                 Err(CardinalError(format!("Cannot execute scripted effect '{}': RhaiEngine not available", script_name)))
             }
         }
+        EffectRef::Sequence(effects) => {
+            // Each effect sees what the earlier ones did, e.g. a coin flip's result.
+            // The scratch state's RNG advances exactly as the real one will.
+            let mut next = state.clone();
+            let mut commands = Vec::new();
+            for effect in effects {
+                let step = run_effect(effect, source, controller, &next, scripting, cards, builtins, out, depth + 1)?;
+                crate::engine::events::commit_commands(&mut next, &step);
+                commands.extend(step);
+            }
+            Ok(commands)
+        }
+        EffectRef::Conditional(condition, effect) => {
+            // Like ability conditions, one that can't be evaluated counts as false
+            let met = matches!(crate::rules::query::evaluate_condition(condition, state, controller), Ok(true));
+            out.record(|| TraceStep::Condition { depth, condition: condition.to_string(), met });
+            if met {
+                run_effect(effect, source, controller, state, scripting, cards, builtins, out, depth + 1)
+            } else {
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+/// The commands of a builtin that needs no further resolving
+fn builtin_commands(
+    effect_str: &str,
+    source: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
+    cards: Option<&CardRegistry>,
+    builtins: Option<&BuiltinRegistry>,
+) -> Result<Vec<Command>, CardinalError> {
+    if let Some((handler, args)) = builtins.and_then(|b| b.find(effect_str)) {
+        handler(&BuiltinArgs { args, source, controller, state })
+    } else if let Some(rest) = effect_str.strip_prefix("move_all_") {
+        let cards = cards.ok_or_else(|| CardinalError(format!(
+            "Cannot execute '{}': card definitions not available",
+            effect_str
+        )))?;
+        move_all_commands(rest, effect_str, controller, state, cards)
+    } else if let Some(rest) = effect_str.strip_prefix("search_") {
+        search_commands(rest, effect_str, source, state, cards)
+    } else if let Some(rest) = effect_str.strip_prefix("cascade_player_") {
+        let cards = cards.ok_or_else(|| CardinalError(format!(
+            "Cannot execute '{}': card definitions not available",
+            effect_str
+        )))?;
+        cascade_commands(rest, effect_str, state, cards)
+    } else if let Some(card) = effect_str.strip_prefix("shuffle_into_deck_") {
+        shuffle_into_deck_commands(parse_card_suffix(card, effect_str)?, controller, state)
+    } else if let Some(rest) = effect_str.strip_prefix("transform_") {
+        let (card, into) = rest.split_once("_into_")
+            .ok_or_else(|| CardinalError(format!("Invalid transform effect: {}", effect_str)))?;
+        transform_commands(parse_card_suffix(card, effect_str)?, parse_card_suffix(into, effect_str)?, cards)
+    } else if let Some(rest) = effect_str.strip_prefix("gain_control_player_") {
+        let (player, card) = rest.split_once('_')
+            .ok_or_else(|| CardinalError(format!("Invalid gain_control effect: {}", effect_str)))?;
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player ID in: {}", effect_str)))?;
+        gain_control_commands(parse_card_suffix(card, effect_str)?, PlayerId(player), state)
+    } else if let Some(card) = effect_str.strip_prefix("tap_") {
+        set_tapped_commands(parse_card_suffix(card, effect_str)?, true, state)
+    } else if let Some(card) = effect_str.strip_prefix("untap_") {
+        set_tapped_commands(parse_card_suffix(card, effect_str)?, false, state)
+    } else if let Some(item) = effect_str.strip_prefix("counter_") {
+        let id = match item {
+            "top" => state.stack.last().map(|i| i.id)
+                .ok_or_else(|| CardinalError("Nothing on the stack to counter".to_string()))?,
+            id => id.parse::<u32>()
+                .map_err(|_| CardinalError(format!("Invalid stack item ID in: {}", effect_str)))?,
+        };
+        counter_commands(id, state)
+    } else if let Some(rest) = effect_str.strip_prefix("discard_random_") {
+        let (count, player) = parse_count_and_player(rest, effect_str)?;
+        Ok(vec![Command::DiscardRandom { player, count }])
+    } else if let Some(rest) = effect_str.strip_prefix("discard_choose_") {
+        discard_choose_commands(rest, effect_str, state)
+    } else if let Some(rest) = effect_str.strip_prefix("discard_chosen_from_player_") {
+        // Format: discard_chosen_from_player_{id}, bound to _card_{card} once chosen
+        let (player, chosen) = match rest.split_once("_card_") {
+            Some((player, card)) => (player, Some(parse_card_suffix(card, effect_str)?)),
+            None => (rest, None),
+        };
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player ID in: {}", effect_str)))?;
+        Ok(discard_chosen_from_commands(PlayerId(player), chosen, controller, state))
+    } else if let Some(rest) = effect_str.strip_prefix("discard_") {
+        // Format: discard_{count}[_player_{id}]; the discarding player picks the cards
+        let (count, player) = match rest.split_once("_player_") {
+            Some((count, player)) => (count, player.parse::<u8>().ok().map(PlayerId)),
+            None => (rest, Some(controller)),
+        };
+        let count = count.parse::<usize>()
+            .map_err(|_| CardinalError(format!("Invalid discard count in: {}", effect_str)))?;
+        let player = player
+            .ok_or_else(|| CardinalError(format!("Invalid player ID in: {}", effect_str)))?;
+        Ok(request_discard_choice(player, count, state))
+    } else if let Some(rest) = effect_str.strip_prefix("mill_") {
+        // Format: mill_{count}[_player_{id}]
+        let (count, player) = match rest.split_once("_player_") {
+            Some((count, player)) => (count, player.parse::<u8>().ok().map(PlayerId)),
+            None => (rest, Some(controller)),
+        };
+        let count = count.parse::<usize>()
+            .map_err(|_| CardinalError(format!("Invalid mill count in: {}", effect_str)))?;
+        let player = player
+            .ok_or_else(|| CardinalError(format!("Invalid player in: {}", effect_str)))?;
+        mill_commands(player, count, state)
+    } else if let Some(rest) = effect_str.strip_prefix("pump_") {
+        // Format: pump_{power}_{toughness}, applied to the effect's source card.
+        // Negative values shrink the card, so they aren't rejected.
+        let (power, toughness) = rest.split_once('_')
+            .and_then(|(power, toughness)| Some((power.parse::<i32>().ok()?, toughness.parse::<i32>().ok()?)))
+            .ok_or_else(|| CardinalError(format!("Invalid pump effect: {}", effect_str)))?;
+        let card = source
+            .ok_or_else(|| CardinalError(format!("Cannot execute '{}': it has no source card to pump", effect_str)))?;
+        Ok(vec![Command::ModifyStats { card, power, toughness, duration: Duration::Permanent }])
+    } else if let Some(rest) = effect_str.strip_prefix("additional_cost_") {
+        additional_cost_commands(rest, effect_str, state)
+    } else {
+        execute_builtin_effect(effect_str, controller)
     }
 }
 
+/// The effect as a trace shows it, e.g. "builtin damage_3_player_1"
+fn describe_effect(effect: &EffectRef) -> String {
+    match effect {
+        EffectRef::Builtin(name) => format!("builtin {}", name),
+        EffectRef::Scripted(name) => format!("script {}", name),
+        EffectRef::Sequence(effects) => format!("sequence of {}", effects.len()),
+        EffectRef::Conditional(condition, _) => format!("if {}", condition),
+    }
+}
+
+/// The player or card a bound builtin names last (`_player_{id}` or
+/// `_card_{id}`), if any
+fn builtin_target(effect_str: &str) -> Option<TargetRef> {
+    if let Some((_, player)) = effect_str.rsplit_once("_player_")
+        && let Ok(player) = player.parse::<u8>()
+    {
+        return Some(TargetRef::Player(PlayerId(player)));
+    }
+    let (_, card) = effect_str.rsplit_once("_card_")?;
+    card.parse::<u32>().ok().map(|card| TargetRef::Card(CardId(card)))
+}
+
+/// For `each_player_{effect}` / `each_opponent_{effect}`, the inner effect
+/// and the players it runs for, in seat order: every player starting with
+/// the controller, or the controller's opponents
+fn each_player_effect<'a>(effect_str: &'a str, controller: PlayerId, state: &GameState) -> Option<(&'a str, Vec<PlayerId>)> {
+    if let Some(inner) = effect_str.strip_prefix("each_player_") {
+        let players = std::iter::once(controller)
+            .chain(crate::rules::query::opponents_of(state, controller))
+            .collect();
+        Some((inner, players))
+    } else {
+        effect_str.strip_prefix("each_opponent_")
+            .map(|inner| (inner, crate::rules::query::opponents_of(state, controller)))
+    }
+}
+
+/// The builtin with each `{expression}` placeholder (left by an expression
+/// param, see `cards::spec_to_ref`) replaced by its value in `state`
+fn evaluate_placeholders(
+    effect_str: &str,
+    controller: PlayerId,
+    state: &GameState,
+    cards: Option<&CardRegistry>,
+) -> Result<String, CardinalError> {
+    let no_cards = CardRegistry::new();
+    let registry = cards.unwrap_or(&no_cards);
+    let mut result = String::new();
+    let mut rest = effect_str;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| CardinalError(format!("Unclosed placeholder in: {}", effect_str)))?;
+        let value = crate::rules::query::evaluate_amount(&rest[start + 1..end], state, controller, registry)?;
+        result.push_str(&rest[..start]);
+        result.push_str(&value.to_string());
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
 /// Execute a scripted effect via RhaiEngine
 fn execute_scripted_effect(
     script_name: &str,
     source: Option<CardId>,
     controller: PlayerId,
+    state: &GameState,
     engine: &RhaiEngine,
+    cards: Option<&CardRegistry>,
+    log: &mut Vec<String>,
 ) -> Result<Vec<Command>, CardinalError> {
+    let zones = state.zones.iter()
+        .map(|z| (z.id.0.to_string(), z.cards.iter().map(|c| c.0).collect()))
+        .collect();
+    let no_cards = CardRegistry::new();
+    let registry = cards.unwrap_or(&no_cards);
+    let snapshots = state.zones.iter()
+        .flat_map(|z| z.cards.iter().copied())
+        .chain(state.card_instances.keys().copied())
+        .map(|card| {
+            let (power, toughness) = crate::rules::query::effective_stats(card, registry, state);
+            let counters = state.card_instances.get(&card)
+                .map(|i| i.counters.clone())
+                .unwrap_or_default();
+            (card.0, CardSnapshot { power, toughness, counters })
+        })
+        .collect();
     let context = ScriptContext {
         controller: controller.0,
         source_card: source.map(|c| c.0).unwrap_or(0),
         active_player: None,
         turn_number: None,
         phase: None,
+        zones: Some(zones),
+        cards: Some(snapshots),
     };
     
-    let results = engine.execute_ability(script_name, context)?;
+    let output = engine.execute_ability_logged(script_name, context)?;
+    log.extend(output.log);
+    let results = output.effects;
     
     // Convert Rhai Dynamic results into Commands
     let mut commands = Vec::new();
@@ -70,6 +532,7 @@ fn execute_scripted_effect(
                 script_name, index
             )))?;
         
+        let start = commands.len();
         match effect_type.as_str() {
             "damage" => {
                 let target = extract_i32(&map, "target", script_name)?;
@@ -79,11 +542,30 @@ fn execute_scripted_effect(
                 validate_non_negative(amount, "amount", script_name)?;
                 validate_u8_range(target, "target", script_name)?;
                 
-                commands.push(Command::ChangeLife {
-                    player: PlayerId(target as u8),
-                    delta: -amount,
+                commands.push(Command::DealDamage {
+                    target: TargetRef::Player(PlayerId(target as u8)),
+                    amount,
+                    combat: false,
                 });
             }
+            "prevent_damage" => {
+                let amount = extract_i32(&map, "amount", script_name)?;
+                validate_non_negative(amount, "amount", script_name)?;
+                let target = if map.contains_key("card") {
+                    let card = extract_i32(&map, "card", script_name)?;
+                    validate_non_negative(card, "card", script_name)?;
+                    TargetRef::Card(CardId(card as u32))
+                } else {
+                    let player = extract_i32(&map, "player", script_name)?;
+                    validate_non_negative(player, "player", script_name)?;
+                    validate_u8_range(player, "player", script_name)?;
+                    TargetRef::Player(PlayerId(player as u8))
+                };
+                let combat_only = map.get("combat_only")
+                    .and_then(|v| v.as_bool().ok())
+                    .unwrap_or(false);
+                commands.push(Command::PreventDamage { target, amount, combat_only });
+            }
             "draw" => {
                 let player = extract_i32(&map, "player", script_name)?;
                 let count = extract_i32(&map, "count", script_name)?;
@@ -92,9 +574,7 @@ fn execute_scripted_effect(
                 validate_u8_range(player, "player", script_name)?;
                 validate_positive(count, "count", script_name)?;
                 
-                // Draw cards: move from deck to hand
-                // For now, we don't have deck/hand tracking, so this is a placeholder
-                // In a full implementation, this would generate MoveCard commands
+                commands.push(Command::DrawCards { player: PlayerId(player as u8), count: count as u32 });
             }
             "gain_life" => {
                 let player = extract_i32(&map, "player", script_name)?;
@@ -143,17 +623,7 @@ fn execute_scripted_effect(
                 validate_non_negative(count, "count", script_name)?;
                 validate_u8_range(player, "player", script_name)?;
                 
-                // TODO: Implement milling (deck to graveyard)
-            }
-            "discard" => {
-                let player = extract_i32(&map, "player", script_name)?;
-                let count = extract_i32(&map, "count", script_name)?;
-                
-                validate_non_negative(player, "player", script_name)?;
-                validate_non_negative(count, "count", script_name)?;
-                validate_u8_range(player, "player", script_name)?;
-                
-                // TODO: Implement discarding (hand to graveyard)
+                commands.extend(mill_commands(PlayerId(player as u8), count as usize, state)?);
             }
             "move_card" => {
                 let card = extract_i32(&map, "card", script_name)?;
@@ -165,13 +635,108 @@ fn execute_scripted_effect(
                 // Convert zone strings to ZoneId
                 let from_zone_id = string_to_zone_id(&from_zone);
                 let to_zone_id = string_to_zone_id(&to_zone);
+                let position = extract_position(&map, script_name)?;
                 
                 commands.push(Command::MoveCard {
                     card: CardId(card as u32),
                     from: from_zone_id,
                     to: to_zone_id,
+                    position,
                 });
             }
+            "shuffle_into_deck" => {
+                let card = extract_i32(&map, "card", script_name)?;
+                validate_non_negative(card, "card", script_name)?;
+                commands.extend(shuffle_into_deck_commands(CardId(card as u32), controller, state)?);
+            }
+            "transform" => {
+                let card = extract_i32(&map, "card", script_name)?;
+                let into = extract_i32(&map, "into", script_name)?;
+                validate_non_negative(card, "card", script_name)?;
+                validate_non_negative(into, "into", script_name)?;
+                commands.extend(transform_commands(CardId(card as u32), CardId(into as u32), cards)?);
+            }
+            "gain_control" => {
+                let card = extract_i32(&map, "card", script_name)?;
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(card, "card", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                commands.extend(gain_control_commands(CardId(card as u32), PlayerId(player as u8), state)?);
+            }
+            "tap" | "untap" => {
+                let card = extract_i32(&map, "card", script_name)?;
+                validate_non_negative(card, "card", script_name)?;
+                commands.extend(set_tapped_commands(CardId(card as u32), effect_type == "tap", state)?);
+            }
+            "discard" | "discard_random" | "discard_choose" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                let count = extract_i32(&map, "count", script_name)?;
+                
+                validate_non_negative(player, "player", script_name)?;
+                validate_non_negative(count, "count", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                
+                let player = PlayerId(player as u8);
+                if effect_type == "discard_random" {
+                    commands.push(Command::DiscardRandom { player, count: count as usize });
+                } else {
+                    commands.extend(request_discard_choice(player, count as usize, state));
+                }
+            }
+            "discard_chosen_from" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                commands.extend(discard_chosen_from_commands(PlayerId(player as u8), None, controller, state));
+            }
+            "coin_flip" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                commands.push(Command::FlipCoin { player: PlayerId(player as u8) });
+            }
+            "extra_turn" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                commands.push(Command::AddExtraTurn { player: PlayerId(player as u8) });
+            }
+            "skip_next" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                let id = map.get("step")
+                    .and_then(|v| v.clone().try_cast::<String>())
+                    .ok_or_else(|| CardinalError(format!(
+                        "Script '{}' skip_next is missing a string 'step' field",
+                        script_name
+                    )))?;
+                commands.push(Command::SkipNext { player: PlayerId(player as u8), id });
+            }
+            "cascade" => {
+                let player = extract_i32(&map, "player", script_name)?;
+                validate_non_negative(player, "player", script_name)?;
+                validate_u8_range(player, "player", script_name)?;
+                let filter = map.get("filter")
+                    .and_then(|v| v.clone().try_cast::<String>())
+                    .unwrap_or_default();
+                let cast = map.get("cast").and_then(|v| v.as_bool().ok()).unwrap_or(true);
+                let cards = cards.ok_or_else(|| CardinalError(format!(
+                    "Script '{}' cascade needs card definitions",
+                    script_name
+                )))?;
+                let mut rest = format!("{}_{}", player, if cast { "cast" } else { "hand" });
+                if !filter.is_empty() {
+                    rest.push_str(&format!("_where_{}", filter));
+                }
+                commands.extend(cascade_commands(&rest, &format!("cascade_player_{}", rest), state, cards)?);
+            }
+            "counter" => {
+                let id = extract_i32(&map, "stack_item", script_name)?;
+                validate_non_negative(id, "stack_item", script_name)?;
+                commands.extend(counter_commands(id as u32, state)?);
+            }
             "shuffle_zone" => {
                 let _player = extract_i32(&map, "player", script_name)?;
                 let _zone = extract_string(&map, "zone", script_name)?;
@@ -195,6 +760,7 @@ fn execute_scripted_effect(
                     card: CardId(card as u32),
                     power,
                     toughness,
+                    duration: Duration::Permanent,
                 });
             }
             "set_stats" => {
@@ -245,6 +811,7 @@ fn execute_scripted_effect(
                 commands.push(Command::GrantKeyword {
                     card: CardId(card as u32),
                     keyword,
+                    duration: Duration::Permanent,
                 });
             }
             "remove_keyword" => {
@@ -354,11 +921,45 @@ fn execute_scripted_effect(
                 )));
             }
         }
+
+        // `until_end_of_turn` marks effects that wear off as the turn ends
+        if map.get("duration").and_then(|d| d.clone().try_cast::<String>()).as_deref() == Some("end_of_turn") {
+            let effect = commands.split_off(start);
+            commands.extend(until_end_of_turn(effect));
+        }
     }
     
     Ok(commands)
 }
 
+/// `commands` made to wear off at end of turn: pumps and granted keywords
+/// are marked `Duration::UntilEndOfTurn`, and other changes are followed by
+/// a cleanup that undoes them (see `GameState::end_of_turn`). Commands with
+/// nothing to undo, like damage or a draw, stay as they are.
+fn until_end_of_turn(commands: Vec<Command>) -> Vec<Command> {
+    commands.into_iter()
+        .flat_map(|command| {
+            let (command, undo) = match command {
+                Command::ModifyStats { card, power, toughness, .. } => {
+                    (Command::ModifyStats { card, power, toughness, duration: Duration::UntilEndOfTurn }, None)
+                }
+                Command::GrantKeyword { card, keyword, .. } => {
+                    (Command::GrantKeyword { card, keyword, duration: Duration::UntilEndOfTurn }, None)
+                }
+                Command::ModifyStat { card, stat_name, delta } => {
+                    let undo = Command::ModifyStat { card, stat_name: stat_name.clone(), delta: -delta };
+                    (Command::ModifyStat { card, stat_name, delta }, Some(undo))
+                }
+                Command::GainControl { card, player } => {
+                    (Command::GainControl { card, player }, Some(Command::ReleaseControl { card }))
+                }
+                command => (command, None),
+            };
+            std::iter::once(command).chain(undo.map(|undo| Command::AtEndOfTurn { command: Box::new(undo) }))
+        })
+        .collect()
+}
+
 // Helper functions to extract and validate values from Rhai maps
 fn extract_i32(map: &rhai::Map, key: &str, script_name: &str) -> Result<i32, CardinalError> {
     map.get(key)
@@ -388,6 +989,467 @@ fn extract_string(map: &rhai::Map, key: &str, script_name: &str) -> Result<Strin
         )))
 }
 
+/// Move a card from whatever zone holds it into its owner's deck, then shuffle
+/// that deck. The owner is the owner of the card's current zone (the
+/// controller for shared zones).
+fn shuffle_into_deck_commands(
+    card: CardId,
+    controller: PlayerId,
+    state: &GameState,
+) -> Result<Vec<Command>, CardinalError> {
+    let zone = state.zones.iter()
+        .find(|z| z.cards.contains(&card))
+        .ok_or_else(|| CardinalError(format!("Card {} is not in any zone", card.0)))?;
+    let owner = zone.owner.unwrap_or(controller);
+
+    let deck_id = format!("deck@{}", owner.0);
+    let deck = state.zones.iter()
+        .find(|z| z.id.0 == deck_id)
+        .ok_or_else(|| CardinalError(format!("Player {} has no deck zone", owner.0)))?;
+
+    Ok(vec![
+        Command::MoveCard { card, from: zone.id.clone(), to: deck.id.clone(), position: None },
+        Command::ShuffleZone { zone: deck.id.clone() },
+    ])
+}
+
+/// `move_all_{from}_to_{to}[_player_{p}][_where_{filter}]`: move every card
+/// in a `from` zone that matches the filter to the `to` zone of the same
+/// owner (or the shared `to` zone), in zone order. Only player `p`'s zones
+/// are swept if one is given, otherwise every player's. Cards without a
+/// definition only match an empty filter.
+fn move_all_commands(
+    rest: &str,
+    effect_str: &str,
+    controller: PlayerId,
+    state: &GameState,
+    cards: &CardRegistry,
+) -> Result<Vec<Command>, CardinalError> {
+    let invalid = || CardinalError(format!("Invalid move_all effect: {}", effect_str));
+    let (rest, filter) = rest.split_once("_where_").unwrap_or((rest, ""));
+    let query = CardQuery::parse(filter)?;
+    let (zones, player) = match rest.split_once("_player_") {
+        Some((zones, player)) => (zones, Some(PlayerId(player.parse::<u8>().map_err(|_| invalid())?))),
+        None => (rest, None),
+    };
+    let (from, to) = zones.split_once("_to_").ok_or_else(invalid)?;
+
+    let mut commands = Vec::new();
+    for zone in &state.zones {
+        let base = zone.id.0.split('@').next().unwrap_or("");
+        if base != from || (player.is_some() && zone.owner != player) {
+            continue;
+        }
+
+        let owner = zone.owner.unwrap_or(controller);
+        let owned = format!("{}@{}", to, owner.0);
+        let destination = state.zones.iter()
+            .find(|z| z.id.0 == owned)
+            .or_else(|| state.zones.iter().find(|z| z.id.0 == to))
+            .ok_or_else(|| CardinalError(format!("No '{}' zone for player {}", to, owner.0)))?;
+
+        for card in &zone.cards {
+            if query.matches_card(*card, cards, state) {
+                commands.push(Command::MoveCard {
+                    card: *card,
+                    from: zone.id.clone(),
+                    to: destination.id.clone(),
+                    position: None,
+                });
+            }
+        }
+    }
+    Ok(commands)
+}
+
+/// Move the top `count` cards of `player`'s deck to their graveyard, top
+/// card first; a smaller deck is milled entirely
+fn mill_commands(player: PlayerId, count: usize, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    let zone = |base: &str| {
+        let id = format!("{}@{}", base, player.0);
+        state.zones.iter()
+            .find(|z| z.id.0 == id)
+            .ok_or_else(|| CardinalError(format!("No '{}' zone for player {}", base, player.0)))
+    };
+    let (deck, graveyard) = (zone("deck")?, zone("graveyard")?);
+    Ok(deck.cards.iter()
+        .take(count)
+        .map(|card| Command::MoveCard { card: *card, from: deck.id.clone(), to: graveyard.id.clone(), position: None })
+        .collect())
+}
+
+/// `search_{max}_player_{p}_{zone}_to_{to}[_where_{filter}]`: player `p`
+/// picks up to `max` cards matching the filter from their `zone`. The search
+/// waits on the stack for a `ChooseCards` choice; once it is bound to the
+/// picks (`_cards_{id},...`) they move to `p`'s `to` zone and `zone` is
+/// shuffled. With nothing to pick, `zone` is just shuffled.
+fn search_commands(
+    rest: &str,
+    effect_str: &str,
+    source: Option<CardId>,
+    state: &GameState,
+    cards: Option<&CardRegistry>,
+) -> Result<Vec<Command>, CardinalError> {
+    let invalid = || CardinalError(format!("Invalid search effect: {}", effect_str));
+    if rest.ends_with("_target") {
+        return Err(CardinalError(format!("Search has not been chosen yet: {}", effect_str)));
+    }
+    let (rest, chosen) = match rest.rsplit_once("_cards_") {
+        Some((rest, ids)) => {
+            let ids = ids.split(',')
+                .filter(|id| !id.is_empty())
+                .map(|id| id.parse::<u32>().map(CardId).map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+            (rest, Some(ids))
+        }
+        None => (rest, None),
+    };
+    let (rest, filter) = rest.split_once("_where_").unwrap_or((rest, ""));
+    let (max, rest) = rest.split_once("_player_").ok_or_else(invalid)?;
+    let max = max.parse::<usize>().map_err(|_| invalid())?;
+    let (player, zones) = rest.split_once('_').ok_or_else(invalid)?;
+    let player = PlayerId(player.parse::<u8>().map_err(|_| invalid())?);
+    let (from, to) = zones.split_once("_to_").ok_or_else(invalid)?;
+
+    // The player's own zone, or the shared one of that name
+    let zone_of = |name: &str| {
+        let owned = format!("{}@{}", name, player.0);
+        state.zones.iter()
+            .find(|z| z.id.0 == owned)
+            .or_else(|| state.zones.iter().find(|z| z.id.0 == name))
+            .ok_or_else(|| CardinalError(format!("No '{}' zone for player {}", name, player.0)))
+    };
+    let zone = zone_of(from)?;
+
+    let Some(chosen) = chosen else {
+        let cards = cards.ok_or_else(|| CardinalError(format!(
+            "Cannot execute '{}': card definitions not available",
+            effect_str
+        )))?;
+        let filter = CardQuery::parse(filter)?;
+        if max == 0 || !zone.cards.iter().any(|card| filter.matches_card(*card, cards, state)) {
+            return Ok(vec![Command::ShuffleZone { zone: zone.id.clone() }]);
+        }
+
+        let effect = format!("{}_target", effect_str);
+        return Ok(vec![
+            Command::PushStack {
+                item: StackItem {
+                    id: PLACEHOLDER_ID,
+                    source,
+                    controller: player,
+                    effect: EffectRef::Builtin(Box::leak(effect.into_boxed_str())),
+                },
+            },
+            Command::RequestChoice {
+                player,
+                choice: PendingChoice {
+                    id: PLACEHOLDER_ID,
+                    prompt: format!("Choose up to {} card(s) from {}", max, zone.id.0),
+                    kind: ChoiceKind::ChooseCards { zone: zone.id.clone(), filter, max },
+                    stack_item: Some(PLACEHOLDER_ID),
+                },
+            },
+        ]);
+    };
+
+    let destination = zone_of(to)?;
+    let mut commands: Vec<Command> = chosen.into_iter()
+        // A card that left the zone in the meantime stays where it is
+        .filter(|card| zone.cards.contains(card))
+        .map(|card| Command::MoveCard { card, from: zone.id.clone(), to: destination.id.clone(), position: None })
+        .collect();
+    commands.push(Command::ShuffleZone { zone: zone.id.clone() });
+    Ok(commands)
+}
+
+/// `cascade_player_{p}_{cast|hand}[_where_{filter}]`: player `p` reveals
+/// cards from the top of their deck until one matches the filter. With
+/// `cast` the hit is put onto their field and cast without paying its cost,
+/// so its `on_play` abilities trigger; with `hand` it goes to their hand.
+/// The cards revealed before it go to their graveyard, in reveal order. With
+/// no match, the whole deck is revealed and binned.
+fn cascade_commands(
+    rest: &str,
+    effect_str: &str,
+    state: &GameState,
+    cards: &CardRegistry,
+) -> Result<Vec<Command>, CardinalError> {
+    let invalid = || CardinalError(format!("Invalid cascade effect: {}", effect_str));
+    let (rest, filter) = rest.split_once("_where_").unwrap_or((rest, ""));
+    let query = CardQuery::parse(filter)?;
+    let (player, mode) = rest.split_once('_').ok_or_else(invalid)?;
+    let player = PlayerId(player.parse::<u8>().map_err(|_| invalid())?);
+    let cast = match mode {
+        "cast" => true,
+        "hand" => false,
+        _ => return Err(invalid()),
+    };
+
+    let zone = |name: &str| {
+        let id = format!("{}@{}", name, player.0);
+        state.zones.iter()
+            .find(|z| z.id.0 == id)
+            .ok_or_else(|| CardinalError(format!("No '{}' zone for player {}", name, player.0)))
+    };
+    let deck = zone("deck")?;
+    let graveyard = zone("graveyard")?;
+
+    let hit = deck.cards.iter().position(|card| query.matches_card(*card, cards, state));
+    let revealed = &deck.cards[..hit.map_or(deck.cards.len(), |i| i + 1)];
+    if revealed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut commands = vec![Command::RevealCards { player, cards: revealed.to_vec() }];
+    commands.extend(revealed[..hit.unwrap_or(revealed.len())].iter().map(|card| Command::MoveCard {
+        card: *card,
+        from: deck.id.clone(),
+        to: graveyard.id.clone(),
+        position: None,
+    }));
+    if let Some(i) = hit {
+        let card = deck.cards[i];
+        if cast {
+            commands.extend(crate::engine::cards::with_entry_modifiers(
+                vec![Command::MoveCard { card, from: deck.id.clone(), to: zone("field")?.id.clone(), position: None }],
+                cards,
+                state,
+            ));
+            commands.push(Command::CastCard { player, card });
+        } else {
+            commands.push(Command::MoveCard { card, from: deck.id.clone(), to: zone("hand")?.id.clone(), position: None });
+        }
+    }
+    Ok(commands)
+}
+
+/// Card ID at the end of a builtin string, optionally written as `card_C`
+fn parse_card_suffix(card: &str, effect_str: &str) -> Result<CardId, CardinalError> {
+    card.strip_prefix("card_").unwrap_or(card).parse::<u32>()
+        .map(CardId)
+        .map_err(|_| CardinalError(format!("Invalid card ID in: {}", effect_str)))
+}
+
+/// Turn `card` into the definition `into`, which must exist
+fn transform_commands(card: CardId, into: CardId, cards: Option<&CardRegistry>) -> Result<Vec<Command>, CardinalError> {
+    let cards = cards.ok_or_else(|| CardinalError(format!(
+        "Cannot transform card {}: card definitions not available",
+        card.0
+    )))?;
+    if !cards.contains_key(&into.0) {
+        return Err(CardinalError(format!("Cannot transform card {}: no card {} is defined", card.0, into.0)));
+    }
+    Ok(vec![Command::Transform { card, into }])
+}
+
+/// `player` gains control of a card on the field
+fn gain_control_commands(card: CardId, player: PlayerId, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    let on_field = state.zones.iter()
+        .any(|z| z.id.0.starts_with("field") && z.cards.contains(&card));
+    if !on_field {
+        return Err(CardinalError(format!("Cannot gain control of card {}: it is not on the field", card.0)));
+    }
+    if !state.players.iter().any(|p| p.id == player) {
+        return Err(CardinalError(format!("Player {} does not exist", player.0)));
+    }
+    Ok(vec![Command::GainControl { card, player }])
+}
+
+/// Tap or untap a card. Only permanents (cards on a field zone) can be tapped.
+fn set_tapped_commands(card: CardId, tapped: bool, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    let on_field = state.zones.iter()
+        .any(|z| z.id.0.starts_with("field") && z.cards.contains(&card));
+    if !on_field {
+        return Err(CardinalError(format!(
+            "Cannot {} card {}: it is not a permanent on the field",
+            if tapped { "tap" } else { "untap" },
+            card.0
+        )));
+    }
+    Ok(vec![Command::SetTapped { card, tapped }])
+}
+
+/// Counter the stack item with the given ID, which must be on the stack
+fn counter_commands(id: u32, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    if !state.stack.iter().any(|item| item.id == id) {
+        return Err(CardinalError(format!("Stack item {} is not on the stack", id)));
+    }
+    Ok(vec![Command::RemoveStackItem { id }])
+}
+
+/// `{count}_player_{player}`, as used by the discard builtins
+fn parse_count_and_player(rest: &str, effect_str: &str) -> Result<(usize, PlayerId), CardinalError> {
+    rest.split_once("_player_")
+        .and_then(|(count, player)| Some((count.parse::<usize>().ok()?, PlayerId(player.parse::<u8>().ok()?))))
+        .ok_or_else(|| CardinalError(format!("Invalid discard effect: {}", effect_str)))
+}
+
+/// `discard_choose_{count}_player_{player}`, optionally bound to `_card_{card}`
+/// once the player has picked. Discards the picked card, then asks again
+/// while cards remain to be discarded and the hand isn't empty.
+fn discard_choose_commands(rest: &str, effect_str: &str, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    let (rest, chosen) = match rest.split_once("_card_") {
+        Some((rest, card)) => (rest, Some(parse_card_suffix(card, effect_str)?)),
+        None => (rest, None),
+    };
+    let (count, player) = parse_count_and_player(rest, effect_str)?;
+
+    let mut commands = Vec::new();
+    let mut remaining = count;
+    if let Some(card) = chosen {
+        let hand = state.zones.iter().find(|z| z.id.0 == format!("hand@{}", player.0));
+        let graveyard = state.zones.iter().find(|z| z.id.0 == format!("graveyard@{}", player.0));
+        // A card that left the hand in the meantime is simply not discarded
+        if let (Some(hand), Some(graveyard)) = (hand, graveyard)
+            && hand.cards.contains(&card)
+        {
+            commands.push(Command::MoveCard { card, from: hand.id.clone(), to: graveyard.id.clone(), position: None });
+        }
+        remaining = remaining.saturating_sub(1);
+    }
+
+    let mut next = state.clone();
+    crate::engine::events::commit_commands(&mut next, &commands);
+    commands.extend(request_discard_choice(player, remaining, &next));
+    Ok(commands)
+}
+
+/// `additional_cost_{sacrifice|discard}_{card}_player_{player}_card_{chosen}`:
+/// pay the additional cost of `card` by putting the chosen card into its
+/// owner's graveyard, then play `card` (see `AdditionalCost`)
+fn additional_cost_commands(rest: &str, effect_str: &str, state: &GameState) -> Result<Vec<Command>, CardinalError> {
+    let invalid = || CardinalError(format!("Invalid additional cost effect: {}", effect_str));
+    let (kind, rest) = rest.split_once('_').ok_or_else(invalid)?;
+    let (card, rest) = rest.split_once("_player_").ok_or_else(invalid)?;
+    let (player, chosen) = rest.split_once("_card_")
+        .ok_or_else(|| CardinalError(format!("Cannot execute '{}': no card chosen yet", effect_str)))?;
+    let card = parse_card_suffix(card, effect_str)?;
+    let player = PlayerId(player.parse::<u8>().map_err(|_| invalid())?);
+    let chosen = parse_card_suffix(chosen, effect_str)?;
+
+    let from = match kind {
+        "sacrifice" => state.zones.iter()
+            .find(|z| z.id.0.starts_with("field") && z.cards.contains(&chosen))
+            .filter(|_| state.controller_of(chosen) == Some(player)),
+        "discard" => state.zones.iter()
+            .find(|z| z.id.0 == format!("hand@{}", player.0) && z.cards.contains(&chosen)),
+        _ => return Err(invalid()),
+    };
+    let from = from.ok_or_else(|| CardinalError(format!(
+        "Cannot pay the additional cost: card {} is no longer available", chosen.0
+    )))?;
+    let owner = from.owner.unwrap_or(player);
+    let graveyard = state.zones.iter()
+        .find(|z| z.id.0 == format!("graveyard@{}", owner.0))
+        .ok_or_else(|| CardinalError(format!("Player {} has no graveyard", owner.0)))?;
+
+    Ok(vec![
+        Command::MoveCard { card: chosen, from: from.id.clone(), to: graveyard.id.clone(), position: None },
+        Command::CastCard { player, card },
+    ])
+}
+
+/// Show `player`'s hand to `controller` alone and ask `controller` to pick a
+/// card from it. Once `chosen`, that card goes to `player`'s graveyard, unless
+/// it left the hand in the meantime.
+fn discard_chosen_from_commands(
+    player: PlayerId,
+    chosen: Option<CardId>,
+    controller: PlayerId,
+    state: &GameState,
+) -> Vec<Command> {
+    let hand = state.zones.iter().find(|z| z.id.0 == format!("hand@{}", player.0));
+    let graveyard = state.zones.iter().find(|z| z.id.0 == format!("graveyard@{}", player.0));
+    let (Some(hand), Some(graveyard)) = (hand, graveyard) else {
+        return Vec::new();
+    };
+
+    if let Some(card) = chosen {
+        if !hand.cards.contains(&card) {
+            return Vec::new();
+        }
+        return vec![Command::MoveCard { card, from: hand.id.clone(), to: graveyard.id.clone(), position: None }];
+    }
+
+    let mut commands = vec![Command::RevealHandTo { player, viewer: controller }];
+    if hand.cards.is_empty() {
+        return commands;
+    }
+    let effect = format!("discard_chosen_from_player_{}_target", player.0);
+    commands.extend([
+        Command::PushStack {
+            item: StackItem {
+                id: PLACEHOLDER_ID,
+                source: None,
+                controller,
+                effect: EffectRef::Builtin(Box::leak(effect.into_boxed_str())),
+            },
+        },
+        Command::RequestChoice {
+            player: controller,
+            choice: PendingChoice {
+                id: PLACEHOLDER_ID,
+                prompt: format!("Choose a card from player {}'s hand to discard", player.0),
+                kind: ChoiceKind::ChooseTarget { allowed: AllowedTargets::CardInZone(hand.id.clone()) },
+                stack_item: Some(PLACEHOLDER_ID),
+            },
+        },
+    ]);
+    commands
+}
+
+/// Ask `player` to pick a card from their hand to discard, `count` times in
+/// all. The discard waits on the stack until the choice is answered.
+fn request_discard_choice(player: PlayerId, count: usize, state: &GameState) -> Vec<Command> {
+    let Some(hand) = state.zones.iter().find(|z| z.id.0 == format!("hand@{}", player.0)) else {
+        return Vec::new();
+    };
+    if count == 0 || hand.cards.is_empty() {
+        return Vec::new();
+    }
+
+    let effect = format!("discard_choose_{}_player_{}_target", count, player.0);
+    vec![
+        Command::PushStack {
+            item: StackItem {
+                id: PLACEHOLDER_ID,
+                source: None,
+                controller: player,
+                effect: EffectRef::Builtin(Box::leak(effect.into_boxed_str())),
+            },
+        },
+        Command::RequestChoice {
+            player,
+            choice: PendingChoice {
+                id: PLACEHOLDER_ID,
+                prompt: format!("Choose a card to discard ({} left)", count),
+                kind: ChoiceKind::ChooseTarget { allowed: AllowedTargets::CardInZone(hand.id.clone()) },
+                stack_item: Some(PLACEHOLDER_ID),
+            },
+        },
+    ]
+}
+
+/// Optional `position` field: "top", "bottom", or a non-negative index from the top
+fn extract_position(map: &rhai::Map, script_name: &str) -> Result<Option<ZonePosition>, CardinalError> {
+    let Some(value) = map.get("position") else {
+        return Ok(None);
+    };
+    if let Some(index) = value.clone().try_cast::<i32>() {
+        validate_non_negative(index, "position", script_name)?;
+        return Ok(Some(ZonePosition::Index(index as usize)));
+    }
+    match value.clone().try_cast::<String>().as_deref() {
+        Some("top") => Ok(Some(ZonePosition::Top)),
+        Some("bottom") => Ok(Some(ZonePosition::Bottom)),
+        _ => Err(CardinalError(format!(
+            "Script '{}' effect has invalid 'position' (expected \"top\", \"bottom\" or an index)",
+            script_name
+        ))),
+    }
+}
+
 fn validate_non_negative(value: i32, field: &str, script_name: &str) -> Result<(), CardinalError> {
     if value < 0 {
         return Err(CardinalError(format!(
@@ -430,14 +1492,47 @@ fn string_to_zone_id(zone_str: &str) -> ZoneId {
     ZoneId(static_str)
 }
 
+/// Target suffix of a builtin effect string
+enum BuiltinTarget {
+    /// No suffix; the effect's default (usually the controller)
+    Default,
+    Player(PlayerId),
+    Card(CardId),
+}
+
+impl BuiltinTarget {
+    /// The target as a `TargetRef`, with `Default` meaning `controller`
+    fn resolve(self, controller: PlayerId) -> TargetRef {
+        match self {
+            BuiltinTarget::Default => TargetRef::Player(controller),
+            BuiltinTarget::Player(player) => TargetRef::Player(player),
+            BuiltinTarget::Card(card) => TargetRef::Card(card),
+        }
+    }
+}
+
+/// Parse "{amount}", "{amount}_player_{id}" or "{amount}_card_{id}"
+///
+/// Returns `None` on a malformed string, including an unbound `_target`
+/// placeholder (the target choice was never made).
+fn parse_amount_and_target(rest: &str) -> Option<(i32, BuiltinTarget)> {
+    if let Some((amount, player)) = rest.split_once("_player_") {
+        return Some((amount.parse().ok()?, BuiltinTarget::Player(PlayerId(player.parse().ok()?))));
+    }
+    if let Some((amount, card)) = rest.split_once("_card_") {
+        return Some((amount.parse().ok()?, BuiltinTarget::Card(CardId(card.parse().ok()?))));
+    }
+    Some((rest.parse().ok()?, BuiltinTarget::Default))
+}
+
 /// Execute a builtin effect parsed from its string representation
 /// Format: "{effect_type}_{param1}_{param2}..."
-/// Examples: "damage_2", "draw_1", "gain_life_3", "pump_1_1"
+/// Examples: "damage_2", "draw_1", "gain_life_3"
 fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<Command>, CardinalError> {
     // Handle different effect patterns
     if effect_str.starts_with("damage_") {
-        let amount = effect_str.strip_prefix("damage_")
-            .and_then(|s| s.parse::<i32>().ok())
+        // Format: damage_{amount}[_player_{id} | _card_{id}]
+        let (amount, target) = parse_amount_and_target(effect_str.strip_prefix("damage_").unwrap_or(""))
             .ok_or_else(|| CardinalError(format!("Invalid damage amount in: {}", effect_str)))?;
         
         // Validate amount is non-negative to prevent healing via damage
@@ -448,17 +1543,54 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             )));
         }
         
-        // TODO: Add proper target selection
-        // For now, damage affects the controller as a placeholder
-        // Future: request target via PendingChoice, then apply to selected target
-        Ok(vec![Command::ChangeLife {
-            player: controller,
-            delta: -amount,
-        }])
+        // Without an explicit target, damage affects the controller (legacy behavior)
+        Ok(vec![Command::DealDamage { target: target.resolve(controller), amount, combat: false }])
+    } else if let Some((rest, combat_only)) = effect_str.strip_prefix("prevent_damage_")
+        .map(|rest| (rest, false))
+        .or_else(|| effect_str.strip_prefix("prevent_combat_damage_").map(|rest| (rest, true)))
+    {
+        // Format: prevent[_combat]_damage_{amount}[_player_{id} | _card_{id}]
+        let (amount, target) = parse_amount_and_target(rest)
+            .ok_or_else(|| CardinalError(format!("Invalid prevention amount in: {}", effect_str)))?;
+        if amount < 0 {
+            return Err(CardinalError(format!(
+                "Builtin prevent_damage effect has negative amount: {} (effect: {})",
+                amount, effect_str
+            )));
+        }
+        Ok(vec![Command::PreventDamage { target: target.resolve(controller), amount, combat_only }])
+    } else if let Some(player) = effect_str.strip_prefix("coin_flip_player_") {
+        // Format: coin_flip_player_{id}
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player in: {}", effect_str)))?;
+        Ok(vec![Command::FlipCoin { player: PlayerId(player) }])
+    } else if let Some(player) = effect_str.strip_prefix("extra_turn_player_") {
+        // Format: extra_turn_player_{id}
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player in: {}", effect_str)))?;
+        Ok(vec![Command::AddExtraTurn { player: PlayerId(player) }])
+    } else if let Some(rest) = effect_str.strip_prefix("skip_next_") {
+        // Format: skip_next_{step or phase}_player_{id}
+        let (id, player) = rest.rsplit_once("_player_")
+            .ok_or_else(|| CardinalError(format!("Invalid skip effect: {}", effect_str)))?;
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player in: {}", effect_str)))?;
+        Ok(vec![Command::SkipNext { player: PlayerId(player), id: id.to_string() }])
+    } else if let Some(player) = effect_str.strip_prefix("reveal_hand_player_") {
+        // Format: reveal_hand_player_{id}
+        let player = player.parse::<u8>()
+            .map_err(|_| CardinalError(format!("Invalid player in: {}", effect_str)))?;
+        Ok(vec![Command::RevealHand { player: PlayerId(player) }])
     } else if effect_str.starts_with("draw_") {
-        let count = effect_str.strip_prefix("draw_")
-            .and_then(|s| s.parse::<u32>().ok())
+        // Format: draw_{count}[_player_{id}]
+        let (count, player) = match effect_str.strip_prefix("draw_").unwrap_or("").split_once("_player_") {
+            Some((count, player)) => (count, player.parse::<u8>().ok().map(PlayerId)),
+            None => (effect_str.strip_prefix("draw_").unwrap_or(""), Some(controller)),
+        };
+        let count = count.parse::<u32>().ok()
             .ok_or_else(|| CardinalError(format!("Invalid draw count in: {}", effect_str)))?;
+        let player = player
+            .ok_or_else(|| CardinalError(format!("Invalid player in: {}", effect_str)))?;
         
         // Validate count is reasonable (prevent excessive draws)
         if count == 0 {
@@ -468,13 +1600,18 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             )));
         }
         
-        // TODO: Implement card drawing
-        // For now, return empty (no MoveCard commands yet)
-        Ok(vec![])
+        Ok(vec![Command::DrawCards { player, count }])
     } else if effect_str.starts_with("gain_life_") {
-        let amount = effect_str.strip_prefix("gain_life_")
-            .and_then(|s| s.parse::<i32>().ok())
+        // Format: gain_life_{amount}[_player_{id}]
+        let (amount, target) = parse_amount_and_target(effect_str.strip_prefix("gain_life_").unwrap_or(""))
             .ok_or_else(|| CardinalError(format!("Invalid life amount in: {}", effect_str)))?;
+        let player = match target {
+            BuiltinTarget::Default => controller,
+            BuiltinTarget::Player(player) => player,
+            BuiltinTarget::Card(_) => {
+                return Err(CardinalError(format!("gain_life cannot target a card (effect: {})", effect_str)));
+            }
+        };
         
         // Validate amount is non-negative to prevent damage via life gain
         if amount < 0 {
@@ -485,7 +1622,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
         }
         
         Ok(vec![Command::ChangeLife {
-            player: controller,
+            player,
             delta: amount,
         }])
     } else if effect_str.starts_with("lose_life_") {
@@ -495,7 +1632,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .split("_player_")
             .collect();
         
-        let amount = parts.get(0)
+        let amount = parts.first()
             .and_then(|s| s.parse::<i32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid life amount in: {}", effect_str)))?;
         let player = parts.get(1)
@@ -520,7 +1657,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .split("_player_")
             .collect();
         
-        let amount = parts.get(0)
+        let amount = parts.first()
             .and_then(|s| s.parse::<i32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid life amount in: {}", effect_str)))?;
         let player = parts.get(1)
@@ -538,67 +1675,6 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             player: PlayerId(player),
             amount,
         }])
-    } else if effect_str.starts_with("mill_") {
-        // Format: mill_{count}_player_{player_id}
-        let parts: Vec<&str> = effect_str.strip_prefix("mill_")
-            .unwrap_or("")
-            .split("_player_")
-            .collect();
-        
-        let _count = parts.get(0)
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| CardinalError(format!("Invalid mill count in: {}", effect_str)))?;
-        let _player = parts.get(1)
-            .and_then(|s| s.parse::<u8>().ok())
-            .unwrap_or(controller.0);
-        
-        // Placeholder: milling (deck to graveyard) is not implemented yet for builtin effects.
-        // Fail explicitly so game designers are not misled by a silent no-op.
-        Err(CardinalError(format!(
-            "Builtin effect '{}' is not implemented yet (milling is currently unsupported)",
-            effect_str
-        )))
-    } else if effect_str.starts_with("discard_") {
-        // Format: discard_{count}_player_{player_id}
-        let parts: Vec<&str> = effect_str.strip_prefix("discard_")
-            .unwrap_or("")
-            .split("_player_")
-            .collect();
-        
-        let _count = parts.get(0)
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| CardinalError(format!("Invalid discard count in: {}", effect_str)))?;
-        let _player = parts.get(1)
-            .and_then(|s| s.parse::<u8>().ok())
-            .unwrap_or(controller.0);
-        
-        // Placeholder: discarding (hand to graveyard) is not implemented yet for builtin effects.
-        // Fail explicitly so game designers are not misled by a silent no-op.
-        Err(CardinalError(format!(
-            "Builtin effect '{}' is not implemented yet (discarding is currently unsupported)",
-            effect_str
-        )))
-    } else if effect_str.starts_with("pump_") {
-        let parts: Vec<&str> = effect_str.strip_prefix("pump_")
-            .unwrap_or("")
-            .split('_')
-            .collect();
-        
-        let _power = parts.get(0)
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| CardinalError(format!("Invalid power in: {}", effect_str)))?;
-        let _toughness = parts.get(1)
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| CardinalError(format!("Invalid toughness in: {}", effect_str)))?;
-        
-        // Note: pump can have negative values to reduce stats, so no validation here
-        
-        // Placeholder: creature stat modification is not implemented yet for builtin effects.
-        // Fail explicitly so game designers are not misled by a silent no-op.
-        Err(CardinalError(format!(
-            "Builtin effect '{}' is not implemented yet (pump is currently unsupported)",
-            effect_str
-        )))
     } else if effect_str.starts_with("set_stats_") {
         // Format: set_stats_{card_id}_{power}_{toughness}
         let parts: Vec<&str> = effect_str.strip_prefix("set_stats_")
@@ -606,7 +1682,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .split('_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let power = parts.get(1)
@@ -628,7 +1704,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(2, '_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let keyword = parts.get(1)
@@ -638,6 +1714,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
         Ok(vec![Command::GrantKeyword {
             card: CardId(card),
             keyword,
+            duration: Duration::Permanent,
         }])
     } else if effect_str.starts_with("remove_keyword_") {
         // Format: remove_keyword_{card_id}_{keyword}
@@ -646,7 +1723,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(2, '_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let keyword = parts.get(1)
@@ -664,7 +1741,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let player = parts.get(0)
+        let player = parts.first()
             .and_then(|s| s.parse::<u8>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid player id in: {}", effect_str)))?;
         let resource = parts.get(1)
@@ -693,7 +1770,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let player = parts.get(0)
+        let player = parts.first()
             .and_then(|s| s.parse::<u8>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid player id in: {}", effect_str)))?;
         let resource = parts.get(1)
@@ -722,7 +1799,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let player = parts.get(0)
+        let player = parts.first()
             .and_then(|s| s.parse::<u8>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid player id in: {}", effect_str)))?;
         let resource = parts.get(1)
@@ -751,7 +1828,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let counter_type = parts.get(1)
@@ -780,7 +1857,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let counter_type = parts.get(1)
@@ -839,7 +1916,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             .splitn(3, '_')
             .collect();
         
-        let card = parts.get(0)
+        let card = parts.first()
             .and_then(|s| s.parse::<u32>().ok())
             .ok_or_else(|| CardinalError(format!("Invalid card id in: {}", effect_str)))?;
         let from_zone_str = parts.get(1)
@@ -854,6 +1931,7 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
             card: CardId(card),
             from: from_zone,
             to: to_zone,
+            position: None,
         }])
     } else {
         Err(CardinalError(format!("Unknown builtin effect type: {}", effect_str)))
@@ -863,10 +1941,32 @@ fn execute_builtin_effect(effect_str: &str, controller: PlayerId) -> Result<Vec<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::gamestate::{GameState, TurnState, PlayerState};
+    use crate::state::gamestate::{GameState, TurnState, PlayerState, ZoneState};
     use crate::ids::{PhaseId, StepId};
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
     
+    #[test]
+    fn test_custom_builtin_effect() {
+        let mut builtins = BuiltinRegistry::new();
+        builtins.register("poison", |args| {
+            let amount = args.args.parse::<i32>()
+                .map_err(|_| CardinalError(format!("Invalid poison amount: {}", args.args)))?;
+            let target = crate::rules::query::opponents_of(args.state, args.controller)[0];
+            Ok(vec![Command::ChangeLife { player: target, delta: -amount }])
+        });
+        let state = minimal_game_state();
+
+        let run = |effect| execute_effect_with_cards(&EffectRef::Builtin(effect), None, PlayerId(0), &state, None, None, Some(&builtins));
+
+        let commands = run("poison_3").unwrap();
+        assert!(matches!(commands[..], [Command::ChangeLife { player: PlayerId(1), delta: -3 }]));
+        assert!(run("poison_x").is_err());
+        // Only whole prefixes match
+        assert!(run("poisonous_3").is_err());
+        // Nothing is registered outside the registry it was added to
+        assert!(execute_effect(&EffectRef::Builtin("poison_3"), None, PlayerId(0), &state, None).is_err());
+    }
+
     fn minimal_game_state() -> GameState {
         GameState {
             turn: TurnState {
@@ -878,14 +1978,22 @@ mod tests {
                 priority_passes: 0,
             },
             players: vec![
-                PlayerState { id: PlayerId(0), life: 20, resources: HashMap::new() },
-                PlayerState { id: PlayerId(1), life: 20, resources: HashMap::new() },
+                PlayerState { id: PlayerId(0), life: 20, resources: BTreeMap::new(), draws_this_turn: 0, plays_this_turn: BTreeMap::new(), revealed: Vec::new(), revealed_to: Vec::new(), conceded: false },
+                PlayerState { id: PlayerId(1), life: 20, resources: BTreeMap::new(), draws_this_turn: 0, plays_this_turn: BTreeMap::new(), revealed: Vec::new(), revealed_to: Vec::new(), conceded: false },
             ],
             zones: vec![],
             stack: vec![],
             pending_choice: None,
             ended: None,
-            card_instances: HashMap::new(),
+            card_instances: BTreeMap::new(),
+            damage_shields: Vec::new(),
+            last_coin_flip: None,
+            rng: crate::util::rng::GameRng::new(0),
+            resource_caps: BTreeMap::new(),
+            max_hand_size: None,
+            extra_turns: Vec::new(),
+            skips: Vec::new(),
+            end_of_turn: Vec::new(),
         }
     }
     
@@ -902,11 +2010,12 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::ChangeLife { player, delta } => {
-                assert_eq!(*player, controller);
-                assert_eq!(*delta, -2);
+            Command::DealDamage { target, amount, combat } => {
+                assert_eq!(*target, TargetRef::Player(controller));
+                assert_eq!(*amount, 2);
+                assert!(!combat);
             }
-            _ => panic!("Expected ChangeLife command"),
+            _ => panic!("Expected DealDamage command"),
         }
     }
     
@@ -943,21 +2052,25 @@ mod tests {
         let result = execute_effect(&effect, None, controller, &state, None);
         assert!(result.is_ok());
         
-        // Draw not yet implemented, should return empty
         let commands = result.unwrap();
-        assert_eq!(commands.len(), 0);
+        assert!(matches!(commands.as_slice(), [Command::DrawCards { player: PlayerId(0), count: 1 }]));
     }
     
     #[test]
     fn test_execute_pump_effect() {
-        let effect = EffectRef::Builtin("pump_1_1");
         let controller = PlayerId(0);
-        let state = minimal_game_state();
-        
-        let result = execute_effect(&effect, None, controller, &state, None);
-        // Pump not yet implemented, should return error
-        assert!(result.is_err());
-        assert!(result.unwrap_err().0.contains("not implemented yet"));
+        let mut state = minimal_game_state();
+
+        let commands = execute_effect(&EffectRef::Builtin("pump_2_-1"), Some(CardId(5)), controller, &state, None).unwrap();
+        assert!(matches!(commands[..], [Command::ModifyStats { card: CardId(5), power: 2, toughness: -1, duration: Duration::Permanent }]));
+        crate::engine::events::commit_commands(&mut state, &commands);
+        let instance = &state.card_instances[&CardId(5)];
+        assert_eq!(instance.stat_modifiers.get("power"), Some(&2));
+        assert_eq!(instance.stat_modifiers.get("toughness"), Some(&-1));
+
+        // Without a source there is nothing to pump
+        assert!(execute_effect(&EffectRef::Builtin("pump_1_1"), None, controller, &state, None).is_err());
+        assert!(execute_effect(&EffectRef::Builtin("pump_x_1"), Some(CardId(5)), controller, &state, None).is_err());
     }
     
     #[test]
@@ -1012,6 +2125,122 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_scripted_effect_log_is_returned_with_commands() {
+        use crate::engine::scripting::RhaiEngine;
+        
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                log("hit branch A");
+                gain_life(0, 3)
+            }
+        "#;
+        
+        engine.register_script("chatty_card".to_string(), script).unwrap();
+        
+        // A sequence collects the log of each script it runs
+        let effect = EffectRef::Sequence(vec![
+            EffectRef::Scripted("chatty_card".to_string()),
+            EffectRef::Scripted("chatty_card".to_string()),
+        ]);
+        let state = minimal_game_state();
+        let mut log = Vec::new();
+        let commands = execute_effect_logged(&effect, None, PlayerId(0), &state, Some(&engine), None, None, &mut log).unwrap();
+        
+        assert_eq!(commands.len(), 2);
+        assert_eq!(log, vec!["hit branch A".to_string(), "hit branch A".to_string()]);
+    }
+    
+    #[test]
+    fn test_trace_reports_each_step_of_a_drain() {
+        use crate::engine::scripting::RhaiEngine;
+
+        let mut engine = RhaiEngine::new();
+        engine.register_script("drain_card".to_string(), r#"
+            fn execute_ability() {
+                log("draining");
+                drain(1, 3, controller)
+            }
+        "#).unwrap();
+
+        // The scripted drain, then the same as two builtins
+        let effect = EffectRef::Sequence(vec![
+            EffectRef::Scripted("drain_card".to_string()),
+            EffectRef::Sequence(vec![
+                EffectRef::Builtin("damage_3_player_1"),
+                EffectRef::Builtin("gain_life_3"),
+            ]),
+        ]);
+        let state = minimal_game_state();
+        let traced = execute_effect_traced(&effect, None, PlayerId(0), &state, Some(&engine), None, None, true).unwrap();
+
+        assert_eq!(traced.commands.len(), 4);
+        assert_eq!(traced.log, vec!["draining".to_string()]);
+
+        let produced: Vec<(usize, &Command)> = traced.trace.iter()
+            .filter_map(|step| match step {
+                TraceStep::Produced { depth, command } => Some((*depth, command)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(produced.len(), 4);
+        assert!(matches!(produced[0], (1, Command::DealDamage { target: TargetRef::Player(PlayerId(1)), amount: 3, .. })));
+        assert!(matches!(produced[1], (1, Command::ChangeLife { player: PlayerId(0), delta: 3 })));
+        assert!(matches!(produced[2], (2, Command::DealDamage { amount: 3, .. })));
+        assert!(matches!(produced[3], (2, Command::ChangeLife { delta: 3, .. })));
+
+        let lines: Vec<String> = traced.trace.iter().map(|step| step.to_string()).collect();
+        assert!(lines.contains(&"effect script drain_card".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"log draining".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"effect builtin damage_3_player_1".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"target player 1".to_string()), "{:?}", lines);
+
+        // Without tracing only the commands and log come back
+        let untraced = execute_effect_traced(&effect, None, PlayerId(0), &state, Some(&engine), None, None, false).unwrap();
+        assert_eq!(untraced.commands.len(), 4);
+        assert!(untraced.trace.is_empty());
+    }
+
+    #[test]
+    fn test_scripted_effect_branches_on_counters() {
+        use crate::engine::scripting::RhaiEngine;
+        
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                if counter_count(source_card, "charge") >= 3 {
+                    let removed = 3;
+                    [remove_counter(source_card, "charge", removed), deal_damage(1, removed)]
+                } else {
+                    [add_counter(source_card, "charge", 1)]
+                }
+            }
+        "#;
+        engine.register_script("battery".to_string(), script).unwrap();
+        
+        let effect = EffectRef::Scripted("battery".to_string());
+        let source = Some(CardId(7));
+        let charge = |amount| Command::AddCounter { card: CardId(7), counter_type: "charge".to_string(), amount };
+        let mut state = minimal_game_state();
+        crate::engine::events::commit_commands(&mut state, &[charge(2)]);
+        
+        // Two counters aren't enough, so the script charges up instead
+        let commands = execute_effect(&effect, source, PlayerId(0), &state, Some(&engine)).unwrap();
+        assert!(matches!(&commands[..], [Command::AddCounter { amount: 1, .. }]));
+        
+        crate::engine::events::commit_commands(&mut state, &[charge(2)]);
+        let commands = execute_effect(&effect, source, PlayerId(0), &state, Some(&engine)).unwrap();
+        match &commands[..] {
+            [Command::RemoveCounter { card, counter_type, amount: 3 }, Command::DealDamage { target, amount: 3, .. }] => {
+                assert_eq!(*card, CardId(7));
+                assert_eq!(counter_type, "charge");
+                assert_eq!(*target, TargetRef::Player(PlayerId(1)));
+            }
+            other => panic!("Expected RemoveCounter then DealDamage, got {:?}", other),
+        }
+    }
+    
     #[test]
     fn test_execute_scripted_damage_effect() {
         use crate::engine::scripting::RhaiEngine;
@@ -1036,11 +2265,11 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::ChangeLife { player, delta } => {
-                assert_eq!(*player, PlayerId(1));
-                assert_eq!(*delta, -5);
+            Command::DealDamage { target, amount, .. } => {
+                assert_eq!(*target, TargetRef::Player(PlayerId(1)));
+                assert_eq!(*amount, 5);
             }
-            _ => panic!("Expected ChangeLife command"),
+            _ => panic!("Expected DealDamage command"),
         }
     }
     
@@ -1133,7 +2362,7 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::ModifyStats { card, power, toughness } => {
+            Command::ModifyStats { card, power, toughness, .. } => {
                 assert_eq!(card.0, 5);
                 assert_eq!(*power, 2);
                 assert_eq!(*toughness, 2);
@@ -1200,7 +2429,7 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::GrantKeyword { card, keyword } => {
+            Command::GrantKeyword { card, keyword, .. } => {
                 assert_eq!(card.0, 7);
                 assert_eq!(keyword, "flying");
             }
@@ -1531,13 +2760,129 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::MoveCard { card, from: _, to: _ } => {
+            Command::MoveCard { card, .. } => {
                 assert_eq!(card.0, 10);
             }
             _ => panic!("Expected MoveCard command"),
         }
     }
     
+    #[test]
+    fn test_scripted_move_card_position() {
+        use crate::engine::scripting::RhaiEngine;
+        
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                [
+                    move_card(10, "graveyard", "deck", "bottom"),
+                    #{ type: "move_card", card: 11, from_zone: "graveyard", to_zone: "deck", position: 2 }
+                ]
+            }
+        "#;
+        
+        engine.register_script("tuck".to_string(), script).unwrap();
+        
+        let effect = EffectRef::Scripted("tuck".to_string());
+        let state = minimal_game_state();
+        
+        let commands = execute_effect(&effect, None, PlayerId(0), &state, Some(&engine)).unwrap();
+        let positions: Vec<_> = commands.iter().map(|c| match c {
+            Command::MoveCard { position, .. } => *position,
+            _ => panic!("Expected MoveCard command"),
+        }).collect();
+        assert_eq!(positions, vec![Some(ZonePosition::Bottom), Some(ZonePosition::Index(2))]);
+    }
+    
+    fn state_with_field(cards: &[u32]) -> GameState {
+        let mut state = minimal_game_state();
+        state.zones.push(ZoneState {
+            id: ZoneId("field@0"),
+            owner: Some(PlayerId(0)),
+            cards: cards.iter().map(|c| CardId(*c)).collect(),
+        });
+        state
+    }
+    
+    #[test]
+    fn test_scripted_tap_source_card() {
+        use crate::engine::scripting::RhaiEngine;
+        
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                tap(source_card)
+            }
+        "#;
+        
+        engine.register_script("tapper".to_string(), script).unwrap();
+        
+        let effect = EffectRef::Scripted("tapper".to_string());
+        let state = state_with_field(&[7]);
+        
+        let commands = execute_effect(&effect, Some(CardId(7)), PlayerId(0), &state, Some(&engine)).unwrap();
+        assert!(matches!(
+            commands.as_slice(),
+            [Command::SetTapped { card: CardId(7), tapped: true }]
+        ));
+    }
+    
+    #[test]
+    fn test_builtin_untap_requires_permanent() {
+        let effect = EffectRef::Builtin("untap_5");
+        
+        let commands = execute_effect(&effect, None, PlayerId(0), &state_with_field(&[5]), None).unwrap();
+        assert!(matches!(
+            commands.as_slice(),
+            [Command::SetTapped { card: CardId(5), tapped: false }]
+        ));
+        
+        // Not on the field, so not a permanent
+        let result = execute_effect(&effect, None, PlayerId(0), &minimal_game_state(), None);
+        assert!(result.unwrap_err().0.contains("not a permanent"));
+    }
+    
+    #[test]
+    fn test_counter_removes_stack_item() {
+        use crate::model::command::StackItem;
+        
+        let mut state = minimal_game_state();
+        for id in [1, 2] {
+            state.stack.push(StackItem {
+                id,
+                source: None,
+                controller: PlayerId(1),
+                effect: EffectRef::Builtin("damage_3_player_0"),
+            });
+        }
+        
+        let commands = execute_effect(&EffectRef::Builtin("counter_1"), None, PlayerId(0), &state, None).unwrap();
+        assert!(matches!(commands.as_slice(), [Command::RemoveStackItem { id: 1 }]));
+        
+        let events = crate::engine::events::commit_commands(&mut state, &commands);
+        assert!(matches!(events.as_slice(), [crate::model::event::Event::SpellCountered { item_id: 1 }]));
+        let remaining: Vec<_> = state.stack.iter().map(|i| i.id).collect();
+        assert_eq!(remaining, vec![2]);
+        
+        // counter_top picks the topmost remaining item
+        let commands = execute_effect(&EffectRef::Builtin("counter_top"), None, PlayerId(0), &state, None).unwrap();
+        assert!(matches!(commands.as_slice(), [Command::RemoveStackItem { id: 2 }]));
+    }
+    
+    #[test]
+    fn test_counter_nonexistent_stack_item_fails() {
+        use crate::engine::scripting::RhaiEngine;
+        
+        let state = minimal_game_state();
+        let result = execute_effect(&EffectRef::Builtin("counter_42"), None, PlayerId(0), &state, None);
+        assert!(result.unwrap_err().0.contains("not on the stack"));
+        
+        let mut engine = RhaiEngine::new();
+        engine.register_script("negate".to_string(), "fn execute_ability() { counter(42) }").unwrap();
+        let effect = EffectRef::Scripted("negate".to_string());
+        assert!(execute_effect(&effect, None, PlayerId(0), &state, Some(&engine)).is_err());
+    }
+    
     #[test]
     fn test_scripted_shuffle_zone() {
         use crate::engine::scripting::RhaiEngine;
@@ -1671,7 +3016,7 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::GrantKeyword { card, keyword } => {
+            Command::GrantKeyword { card, keyword, .. } => {
                 assert_eq!(*card, CardId(10));
                 assert_eq!(keyword, "flying");
             }
@@ -1844,7 +3189,7 @@ mod tests {
         assert_eq!(commands.len(), 1);
         
         match &commands[0] {
-            Command::MoveCard { card, from, to } => {
+            Command::MoveCard { card, from, to, .. } => {
                 assert_eq!(*card, CardId(15));
                 assert_eq!(*from, ZoneId("graveyard"));
                 assert_eq!(*to, ZoneId("hand"));
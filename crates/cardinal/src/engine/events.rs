@@ -1,8 +1,77 @@
-use crate::state::gamestate::{GameState, CardInstanceData};
-use crate::model::command::Command;
-use crate::model::event::Event;
-use crate::ids::{CardId, ZoneId};
-use std::collections::HashMap;
+use crate::state::gamestate::{GameState, CardInstanceData, DamageShield};
+use crate::model::action::TargetRef;
+use crate::model::command::{Command, Duration, ZonePosition};
+use crate::model::event::{CoinFlip, Event};
+use crate::ids::{CardId, PlayerId};
+use crate::error::CardinalError;
+use std::collections::BTreeMap;
+
+/// Apply a batch of commands all-or-nothing and return the emitted events
+///
+/// Each command is checked against the state as the commands before it left
+/// it (see [`check_command`]). If one fails, `state` is left exactly as it
+/// was and its error is returned; otherwise the whole batch is committed as
+/// by [`commit_commands`].
+pub fn try_commit_commands(state: &mut GameState, commands: &[Command]) -> Result<Vec<Event>, CardinalError> {
+    let mut staged = state.clone();
+    let mut events = Vec::new();
+    for command in commands {
+        check_command(&staged, command)?;
+        events.extend(commit_commands(&mut staged, std::slice::from_ref(command)));
+    }
+
+    *state = staged;
+    Ok(events)
+}
+
+/// Whether a command can be applied to `state` as it stands
+///
+/// Rejects spending more of a resource than the player has, moving a card
+/// that isn't in its source zone or to a zone that doesn't exist, and
+/// changing life or resources of a player that doesn't exist.
+/// [`commit_commands`] itself applies such commands as best it can.
+pub fn check_command(state: &GameState, command: &Command) -> Result<(), CardinalError> {
+    let player_exists = |player: &PlayerId| {
+        if state.players.iter().any(|p| p.id == *player) {
+            Ok(())
+        } else {
+            Err(CardinalError(format!("Player {} does not exist", player.0)))
+        }
+    };
+
+    match command {
+        Command::MoveCard { card, from, to, .. } => {
+            let source = state.zones.iter().find(|z| z.id == *from)
+                .ok_or_else(|| CardinalError(format!("Zone {} does not exist", from.0)))?;
+            if !source.cards.contains(card) {
+                return Err(CardinalError(format!("Card {} is not in {}", card.0, from.0)));
+            }
+            if !state.zones.iter().any(|z| z.id == *to) {
+                return Err(CardinalError(format!("Zone {} does not exist", to.0)));
+            }
+            Ok(())
+        }
+        Command::SpendResource { player, resource, amount } => {
+            player_exists(player)?;
+            let available = state.players.iter()
+                .find(|p| p.id == *player)
+                .and_then(|p| p.resources.get(resource).copied())
+                .unwrap_or(0);
+            if available < *amount {
+                return Err(CardinalError(format!(
+                    "Player {} has {} {} and can't spend {}",
+                    player.0, available, resource, amount
+                )));
+            }
+            Ok(())
+        }
+        Command::ChangeLife { player, .. }
+        | Command::SetLife { player, .. }
+        | Command::GainResource { player, .. }
+        | Command::SetResource { player, .. } => player_exists(player),
+        _ => Ok(()),
+    }
+}
 
 /// Apply a batch of commands to the `GameState` and return emitted events.
 /// Each command mutates the state and produces one or more events.
@@ -11,16 +80,43 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
 
     for cmd in commands {
         match cmd {
-            Command::MoveCard { card, from, to } => {
-                // Remove card from source zone
+            Command::MoveCard { card, from, to, position } => {
+                // Remove card from source zone; decks built from a deck list
+                // hold several copies of an ID, and only one of them moves
+                let mut left_field = false;
                 if let Some(zone) = state.zones.iter_mut().find(|z| z.id == *from) {
-                    zone.cards.retain(|c| c != card);
+                    left_field = from.0.starts_with("field") && from != to && zone.cards.contains(card);
+                    if let Some(pos) = zone.cards.iter().position(|c| c == card) {
+                        zone.cards.remove(pos);
+                    }
                 }
-                // Add card to destination zone
+                // Add card to destination zone at the requested position
                 if let Some(zone) = state.zones.iter_mut().find(|z| z.id == *to) {
-                    zone.cards.push(*card);
+                    // Graveyards keep the order cards arrived in, most recent last
+                    let position = if to.0.starts_with("graveyard") {
+                        ZonePosition::Bottom
+                    } else {
+                        position.unwrap_or(if to.0.starts_with("deck") {
+                            ZonePosition::Top
+                        } else {
+                            ZonePosition::Bottom
+                        })
+                    };
+                    let index = match position {
+                        ZonePosition::Top => 0,
+                        ZonePosition::Bottom => zone.cards.len(),
+                        ZonePosition::Index(i) => i.min(zone.cards.len()),
+                    };
+                    zone.cards.insert(index, *card);
                 }
                 events.push(Event::CardMoved { card: *card, from: from.clone(), to: to.clone() });
+                if left_field {
+                    // Control taken over a permanent ends when it leaves the field
+                    if let Some(instance) = state.card_instances.get_mut(card) {
+                        instance.controller = None;
+                    }
+                    events.push(Event::CardLeftZone { card: *card, from: from.clone(), to: to.clone() });
+                }
             }
             Command::ChangeLife { player, delta } => {
                 if let Some(p) = state.players.iter_mut().find(|pl| pl.id == *player) {
@@ -28,6 +124,44 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
                 }
                 events.push(Event::LifeChanged { player: *player, delta: *delta });
             }
+            Command::DealDamage { target, amount, combat } => {
+                // Shields absorb oldest first; each one that absorbs anything is used up
+                let mut remaining = *amount;
+                for shield in state.damage_shields.iter_mut()
+                    .filter(|s| s.target == *target && (*combat || !s.combat_only))
+                {
+                    if remaining == 0 {
+                        break;
+                    }
+                    let absorbed = remaining.min(shield.amount);
+                    remaining -= absorbed;
+                    shield.amount = 0;
+                }
+                state.damage_shields.retain(|s| s.amount > 0);
+
+                if remaining < *amount {
+                    events.push(Event::DamagePrevented { target: target.clone(), amount: *amount - remaining });
+                }
+                if remaining > 0 {
+                    let dealt = match target {
+                        TargetRef::Player(player) => Command::ChangeLife { player: *player, delta: -remaining },
+                        TargetRef::Card(card) => Command::ModifyStat {
+                            card: *card,
+                            stat_name: "damage".to_string(),
+                            delta: remaining,
+                        },
+                    };
+                    events.extend(commit_commands(state, &[dealt]));
+                }
+            }
+            Command::PreventDamage { target, amount, combat_only } => {
+                state.damage_shields.push(DamageShield {
+                    target: target.clone(),
+                    amount: *amount,
+                    combat_only: *combat_only,
+                });
+                events.push(Event::DamageShieldAdded { target: target.clone(), amount: *amount });
+            }
             Command::SetLife { player, amount } => {
                 if let Some(p) = state.players.iter_mut().find(|pl| pl.id == *player) {
                     p.life = *amount;
@@ -43,37 +177,47 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
                 state.pending_choice = Some(choice.clone());
                 events.push(Event::ChoiceRequested { choice_id: choice.id, player: *player });
             }
-            Command::ShuffleZone { zone: _ } => {
-                // NOTE: ShuffleZone is intentionally left unimplemented here.
-                // A correct implementation must:
-                //   - Use the engine-owned RNG to deterministically reorder the cards
-                //     in the target zone within `GameState`, and
-                //   - Emit a corresponding `ZoneShuffled` event that accurately
-                //     reflects the state change.
-                //
-                // Emitting `ZoneShuffled` without changing `GameState` would violate
-                // the engine's design principles (state is authoritative; events
-                // must describe real state changes). Until proper shuffling is wired
-                // up, this command must not be used in live rules/effects.
-                todo!("Command::ShuffleZone is not yet implemented: it must update GameState and use the engine RNG to shuffle the zone");
-            }
-            Command::ModifyStats { card, power, toughness } => {
+            Command::ShuffleZone { zone } => {
+                if let Some(z) = state.zones.iter_mut().find(|z| z.id == *zone) {
+                    state.rng.shuffle(&mut z.cards);
+                    // A player's deck reports as that player's shuffle
+                    events.push(match z.owner {
+                        Some(player) if zone.0.starts_with("deck") => Event::DeckShuffled { player },
+                        _ => Event::ZoneShuffled { zone: zone.clone() },
+                    });
+                }
+            }
+            Command::ModifyStats { card, power, toughness, duration } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
-                *instance.stat_modifiers.entry("power".to_string()).or_insert(0) += *power;
-                *instance.stat_modifiers.entry("toughness".to_string()).or_insert(0) += *toughness;
+                let modifiers = match duration {
+                    Duration::Permanent => &mut instance.stat_modifiers,
+                    Duration::UntilEndOfTurn => &mut instance.temporary_stat_modifiers,
+                };
+                *modifiers.entry("power".to_string()).or_insert(0) += *power;
+                *modifiers.entry("toughness".to_string()).or_insert(0) += *toughness;
                 events.push(Event::StatsModified { card: *card, power: *power, toughness: *toughness });
             }
             Command::SetStats { card, power, toughness } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
                 instance.stats.insert("power".to_string(), power.to_string());
                 instance.stats.insert("toughness".to_string(), toughness.to_string());
@@ -81,47 +225,73 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
             }
             Command::ModifyStat { card, stat_name, delta } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
                 *instance.stat_modifiers.entry(stat_name.clone()).or_insert(0) += *delta;
                 events.push(Event::StatModified { card: *card, stat_name: stat_name.clone(), delta: *delta });
             }
             Command::SetStat { card, stat_name, value } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
                 instance.stats.insert(stat_name.clone(), value.clone());
                 events.push(Event::StatSet { card: *card, stat_name: stat_name.clone(), value: value.clone() });
             }
-            Command::GrantKeyword { card, keyword } => {
+            Command::GrantKeyword { card, keyword, duration } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
-                if !instance.keywords.contains(keyword) {
-                    instance.keywords.push(keyword.clone());
+                let keywords = match duration {
+                    Duration::Permanent => &mut instance.keywords,
+                    Duration::UntilEndOfTurn => &mut instance.temporary_keywords,
+                };
+                if !keywords.contains(keyword) {
+                    keywords.push(keyword.clone());
                 }
                 events.push(Event::KeywordGranted { card: *card, keyword: keyword.clone() });
             }
             Command::RemoveKeyword { card, keyword } => {
                 if let Some(instance) = state.card_instances.get_mut(card) {
                     instance.keywords.retain(|k| k != keyword);
+                    instance.temporary_keywords.retain(|k| k != keyword);
                 }
                 events.push(Event::KeywordRemoved { card: *card, keyword: keyword.clone() });
             }
             Command::GainResource { player, resource, amount } => {
+                let mut gained = *amount;
                 if let Some(p) = state.players.iter_mut().find(|pl| pl.id == *player) {
-                    *p.resources.entry(resource.clone()).or_insert(0) += *amount;
+                    let current = p.resources.entry(resource.clone()).or_insert(0);
+                    // Overflow past the resource's cap is lost
+                    if let Some(cap) = state.resource_caps.get(resource) {
+                        gained = gained.min((cap - *current).max(0));
+                    }
+                    *current += gained;
                 }
-                events.push(Event::ResourceGained { player: *player, resource: resource.clone(), amount: *amount });
+                events.push(Event::ResourceGained { player: *player, resource: resource.clone(), amount: gained });
             }
             Command::SpendResource { player, resource, amount } => {
                 if let Some(p) = state.players.iter_mut().find(|pl| pl.id == *player) {
@@ -152,10 +322,15 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
                 
                 // Initialize token instance data
                 let instance = CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 };
                 state.card_instances.insert(token_id, instance);
                 
@@ -168,14 +343,188 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
             }
             Command::AddCounter { card, counter_type, amount } => {
                 let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
-                    stats: HashMap::new(),
-                    stat_modifiers: HashMap::new(),
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
                     keywords: Vec::new(),
-                    counters: HashMap::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
                 });
                 *instance.counters.entry(counter_type.clone()).or_insert(0) += *amount;
                 events.push(Event::CounterAdded { card: *card, counter_type: counter_type.clone(), amount: *amount });
             }
+            Command::SetTapped { card, tapped } => {
+                let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
+                    keywords: Vec::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
+                });
+                instance.tapped = *tapped;
+                events.push(if *tapped {
+                    Event::CardTapped { card: *card }
+                } else {
+                    Event::CardUntapped { card: *card }
+                });
+            }
+            Command::DiscardRandom { player, count } => {
+                let hand_id = format!("hand@{}", player.0);
+                let graveyard_id = format!("graveyard@{}", player.0);
+                let hand = state.zones.iter().position(|z| z.id.0 == hand_id);
+                let graveyard = state.zones.iter().position(|z| z.id.0 == graveyard_id);
+                if let (Some(hand), Some(graveyard)) = (hand, graveyard) {
+                    // Asking for more than the hand holds discards the whole hand
+                    for _ in 0..(*count).min(state.zones[hand].cards.len()) {
+                        let index = state.rng.generate::<u32>() as usize % state.zones[hand].cards.len();
+                        let card = state.zones[hand].cards.remove(index);
+                        state.zones[graveyard].cards.push(card);
+                        events.push(Event::CardMoved {
+                            card,
+                            from: state.zones[hand].id.clone(),
+                            to: state.zones[graveyard].id.clone(),
+                        });
+                    }
+                }
+            }
+            Command::DrawCards { player, count } => {
+                let deck_id = format!("deck@{}", player.0);
+                let hand_id = format!("hand@{}", player.0);
+                let deck = state.zones.iter().position(|z| z.id.0 == deck_id);
+                let hand = state.zones.iter().position(|z| z.id.0 == hand_id);
+                if let (Some(deck), Some(hand)) = (deck, hand) {
+                    // A full hand takes no more cards; an empty deck stops the
+                    // draw, which is reported so rules can act on it
+                    let room = state.max_hand_size
+                        .map_or(usize::MAX, |max| max.saturating_sub(state.zones[hand].cards.len()));
+                    let wanted = (*count as usize).min(room);
+                    let drawn = wanted.min(state.zones[deck].cards.len());
+                    for card in state.zones[deck].cards.drain(..drawn).collect::<Vec<_>>() {
+                        state.zones[hand].cards.push(card);
+                        events.push(Event::CardDrawn { player: *player, card });
+                    }
+                    if drawn < wanted {
+                        events.push(Event::DeckRanOut { player: *player, requested: wanted as u32, drawn: drawn as u32 });
+                    }
+                    if let Some(p) = state.players.iter_mut().find(|p| p.id == *player) {
+                        p.draws_this_turn += drawn as u32;
+                    }
+                }
+            }
+            Command::FlipCoin { player } => {
+                let result = if state.rng.generate::<bool>() { CoinFlip::Heads } else { CoinFlip::Tails };
+                state.last_coin_flip = Some(result);
+                events.push(Event::CoinFlipped { player: *player, result });
+            }
+            Command::RevealHand { player } => {
+                let hand_id = format!("hand@{}", player.0);
+                let cards = state.zones.iter()
+                    .find(|z| z.id.0 == hand_id)
+                    .map(|z| z.cards.clone())
+                    .unwrap_or_default();
+                if let Some(p) = state.players.iter_mut().find(|p| p.id == *player) {
+                    for card in &cards {
+                        if !p.revealed.contains(card) {
+                            p.revealed.push(*card);
+                        }
+                    }
+                }
+                events.push(Event::CardsRevealed { player: *player, cards });
+            }
+            Command::RevealHandTo { player, viewer } => {
+                let hand_id = format!("hand@{}", player.0);
+                let cards = state.zones.iter()
+                    .find(|z| z.id.0 == hand_id)
+                    .map(|z| z.cards.clone())
+                    .unwrap_or_default();
+                if let Some(p) = state.players.iter_mut().find(|p| p.id == *player) {
+                    for card in &cards {
+                        if !p.revealed_to.contains(&(*viewer, *card)) {
+                            p.revealed_to.push((*viewer, *card));
+                        }
+                    }
+                }
+                events.push(Event::CardsRevealedTo { player: *player, viewer: *viewer, cards });
+            }
+            Command::RevealCards { player, cards } => {
+                events.push(Event::CardsRevealed { player: *player, cards: cards.clone() });
+            }
+            Command::CastCard { player, card } => {
+                events.push(Event::CardPlayed { player: *player, card: *card });
+            }
+            Command::GainControl { card, player } => {
+                let from = state.controller_of(*card);
+                let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
+                    keywords: Vec::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
+                });
+                instance.controller = Some(*player);
+                events.push(Event::ControlChanged { card: *card, from, player: *player });
+            }
+            Command::ReleaseControl { card } => {
+                let from = state.controller_of(*card);
+                if let Some(instance) = state.card_instances.get_mut(card) {
+                    instance.controller = None;
+                }
+                if let Some(owner) = state.owner_of(*card)
+                    && from != Some(owner)
+                {
+                    events.push(Event::ControlChanged { card: *card, from, player: owner });
+                }
+            }
+            Command::AtEndOfTurn { command } => {
+                state.end_of_turn.push((**command).clone());
+                events.push(Event::CleanupScheduled);
+            }
+            Command::Transform { card, into } => {
+                let instance = state.card_instances.entry(*card).or_insert_with(|| CardInstanceData {
+                    stats: BTreeMap::new(),
+                    stat_modifiers: BTreeMap::new(),
+                    keywords: Vec::new(),
+                    counters: BTreeMap::new(),
+                    tapped: false,
+                    definition: None,
+                    controller: None,
+                    temporary_stat_modifiers: BTreeMap::new(),
+                    temporary_keywords: Vec::new(),
+                });
+                let from = instance.definition.unwrap_or(*card);
+                instance.definition = (*into != *card).then_some(*into);
+                events.push(Event::CardTransformed { card: *card, from, into: *into });
+            }
+            Command::AddExtraTurn { player } => {
+                state.extra_turns.push(*player);
+                events.push(Event::ExtraTurnAdded { player: *player });
+            }
+            Command::SkipNext { player, id } => {
+                state.skips.push((*player, id.clone()));
+                events.push(Event::SkipAdded { player: *player, id: id.clone() });
+            }
+            Command::RemoveStackItem { id } => {
+                // Gone already (e.g. countered twice) is a no-op
+                if let Some(pos) = state.stack.iter().position(|item| item.id == *id) {
+                    state.stack.remove(pos);
+                    // A choice for the countered item can no longer be answered
+                    if state.pending_choice.as_ref().is_some_and(|c| c.stack_item == Some(*id)) {
+                        state.pending_choice = None;
+                    }
+                    events.push(Event::SpellCountered { item_id: *id });
+                }
+            }
             Command::RemoveCounter { card, counter_type, amount } => {
                 if let Some(instance) = state.card_instances.get_mut(card) {
                     let current = instance.counters.entry(counter_type.clone()).or_insert(0);
@@ -189,4 +538,27 @@ pub fn commit_commands(state: &mut GameState, commands: &[Command]) -> Vec<Event
     events
 }
 
+/// Clear every card's until-end-of-turn stat modifiers and keywords, as the
+/// turn ends
+///
+/// Emits the pumps undone as `StatsModified` and `KeywordRemoved` for each
+/// keyword the card no longer has on the instance.
+pub fn end_temporary_effects(state: &mut GameState) -> Vec<Event> {
+    let mut events = Vec::new();
+    for (card, instance) in &mut state.card_instances {
+        let modifiers = std::mem::take(&mut instance.temporary_stat_modifiers);
+        let power = modifiers.get("power").copied().unwrap_or(0);
+        let toughness = modifiers.get("toughness").copied().unwrap_or(0);
+        if power != 0 || toughness != 0 {
+            events.push(Event::StatsModified { card: *card, power: -power, toughness: -toughness });
+        }
+        for keyword in std::mem::take(&mut instance.temporary_keywords) {
+            if !instance.keywords.contains(&keyword) {
+                events.push(Event::KeywordRemoved { card: *card, keyword });
+            }
+        }
+    }
+    events
+}
+
 // Event handling logic
@@ -1,46 +1,291 @@
 use crate::{
+    error::CardinalError,
+    model::event::Event,
     state::gamestate::GameState,
     util::rng::GameRng,
     ids::{CardId, PlayerId},
     rules::schema::Ruleset,
 };
 
+/// Options for the opening sequence
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    /// Shuffle each player's deck. Turn this off to keep decks in their
+    /// declared order so draws come off the top predictably (for debugging
+    /// card interactions). The seed still decides a random first player.
+    pub shuffle_decks: bool,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self { shuffle_decks: true }
+    }
+}
+
+impl InitOptions {
+    /// Options for a game whose decks stay in declared order
+    pub fn no_shuffle() -> Self {
+        Self { shuffle_decks: false }
+    }
+}
+
+/// What the opening sequence did, for checking setup randomness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupReport {
+    pub first_player: PlayerId,
+    /// Why they go first
+    pub first_player_reason: FirstPlayerReason,
+    /// Whether the first player's opening draw was skipped
+    /// (`skip_first_turn_draw_for_first_player`)
+    pub first_draw_skipped: bool,
+    /// One entry per player, in seat order
+    pub players: Vec<PlayerSetup>,
+}
+
+/// How the first player was picked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FirstPlayerReason {
+    /// Named by the rules' `first_player_rule` (e.g. "player_0")
+    Fixed { rule: String },
+    /// Drawn with the game seed, by a "random" `first_player_rule` or as the
+    /// fallback for one the engine doesn't know
+    Random { rule: String, seed: u64 },
+}
+
+/// One player's part of a [`SetupReport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSetup {
+    pub player: PlayerId,
+    /// Cards in their deck before the opening hands were drawn
+    pub deck_size: usize,
+    /// The cards they drew, in draw order
+    pub opening_hand: Vec<CardId>,
+}
+
+impl std::fmt::Display for SetupReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.first_player_reason {
+            FirstPlayerReason::Fixed { rule } => {
+                writeln!(f, "First player: {} (first_player_rule = \"{}\")", self.first_player.0, rule)?
+            }
+            FirstPlayerReason::Random { rule, seed } => writeln!(
+                f,
+                "First player: {} (picked at random with seed {}, first_player_rule = \"{}\")",
+                self.first_player.0, seed, rule
+            )?,
+        }
+        if self.first_draw_skipped {
+            writeln!(f, "First player's opening draw skipped")?;
+        }
+        for player in &self.players {
+            let hand: Vec<String> = player.opening_hand.iter().map(|c| c.0.to_string()).collect();
+            writeln!(
+                f,
+                "Player {}: deck of {}, opening hand [{}]",
+                player.player.0, player.deck_size, hand.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Initialize a game by:
-/// 1. Shuffling each player's deck
-/// 2. Drawing starting hands
-/// 3. Determining the first player
-/// 4. Setting up the initial turn state
+/// 1. Shuffling each player's deck ([`shuffle_all_decks`])
+/// 2. Determining the first player ([`choose_first_player`])
+/// 3. Drawing starting hands ([`draw_opening_hands`])
+/// 4. Giving the players who don't go first their compensation
+///    (`second_player_extra_cards` in step 3, then
+///    `second_player_starting_resources` via [`grant_starting_resources`])
+///
+/// UIs that want to show each step (or run mulligans in between) can call
+/// the pieces themselves with one `GameRng` built from the seed, in this
+/// order, and store the RNG in `state.rng` afterwards.
 pub fn initialize_game(
-    mut state: GameState,
+    state: GameState,
     rules: &Ruleset,
     seed: u64,
 ) -> GameState {
+    initialize_game_with_events(state, rules, seed).0
+}
+
+/// Same as [`initialize_game`], but also returns the events describing the
+/// opening sequence (`GameStarted`, one `DeckShuffled` per player and a
+/// `CardDrawn` per card in each starting hand) so it can be logged or replayed.
+pub fn initialize_game_with_events(
+    state: GameState,
+    rules: &Ruleset,
+    seed: u64,
+) -> (GameState, Vec<Event>) {
+    initialize_game_with_options(state, rules, seed, &InitOptions::default())
+}
+
+/// Same as [`initialize_game_with_events`], with control over the opening
+/// sequence (e.g. [`InitOptions::no_shuffle`])
+pub fn initialize_game_with_options(
+    state: GameState,
+    rules: &Ruleset,
+    seed: u64,
+    options: &InitOptions,
+) -> (GameState, Vec<Event>) {
+    let (state, events, _) = initialize_game_with_report(state, rules, seed, options);
+    (state, events)
+}
+
+/// Same as [`initialize_game_with_options`], also returning a
+/// [`SetupReport`] of who goes first and why, and each player's deck size
+/// and opening hand
+pub fn initialize_game_with_report(
+    mut state: GameState,
+    rules: &Ruleset,
+    seed: u64,
+    options: &InitOptions,
+) -> (GameState, Vec<Event>, SetupReport) {
     let mut rng = GameRng::new(seed);
-    let num_players = state.players.len() as u32;
+    let mut events = vec![Event::GameStarted];
 
-    // 1. Shuffle each player's deck
-    for i in 0..num_players {
-        let player_id = PlayerId(i as u8);
-        shuffle_player_deck(&mut state, player_id, &mut rng, rules);
+    if options.shuffle_decks {
+        events.extend(shuffle_all_decks(&mut state, &mut rng));
     }
 
-    // 2. Determine first player based on rule
-    let first_player = determine_first_player(&rules.players.first_player_rule, num_players, &mut rng);
+    let first_player = choose_first_player(rules, &mut rng);
     state.turn.active_player = first_player;
     state.turn.priority_player = first_player;
 
-    // 3. Draw starting hands
+    let deck_sizes: Vec<usize> = state.players.iter()
+        .map(|p| crate::rules::query::zone_size(&state, "deck", p.id))
+        .collect();
+    let draws = draw_opening_hands(&mut state, rules);
+    grant_starting_resources(&mut state, rules);
+
+    let rule = rules.players.first_player_rule.clone();
+    let report = SetupReport {
+        first_player,
+        first_player_reason: match rule.as_str() {
+            "player_0" | "first" | "player_1" | "second" => FirstPlayerReason::Fixed { rule },
+            _ => FirstPlayerReason::Random { rule, seed },
+        },
+        first_draw_skipped: rules.turn.skip_first_turn_draw_for_first_player,
+        players: state.players.iter()
+            .zip(deck_sizes)
+            .map(|(p, deck_size)| PlayerSetup {
+                player: p.id,
+                deck_size,
+                opening_hand: draws.iter()
+                    .filter_map(|e| match e {
+                        Event::CardDrawn { player, card } if *player == p.id => Some(*card),
+                        _ => None,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    events.extend(draws);
+
+    // Later shuffles continue the same stream
+    state.rng = rng;
+
+    (state, events, report)
+}
+
+/// Shuffle every player's deck, returning a `DeckShuffled` event per player
+pub fn shuffle_all_decks(state: &mut GameState, rng: &mut GameRng) -> Vec<Event> {
+    (0..state.players.len() as u8)
+        .map(|i| {
+            let player_id = PlayerId(i);
+            shuffle_player_deck(state, player_id, rng);
+            Event::DeckShuffled { player: player_id }
+        })
+        .collect()
+}
+
+/// Pick the player who goes first by the rules' `first_player_rule`. Games
+/// have `min_players` seats (see [`GameState::from_ruleset`]).
+pub fn choose_first_player(rules: &Ruleset, rng: &mut GameRng) -> PlayerId {
+    let num_players = rules.players.min_players.max(1) as u32;
+    determine_first_player(&rules.players.first_player_rule, num_players, rng)
+}
+
+/// Draw every player's starting hand, treating `state.turn.active_player`
+/// as the first player: they draw nothing if the rules skip their first
+/// draw, and everyone else draws `second_player_extra_cards` more. Returns a
+/// `CardDrawn` event per card.
+pub fn draw_opening_hands(state: &mut GameState, rules: &Ruleset) -> Vec<Event> {
+    let first_player = state.turn.active_player;
     let skip_first_draw = rules.turn.skip_first_turn_draw_for_first_player;
-    for i in 0..num_players {
-        let player_id = PlayerId(i as u8);
-        let should_skip = skip_first_draw && player_id == first_player;
-        
-        if !should_skip {
-            draw_cards(&mut state, player_id, rules.players.starting_hand_size as u32, rules);
+    let mut events = Vec::new();
+    for i in 0..state.players.len() as u8 {
+        let player_id = PlayerId(i);
+        if skip_first_draw && player_id == first_player {
+            continue;
+        }
+
+        let mut hand_size = rules.players.starting_hand_size;
+        if player_id != first_player {
+            hand_size += rules.players.second_player_extra_cards;
+        }
+        let drawn = draw_cards(state, player_id, hand_size as u32, rules);
+        events.extend(drawn.into_iter().map(|card| Event::CardDrawn { player: player_id, card }));
+    }
+    events
+}
+
+/// Give every player except `state.turn.active_player` the rules'
+/// `second_player_starting_resources`
+pub fn grant_starting_resources(state: &mut GameState, rules: &Ruleset) {
+    let first_player = state.turn.active_player;
+    for player in state.players.iter_mut().filter(|p| p.id != first_player) {
+        for (resource, amount) in &rules.players.second_player_starting_resources {
+            *player.resources.entry(resource.clone()).or_insert(0) += amount;
         }
     }
+}
+
+/// Sideboard between games of a match: for each `(out, in)` pair, a copy of
+/// `out` leaves `player`'s deck for their sideboard and a copy of `in` comes
+/// back the other way. The rules must define a player-owned `sideboard`
+/// zone. Afterwards the deck must still be within `min_deck_size` and
+/// `max_deck_size`, so cards still out in other zones from the last game
+/// have to be back in the deck first. On any error nothing changes.
+/// Returns a `CardMoved` event per card moved.
+pub fn swap_sideboard(
+    state: &mut GameState,
+    rules: &Ruleset,
+    player: PlayerId,
+    swaps: &[(CardId, CardId)],
+) -> Result<Vec<Event>, CardinalError> {
+    let deck_id = format!("deck@{}", player.0);
+    let sideboard_id = format!("sideboard@{}", player.0);
+    let deck = state.zones.iter().position(|z| z.id.0 == deck_id)
+        .ok_or_else(|| CardinalError(format!("Player {} has no deck zone", player.0)))?;
+    let sideboard = state.zones.iter().position(|z| z.id.0 == sideboard_id)
+        .ok_or_else(|| CardinalError(format!("Player {} has no sideboard zone; the rules must define one", player.0)))?;
+
+    let mut deck_cards = state.zones[deck].cards.clone();
+    let mut sideboard_cards = state.zones[sideboard].cards.clone();
+    let (deck_zone, sideboard_zone) = (state.zones[deck].id.clone(), state.zones[sideboard].id.clone());
+    let mut events = Vec::new();
+    for (out, into) in swaps {
+        let out_index = deck_cards.iter().position(|c| c == out)
+            .ok_or_else(|| CardinalError(format!("Card {} is not in player {}'s deck", out.0, player.0)))?;
+        let in_index = sideboard_cards.iter().position(|c| c == into)
+            .ok_or_else(|| CardinalError(format!("Card {} is not in player {}'s sideboard", into.0, player.0)))?;
+        sideboard_cards.push(deck_cards.remove(out_index));
+        deck_cards.push(sideboard_cards.remove(in_index));
+        events.push(Event::CardMoved { card: *out, from: deck_zone.clone(), to: sideboard_zone.clone() });
+        events.push(Event::CardMoved { card: *into, from: sideboard_zone.clone(), to: deck_zone.clone() });
+    }
+
+    let (min, max) = (rules.players.min_deck_size, rules.players.max_deck_size);
+    if deck_cards.len() < min || deck_cards.len() > max {
+        return Err(CardinalError(format!(
+            "Deck would have {} cards; the rules require {} to {}",
+            deck_cards.len(), min, max
+        )));
+    }
 
-    state
+    state.zones[deck].cards = deck_cards;
+    state.zones[sideboard].cards = sideboard_cards;
+    Ok(events)
 }
 
 /// Shuffle a player's deck in-place using the provided RNG
@@ -48,7 +293,6 @@ fn shuffle_player_deck(
     state: &mut GameState,
     player: PlayerId,
     rng: &mut GameRng,
-    _rules: &Ruleset,
 ) {
     // Find the deck zone for this player
     let deck_zone_id_string = format!("deck@{}", player.0);
@@ -56,11 +300,7 @@ fn shuffle_player_deck(
         .find(|z| z.id.0 == deck_zone_id_string);
 
     if let Some(zone) = deck_zone {
-        // Fisher-Yates shuffle
-        for i in (1..zone.cards.len()).rev() {
-            let j: usize = rng.generate::<u32>() as usize % (i + 1);
-            zone.cards.swap(i, j);
-        }
+        rng.shuffle(&mut zone.cards);
     }
 }
 
@@ -91,47 +331,37 @@ fn determine_first_player(
     }
 }
 
-/// Draw `count` cards from a player's deck to their hand
+/// Draw `count` cards from a player's deck to their hand, returning the cards
+/// that actually reached the hand. Draws stop once the hand is at
+/// `max_hand_size`; the cards that didn't fit stay on top of the deck.
 fn draw_cards(
     state: &mut GameState,
     player: PlayerId,
     count: u32,
     rules: &Ruleset,
-) {
+) -> Vec<CardId> {
     let deck_zone_id_string = format!("deck@{}", player.0);
     let hand_zone_id_string = format!("hand@{}", player.0);
 
-    // Find deck and hand zones
-    let deck_cards: Vec<CardId> = state.zones.iter()
-        .find(|z| z.id.0 == deck_zone_id_string)
-        .map(|z| z.cards.clone())
-        .unwrap_or_default();
+    // Only as many cards as the hand has room for
+    let room = state.zones.iter()
+        .find(|z| z.id.0 == hand_zone_id_string)
+        .map_or(0, |z| rules.players.max_hand_size.saturating_sub(z.cards.len()));
 
     // Draw from the top of deck (first card in the Vec)
-    let cards_to_draw = deck_cards.iter()
-        .take(count as usize)
-        .cloned()
-        .collect::<Vec<_>>();
-
-    // Remove from deck
-    if let Some(deck_zone) = state.zones.iter_mut()
-        .find(|z| z.id.0 == deck_zone_id_string)
-    {
-        for _ in 0..cards_to_draw.len() {
-            if !deck_zone.cards.is_empty() {
-                deck_zone.cards.remove(0);
-            }
+    let drawn: Vec<CardId> = match state.zones.iter_mut().find(|z| z.id.0 == deck_zone_id_string) {
+        Some(deck_zone) => {
+            let take = (count as usize).min(room).min(deck_zone.cards.len());
+            deck_zone.cards.drain(..take).collect()
         }
-    }
+        None => Vec::new(),
+    };
 
-    // Add to hand (respecting max hand size)
     if let Some(hand_zone) = state.zones.iter_mut()
         .find(|z| z.id.0 == hand_zone_id_string)
     {
-        for card in cards_to_draw {
-            if hand_zone.cards.len() < rules.players.max_hand_size {
-                hand_zone.cards.push(card);
-            }
-        }
+        hand_zone.cards.extend(drawn.iter().copied());
     }
+
+    drawn
 }
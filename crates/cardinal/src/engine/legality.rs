@@ -1,23 +1,36 @@
 use crate::{
     engine::core::GameEngine,
-    ids::PlayerId,
-    model::action::Action,
+    ids::{CardId, PlayerId},
+    model::action::{Action, TargetRef},
+    model::command::{AllowedTargets, ChoiceKind},
     error::CardinalError,
+    rules::schema::{AdditionalCost, StepDef},
 };
 
 /// Validate that an action is legal in the current game state.
 /// Checks:
 /// - Only the priority player can pass priority
-/// - Only the active player can take other actions
-/// - The current phase allows actions
+/// - Only the active player can take other actions (the priority player for quick cards)
+/// - The current phase allows actions, and the current step allows this kind of action
 /// - Stack requirements are met (if action requires empty stack)
 /// - Zone ownership and card ownership are valid
+/// - Nothing but answering it is allowed while a choice is pending (conceding
+///   and mana abilities aside)
+/// - Chosen targets match the pending choice
+/// - Mana abilities are activated from an untapped card on the player's field
 pub fn validate(engine: &GameEngine, player: PlayerId, action: &Action) -> Result<(), CardinalError> {
     // If game has ended, no more actions allowed
     if engine.state.ended.is_some() {
         return Err(CardinalError("Game has ended".to_string()));
     }
 
+    // A pending choice has to be answered before play goes on
+    if engine.state.pending_choice.is_some()
+        && matches!(action, Action::PassPriority | Action::PlayCard { .. })
+    {
+        return Err(CardinalError("A choice is pending and must be answered first".to_string()));
+    }
+
     // Check action-specific permissions
     match action {
         Action::PassPriority => {
@@ -35,25 +48,38 @@ pub fn validate(engine: &GameEngine, player: PlayerId, action: &Action) -> Resul
             Ok(())
         }
         Action::PlayCard { card, from } => {
-            // Active player only
-            if player != engine.state.turn.active_player {
-                return Err(CardinalError(format!(
-                    "Only active player ({:?}) can take this action",
-                    engine.state.turn.active_player
-                )));
-            }
+            // Quick cards can be played by whoever holds priority, in any phase,
+            // which is how players respond to items on the stack
+            let quick = has_keyword(engine, *card, "quick");
+
+            if quick {
+                if player != engine.state.turn.priority_player {
+                    return Err(CardinalError(format!(
+                        "Only priority player ({:?}) can play quick cards",
+                        engine.state.turn.priority_player
+                    )));
+                }
+            } else {
+                // Active player only
+                if player != engine.state.turn.active_player {
+                    return Err(CardinalError(format!(
+                        "Only active player ({:?}) can take this action",
+                        engine.state.turn.active_player
+                    )));
+                }
 
-            // Check phase permissions
-            let current_phase = engine.rules.turn.phases.iter()
-                .find(|p| p.id.as_str() == engine.state.turn.phase.0)
-                .ok_or_else(|| CardinalError("Invalid phase".to_string()))?;
+                // Check phase permissions
+                let current_phase = engine.rules.turn.phases.iter()
+                    .find(|p| p.id.as_str() == engine.state.turn.phase.0)
+                    .ok_or_else(|| CardinalError("Invalid phase".to_string()))?;
 
-            // PlayCard requires the phase to allow actions
-            if !current_phase.allow_actions {
-                return Err(CardinalError(format!(
-                    "Current phase '{}' does not allow card plays",
-                    current_phase.name
-                )));
+                // PlayCard requires the phase to allow actions
+                if !current_phase.allow_actions {
+                    return Err(CardinalError(format!(
+                        "Current phase '{}' does not allow card plays",
+                        current_phase.name
+                    )));
+                }
             }
 
             // Verify the source zone exists and is owned by the player
@@ -61,10 +87,10 @@ pub fn validate(engine: &GameEngine, player: PlayerId, action: &Action) -> Resul
                 .find(|z| z.id == *from)
                 .ok_or_else(|| CardinalError("Source zone does not exist".to_string()))?;
 
-            if let Some(owner) = zone.owner {
-                if owner != player {
-                    return Err(CardinalError("Cannot play cards from opponent's zones".to_string()));
-                }
+            if let Some(owner) = zone.owner
+                && owner != player
+            {
+                return Err(CardinalError("Cannot play cards from opponent's zones".to_string()));
             }
 
             // Verify the card exists in the source zone
@@ -72,24 +98,70 @@ pub fn validate(engine: &GameEngine, player: PlayerId, action: &Action) -> Resul
                 return Err(CardinalError("Card is not in the specified source zone".to_string()));
             }
 
+            // Some card types may only be played so many times a turn
+            if let Some(card_type) = engine.cards.get(&engine.state.definition_of(*card).0).map(|def| &def.card_type)
+                && let Some(limit) = engine.rules.turn.play_limits.get(card_type)
+                && engine.state.players.iter()
+                    .find(|p| p.id == player)
+                    .and_then(|p| p.plays_this_turn.get(card_type))
+                    .is_some_and(|played| played >= limit)
+            {
+                return Err(CardinalError(format!(
+                    "Cannot play another {} this turn (limit {})",
+                    card_type, limit
+                )));
+            }
+
+            // The current step may restrict which kinds of action are allowed
+            let action_id = if quick { "play_quick" } else { "play_card" };
+            if let Some(step) = current_step(engine)
+                && let Some(allowed) = &step.allowed_actions
+                && !allowed.iter().any(|a| a == action_id)
+            {
+                return Err(CardinalError(format!(
+                    "Current step '{}' does not allow '{}'",
+                    step.name, action_id
+                )));
+            }
+
             // If action requires empty stack, check that stack is empty
             if let Some(action_def) = engine.rules.actions.iter()
-                .find(|a| a.id == "play_card")
+                .find(|a| a.id == action_id)
+                && action_def.requires_empty_stack
+                && !engine.state.stack.is_empty()
+            {
+                return Err(CardinalError(
+                    "Cannot play card: stack is not empty and action requires empty stack"
+                        .to_string(),
+                ));
+            }
+
+            // The card's whole cost is paid as it's played
+            if engine.cards.contains_key(&engine.state.definition_of(*card).0)
+                && !crate::engine::costs::can_afford(engine, player, *card)
             {
-                if action_def.requires_empty_stack && !engine.state.stack.is_empty() {
-                    return Err(CardinalError(
-                        "Cannot play card: stack is not empty and action requires empty stack"
-                            .to_string(),
-                    ));
+                return Err(CardinalError("Cannot play card: its cost can't be paid".to_string()));
+            }
+
+            // So must anything it costs on top, with a card other than itself
+            match engine.cards.get(&engine.state.definition_of(*card).0).and_then(|def| def.additional_cost) {
+                Some(AdditionalCost::SacrificeCreature) if own_creatures(engine, player).iter().all(|c| c == card) => {
+                    return Err(CardinalError("Cannot play card: no creature to sacrifice".to_string()));
                 }
+                Some(AdditionalCost::Discard) if !engine.state.zones.iter()
+                    .any(|z| z.id.0 == format!("hand@{}", player.0) && z.cards.iter().any(|c| c != card)) =>
+                {
+                    return Err(CardinalError("Cannot play card: no other card to discard".to_string()));
+                }
+                _ => {}
             }
 
             Ok(())
         }
-        Action::ChooseTarget { choice_id, target: _ } => {
+        Action::ChooseTarget { choice_id, target } => {
             // ChooseTarget is only valid if there's a pending choice with matching ID
             match &engine.state.pending_choice {
-                Some(choice) if choice.id == *choice_id => Ok(()),
+                Some(choice) if choice.id == *choice_id => validate_target(engine, player, &choice.kind, target),
                 Some(choice) => Err(CardinalError(format!(
                     "Choice ID mismatch: expected {}, got {}",
                     choice.id, choice_id
@@ -97,5 +169,199 @@ pub fn validate(engine: &GameEngine, player: PlayerId, action: &Action) -> Resul
                 None => Err(CardinalError("No pending choice to respond to".to_string())),
             }
         }
+        Action::OrderTriggers { choice_id, order } => {
+            let choice = engine.state.pending_choice.as_ref()
+                .ok_or_else(|| CardinalError("No pending choice to respond to".to_string()))?;
+            if choice.id != *choice_id {
+                return Err(CardinalError(format!(
+                    "Choice ID mismatch: expected {}, got {}",
+                    choice.id, choice_id
+                )));
+            }
+            let ChoiceKind::OrderTriggers { items } = &choice.kind else {
+                return Err(CardinalError("This choice is not a trigger ordering".to_string()));
+            };
+            // Only the active player's triggers are ever put up for ordering
+            if player != engine.state.turn.active_player {
+                return Err(CardinalError(format!(
+                    "Only active player ({:?}) can order their triggers",
+                    engine.state.turn.active_player
+                )));
+            }
+            let mut given = order.clone();
+            let mut expected = items.clone();
+            given.sort_unstable();
+            expected.sort_unstable();
+            if given != expected {
+                return Err(CardinalError(format!(
+                    "Order must list each of the stack items {:?} exactly once",
+                    items
+                )));
+            }
+            Ok(())
+        }
+        Action::ChooseCards { choice_id, cards } => {
+            let choice = engine.state.pending_choice.as_ref()
+                .ok_or_else(|| CardinalError("No pending choice to respond to".to_string()))?;
+            if choice.id != *choice_id {
+                return Err(CardinalError(format!(
+                    "Choice ID mismatch: expected {}, got {}",
+                    choice.id, choice_id
+                )));
+            }
+            let ChoiceKind::ChooseCards { zone, filter, max } = &choice.kind else {
+                return Err(CardinalError("This choice is not a card selection".to_string()));
+            };
+            if cards.len() > *max {
+                return Err(CardinalError(format!("Choose at most {} card(s), not {}", max, cards.len())));
+            }
+            let zone_cards = engine.state.zones.iter()
+                .find(|z| z.id == *zone)
+                .map(|z| z.cards.as_slice())
+                .unwrap_or(&[]);
+            for (i, card) in cards.iter().enumerate() {
+                if cards[..i].contains(card) {
+                    return Err(CardinalError(format!("Card {} is chosen more than once", card.0)));
+                }
+                if !zone_cards.contains(card) {
+                    return Err(CardinalError(format!("Card {} is not in {}", card.0, zone.0)));
+                }
+                if !filter.matches_card(*card, &engine.cards, &engine.state) {
+                    return Err(CardinalError(format!("Card {} doesn't match '{}'", card.0, filter)));
+                }
+            }
+            Ok(())
+        }
+        Action::ActivateAbility { card, ability } => {
+            // Whoever holds priority, or is answering a choice (e.g. paying
+            // for a spell), may make mana
+            let acting = match &engine.state.pending_choice {
+                Some(choice) => engine.chooser(choice),
+                None => engine.state.turn.priority_player,
+            };
+            if player != acting {
+                return Err(CardinalError(format!(
+                    "Only {:?} can activate mana abilities right now",
+                    acting
+                )));
+            }
+
+            if !engine.state.controlled_cards(player).contains(card) {
+                return Err(CardinalError(format!("Card {} is not on your field", card.0)));
+            }
+            if engine.state.card_instances.get(card).is_some_and(|i| i.tapped) {
+                return Err(CardinalError(format!("Card {} is already tapped", card.0)));
+            }
+
+            let def = engine.cards.get(&engine.state.definition_of(*card).0)
+                .ok_or_else(|| CardinalError(format!("Card {} has no definition", card.0)))?;
+            match def.abilities.get(*ability) {
+                Some(a) if a.mana_ability => crate::engine::costs::pay_ability_cost(engine, player, *card, *ability).map(|_| ()),
+                Some(_) => Err(CardinalError(format!("Ability {} of card {} is not a mana ability", ability, card.0))),
+                None => Err(CardinalError(format!("Card {} has no ability {}", card.0, ability))),
+            }
+        }
     }
 }
+
+/// The ruleset definition of the step the game is in, if it has one
+fn current_step(engine: &GameEngine) -> Option<&StepDef> {
+    engine.rules.turn.phases.iter()
+        .find(|p| p.id.as_str() == engine.state.turn.phase.0)?
+        .steps.iter()
+        .find(|s| s.id.as_str() == engine.state.turn.step.0)
+}
+
+/// Check a chosen target against what the pending choice allows
+pub(crate) fn validate_target(
+    engine: &GameEngine,
+    chooser: PlayerId,
+    kind: &ChoiceKind,
+    target: &TargetRef,
+) -> Result<(), CardinalError> {
+    match (kind, target) {
+        (ChoiceKind::ChooseTarget { allowed: AllowedTargets::AnyPlayer }, TargetRef::Player(player)) => {
+            if engine.state.players.iter().any(|p| p.id == *player) {
+                Ok(())
+            } else {
+                Err(CardinalError(format!("Player {} does not exist", player.0)))
+            }
+        }
+        (ChoiceKind::ChooseTarget { allowed: AllowedTargets::AnyCreatureOnField }, TargetRef::Card(card)) => {
+            let on_field = engine.state.zones.iter()
+                .any(|z| z.id.0.starts_with("field") && z.cards.contains(card));
+            if !on_field {
+                return Err(CardinalError("Target card is not on the field".to_string()));
+            }
+            // Cards without a definition (e.g. tokens) are assumed to be creatures
+            if let Some(def) = engine.cards.get(&engine.state.definition_of(*card).0)
+                && def.card_type != "creature"
+            {
+                return Err(CardinalError(format!("'{}' is not a creature", def.name)));
+            }
+            check_protection(engine, chooser, *card)
+        }
+        (ChoiceKind::ChooseTarget { allowed: AllowedTargets::CardInZone(zone) }, TargetRef::Card(card)) => {
+            let in_zone = engine.state.zones.iter()
+                .any(|z| z.id == *zone && z.cards.contains(card));
+            if in_zone {
+                Ok(())
+            } else {
+                Err(CardinalError(format!("Card {} is not in {}", card.0, zone.0)))
+            }
+        }
+        (ChoiceKind::ChooseTarget { allowed: AllowedTargets::OwnCreatureOnField { except } }, TargetRef::Card(card)) => {
+            if *except == Some(*card) {
+                return Err(CardinalError(format!("Card {} can't be chosen for this", card.0)));
+            }
+            if !own_creatures(engine, chooser).contains(card) {
+                return Err(CardinalError(format!("Card {} is not a creature you control", card.0)));
+            }
+            Ok(())
+        }
+        (ChoiceKind::OrderTriggers { .. }, _) => Err(CardinalError("This choice is answered by ordering triggers".to_string())),
+        (ChoiceKind::ChooseCards { .. }, _) => Err(CardinalError("This choice is answered by choosing cards".to_string())),
+        (_, TargetRef::Player(_)) => Err(CardinalError("This choice requires a card target".to_string())),
+        (_, TargetRef::Card(_)) => Err(CardinalError("This choice requires a player target".to_string())),
+    }
+}
+
+/// Creatures `player` controls on the field. Cards without a definition
+/// (e.g. tokens) are assumed to be creatures.
+pub(crate) fn own_creatures(engine: &GameEngine, player: PlayerId) -> Vec<CardId> {
+    engine.state.controlled_cards(player).into_iter()
+        .filter(|card| engine.cards.get(&engine.state.definition_of(*card).0)
+            .is_none_or(|def| def.card_type == "creature"))
+        .collect()
+}
+
+/// Reject targeting a card protected from `chooser`
+///
+/// `shroud` stops everyone, `hexproof` stops everyone except the card's
+/// controller (see `GameState::controller_of`). Keywords come from the card
+/// definition and from keywords granted to the card instance.
+pub(crate) fn check_protection(engine: &GameEngine, chooser: PlayerId, card: CardId) -> Result<(), CardinalError> {
+    let name = engine.cards.get(&engine.state.definition_of(card).0)
+        .map(|def| def.name.clone())
+        .unwrap_or_else(|| format!("Card {}", card.0));
+
+    if has_keyword(engine, card, "shroud") {
+        return Err(CardinalError(format!("'{}' has shroud and can't be targeted", name)));
+    }
+
+    if has_keyword(engine, card, "hexproof") && engine.state.controller_of(card) != Some(chooser) {
+        return Err(CardinalError(format!(
+            "'{}' has hexproof and can't be targeted by opponents",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether a card has a keyword, printed or granted
+fn has_keyword(engine: &GameEngine, card: CardId, keyword: &str) -> bool {
+    crate::rules::query::effective_keywords(card, &engine.cards, &engine.state)
+        .iter()
+        .any(|k| k == keyword)
+}
@@ -7,3 +7,6 @@ pub mod init;
 pub mod cards;
 pub mod scripting;
 pub mod effect_executor;
+pub mod costs;
+pub mod oracle;
+pub mod ai;
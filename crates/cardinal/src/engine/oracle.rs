@@ -0,0 +1,551 @@
+//! Rules text for card abilities, generated from their structured definitions
+//!
+//! Used for card previews and UIs so the text players read can't drift from
+//! what the ability actually does.
+
+use std::collections::HashMap;
+
+use crate::{
+    engine::cards::CardRegistry,
+    rules::schema::{CardAbility, EffectSpec},
+};
+
+/// Render an ability as rules text, e.g. "Deal 3 damage to target player. Draw a card."
+///
+/// Covers the builtin TOML effects with the same parameter defaults the engine
+/// uses. Card IDs in params are shown by name when the registry knows them.
+/// Scripted and unknown effects fall back to a generic description.
+pub fn render_ability_text(ability: &CardAbility, registry: &CardRegistry) -> String {
+    let clauses: Vec<String> = ability.effect_specs().iter()
+        .map(|spec| match &spec.condition {
+            Some(condition) => sentence(&format!(
+                "if {}, {}",
+                condition_text(condition),
+                lowercase_first(&effect_text(spec, registry)),
+            )),
+            None => sentence(&effect_text(spec, registry)),
+        })
+        .collect();
+    let body = clauses.join(" ");
+
+    // Mana abilities are activated by tapping rather than triggered
+    let mut prefix = if ability.mana_ability {
+        Some("Tap: ".to_string())
+    } else {
+        trigger_prefix(&ability.trigger)
+    };
+    if let Some(condition) = &ability.condition {
+        prefix = match prefix {
+            Some(trigger) => Some(format!("{}if {}, ", trigger, condition_text(condition))),
+            None => Some(format!("If {}, ", condition_text(condition))),
+        };
+    }
+
+    match prefix {
+        Some(prefix) => format!("{}{}", prefix, lowercase_first(&body)),
+        None => body,
+    }
+}
+
+/// The lead-in for a trigger; spells (`on_play`) read as plain instructions
+fn trigger_prefix(trigger: &str) -> Option<String> {
+    match trigger {
+        "on_play" | "" => None,
+        "etb" => Some("When this enters the field, ".to_string()),
+        "on_damage" => Some("Whenever this is dealt damage, ".to_string()),
+        "on_gain_life" => Some("Whenever you gain life, ".to_string()),
+        "on_lose_life" => Some("Whenever you lose life, ".to_string()),
+        "on_life_change" => Some("Whenever your life total changes, ".to_string()),
+        other => Some(format!("When {}, ", other.trim_start_matches("on_").replace('_', " "))),
+    }
+}
+
+/// A condition as written, except coin flip checks read as "heads" / "tails"
+fn condition_text(condition: &str) -> String {
+    match condition.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["coin_flip()", "==", side @ ("heads" | "tails")] => side.to_string(),
+        _ => condition.to_string(),
+    }
+}
+
+/// The effect's text, saying how long it lasts when that's limited
+fn effect_text(spec: &EffectSpec, registry: &CardRegistry) -> String {
+    let text = render_effect(spec, registry);
+    match spec.params.get("duration").map(|d| d.as_str()) {
+        Some("end_of_turn") => format!("{} until end of turn", text),
+        _ => text,
+    }
+}
+
+fn render_effect(spec: &EffectSpec, registry: &CardRegistry) -> String {
+    let params = &spec.params;
+    let amount = |default: i32| -> i32 {
+        params.get("amount").and_then(|s| s.parse().ok()).unwrap_or(default)
+    };
+    let resource = params.get("resource").map(|s| s.as_str()).unwrap_or("mana");
+    let counter_type = params.get("counter_type").map(|s| s.as_str()).unwrap_or("+1/+1");
+
+    if let Some(script) = spec.effect.strip_prefix("script:") {
+        return format!("Run the '{}' script", script);
+    }
+
+    match spec.effect.as_str() {
+        "damage" => match params.get("amount").and_then(|a| count_text(a)) {
+            Some(count) => format!("Deal damage to {} equal to {}", target_object(params), count),
+            None => format!("Deal {} damage to {}", amount(1), target_object(params)),
+        },
+        "draw" => {
+            let cards = match amount(1) {
+                1 => "a card".to_string(),
+                n => format!("{} cards", n),
+            };
+            match player_subject(params) {
+                (_, true) => format!("Draw {}", cards),
+                _ => player_does(params, "draw", "draws", &cards),
+            }
+        }
+        "mill" => {
+            let cards = match amount(1) {
+                1 => "a card".to_string(),
+                n => format!("{} cards", n),
+            };
+            match player_subject(params) {
+                (_, true) => format!("Mill {}", cards),
+                _ => player_does(params, "mill", "mills", &cards),
+            }
+        }
+        "prevent_damage" => {
+            let combat = if params.get("combat_only").map(|s| s.as_str()) == Some("true") { "combat " } else { "" };
+            let protected = match params.get("target") {
+                Some(_) => target_object(params),
+                None if params.contains_key("card") => card_name(params, registry),
+                None => "you".to_string(),
+            };
+            format!("Prevent the next {} {}damage that would be dealt to {}", amount(1), combat, protected)
+        }
+        "gain_life" => player_does(params, "gain", "gains", &format!("{} life", amount(1))),
+        "lose_life" => player_does(params, "lose", "loses", &format!("{} life", amount(1))),
+        "set_life" => format!("{} life total becomes {}", possessive(params), amount(20)),
+        "pump" => format!(
+            "{} gets {}/{}",
+            capitalize(&card_name(params, registry)),
+            signed(param_i32(params, "power", 1)),
+            signed(param_i32(params, "toughness", 1)),
+        ),
+        "set_stats" => format!(
+            "{} becomes {}/{}",
+            capitalize(&card_name(params, registry)),
+            param_i32(params, "power", 0),
+            param_i32(params, "toughness", 0),
+        ),
+        "grant_keyword" => format!(
+            "{} gains {}",
+            capitalize(&card_name(params, registry)),
+            params.get("keyword").map(|s| s.as_str()).unwrap_or("a keyword"),
+        ),
+        "remove_keyword" => format!(
+            "{} loses {}",
+            capitalize(&card_name(params, registry)),
+            params.get("keyword").map(|s| s.as_str()).unwrap_or("a keyword"),
+        ),
+        "gain_resource" => player_does(params, "gain", "gains", &format!("{} {}", amount(1), resource)),
+        "spend_resource" => player_does(params, "spend", "spends", &format!("{} {}", amount(1), resource)),
+        "set_resource" => format!("{} {} becomes {}", possessive(params), resource, amount(0)),
+        "add_counter" => format!(
+            "Put {} on {}",
+            counters(amount(1), counter_type),
+            card_name(params, registry),
+        ),
+        "remove_counter" => format!(
+            "Remove {} from {}",
+            counters(amount(1), counter_type),
+            card_name(params, registry),
+        ),
+        "create_token" => {
+            let token = params.get("token_type").map(|s| s.as_str()).unwrap_or("1/1_soldier");
+            let zone = params.get("zone").map(|s| s.as_str()).unwrap_or("field");
+            let mut text = player_does(params, "create", "creates", &format!("a {} token", token.replace('_', " ")));
+            if zone != "field" {
+                text.push_str(&format!(" in {}", zone));
+            }
+            text
+        }
+        "move_card" => format!(
+            "Move {} from {} to {}",
+            card_name(params, registry),
+            params.get("from_zone").map(|s| s.as_str()).unwrap_or("hand"),
+            params.get("to_zone").map(|s| s.as_str()).unwrap_or("field"),
+        ),
+        "move_all" => {
+            let query = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .unwrap_or_default();
+            let mut cards: Vec<&str> = query.keywords.iter().map(|k| k.as_str()).collect();
+            cards.push(query.card_type.as_deref().unwrap_or("card"));
+            let from = params.get("from_zone").map(|s| s.as_str()).unwrap_or("field");
+            let to = params.get("to_zone").map(|s| s.as_str()).unwrap_or("graveyard");
+            let zone = match params.get("target") {
+                Some(_) => format!("{} {}", lowercase_first(&possessive(params)), from),
+                None => format!("every {}", from),
+            };
+            format!("Move each {} in {} to {}", cards.join(" "), zone, to)
+        }
+        "search" => {
+            let query = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .unwrap_or_default();
+            let mut cards: Vec<&str> = query.keywords.iter().map(|k| k.as_str()).collect();
+            cards.push(query.card_type.as_deref().unwrap_or(""));
+            cards.push(if param_usize(params, "max", 1) == 1 { "card" } else { "cards" });
+            let cards = cards.into_iter().filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ");
+            let (subject, second_person) = player_subject(params);
+            let (zone, to) = (
+                params.get("zone").map(|s| s.as_str()).unwrap_or("deck"),
+                params.get("to_zone").map(|s| s.as_str()).unwrap_or("hand"),
+            );
+            let (verb, their) = if second_person { ("search", "your") } else { ("searches", "their") };
+            format!(
+                "{} {} {} {} for up to {} {}, put them into {} {}, then shuffle",
+                capitalize(&subject), verb, their, zone, param_usize(params, "max", 1), cards, their, to,
+            )
+        }
+        "cascade" => {
+            let query = crate::rules::query::CardQuery::parse(params.get("filter").map(|s| s.as_str()).unwrap_or(""))
+                .unwrap_or_default();
+            let mut cards: Vec<&str> = query.keywords.iter().map(|k| k.as_str()).collect();
+            cards.push(query.card_type.as_deref().unwrap_or(""));
+            cards.push("card");
+            let cards = cards.into_iter().filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ");
+            let (subject, second_person) = player_subject(params);
+            let (verb, their) = if second_person { ("reveal", "your") } else { ("reveals", "their") };
+            let hit = if params.get("cast").map(|s| s.as_str()) == Some("false") {
+                format!("put it into {} hand", their)
+            } else {
+                "cast it without paying its cost".to_string()
+            };
+            format!(
+                "{} {} cards from the top of {} deck until {} {} {} {}, {}, and put the rest into {} graveyard",
+                capitalize(&subject), verb, their, subject, verb, article(&cards), cards, hit, their,
+            )
+        }
+        "gain_control" => format!("Gain control of {}", card_object(params, registry)),
+        "tap" | "untap" => format!("{} {}", capitalize(&spec.effect), card_object(params, registry)),
+        "discard" => {
+            let cards = match amount(1) {
+                1 => "a card".to_string(),
+                n => format!("{} cards", n),
+            };
+            let mut text = player_does(params, "discard", "discards", &cards);
+            if params.get("mode").map(|s| s.as_str()) == Some("random") {
+                text.push_str(" at random");
+            }
+            text
+        }
+        "discard_chosen_from" => {
+            let (subject, second_person) = player_subject(params);
+            let (hand, who) = if second_person {
+                ("your hand".to_string(), "You discard")
+            } else {
+                (format!("{}'s hand", subject), "They discard")
+            };
+            format!("Look at {} and choose a card from it. {} that card", hand, who)
+        }
+        "coin_flip" => match params.get("target") {
+            Some(_) => player_does(params, "flip", "flips", "a coin"),
+            None => "Flip a coin".to_string(),
+        },
+        "reveal_hand" => {
+            let hand = if player_subject(params).1 { "your hand" } else { "their hand" };
+            player_does(params, "reveal", "reveals", hand)
+        }
+        "extra_turn" => player_does(params, "take", "takes", "an extra turn after this one"),
+        "skip_next" => {
+            let possessive = if player_subject(params).1 { "your" } else { "their" };
+            let step = params.get("step").map(|s| s.as_str()).unwrap_or("step");
+            player_does(params, "skip", "skips", &format!("{} next {}", possessive, step))
+        }
+        "counter" => match params.get("stack_item") {
+            Some(id) => format!("Counter stack item {}", id),
+            None => "Counter target spell or ability".to_string(),
+        },
+        "transform" => format!(
+            "Transform {} into {}",
+            card_object(params, registry),
+            params.get("into")
+                .and_then(|id| id.parse::<u32>().ok())
+                .and_then(|id| registry.get(&id))
+                .map(|def| def.name.clone())
+                .unwrap_or_else(|| "another card".to_string()),
+        ),
+        "shuffle_into_deck" => format!("Shuffle {} into its owner's deck", card_object(params, registry)),
+        other => capitalize(&other.replace('_', " ")),
+    }
+}
+
+/// Who a player-targeted effect hits, as an object ("target player", "you")
+fn target_object(params: &HashMap<String, String>) -> String {
+    match params.get("target").map(|s| s.as_str()) {
+        Some("choose:creature") => "target creature".to_string(),
+        Some("choose:player") => "target player".to_string(),
+        _ => player_subject(params).0,
+    }
+}
+
+/// The player an effect acts on, and whether the verb takes second person
+///
+/// `target` wins over the older `player` param; with neither the effect
+/// applies to the controller.
+fn player_subject(params: &HashMap<String, String>) -> (String, bool) {
+    let who = params.get("target").or_else(|| params.get("player")).map(|s| s.as_str());
+    match who {
+        None | Some("self") | Some("you") | Some("controller") => ("you".to_string(), true),
+        Some("opponent") => ("your opponent".to_string(), false),
+        Some("each") => ("each player".to_string(), false),
+        Some("all_opponents") => ("each opponent".to_string(), false),
+        Some("choose:player") => ("target player".to_string(), false),
+        Some(other) => (format!("player {}", other), false),
+    }
+}
+
+/// "You gain 3 life" / "Your opponent gains 3 life"
+fn player_does(params: &HashMap<String, String>, verb: &str, verb_s: &str, object: &str) -> String {
+    let (subject, second_person) = player_subject(params);
+    let verb = if second_person { verb } else { verb_s };
+    format!("{} {} {}", capitalize(&subject), verb, object)
+}
+
+/// "a" / "an" for the word that follows
+fn article(word: &str) -> &'static str {
+    if word.starts_with(['a', 'e', 'i', 'o', 'u']) { "an" } else { "a" }
+}
+
+/// "Your" / "Your opponent's" / "Player 1's"
+fn possessive(params: &HashMap<String, String>) -> String {
+    match player_subject(params) {
+        (_, true) => "Your".to_string(),
+        (subject, false) => format!("{}'s", capitalize(&subject)),
+    }
+}
+
+/// The card a card-targeted effect acts on, allowing a chosen creature
+fn card_object(params: &HashMap<String, String>, registry: &CardRegistry) -> String {
+    match params.get("target").map(|s| s.as_str()) {
+        Some("choose:creature") => "target creature".to_string(),
+        _ => card_name(params, registry),
+    }
+}
+
+/// The `card` param by name, or "this" when the effect acts on its source
+fn card_name(params: &HashMap<String, String>, registry: &CardRegistry) -> String {
+    match params.get("card") {
+        None => "this".to_string(),
+        Some(id) => id.parse::<u32>().ok()
+            .and_then(|id| registry.get(&id))
+            .map(|def| def.name.clone())
+            .unwrap_or_else(|| format!("card {}", id)),
+    }
+}
+
+fn counters(amount: i32, counter_type: &str) -> String {
+    match amount {
+        1 => format!("a {} counter", counter_type),
+        n => format!("{} {} counters", n, counter_type),
+    }
+}
+
+/// "the number of creatures you control" for a `count(...)` amount
+fn count_text(amount: &str) -> Option<String> {
+    let player = amount.trim()
+        .strip_prefix("count(creatures_controlled_by(")?
+        .strip_suffix("))")?;
+    let who = match player.trim() {
+        "self" | "you" | "controller" => "you control".to_string(),
+        "opponent" => "your opponent controls".to_string(),
+        other => format!("player {} controls", other),
+    };
+    Some(format!("the number of creatures {}", who))
+}
+
+fn param_usize(params: &HashMap<String, String>, key: &str, default: usize) -> usize {
+    params.get(key).and_then(|s| s.parse().ok()).unwrap_or(default)
+}
+
+fn param_i32(params: &HashMap<String, String>, key: &str, default: i32) -> i32 {
+    params.get(key).and_then(|s| s.parse().ok()).unwrap_or(default)
+}
+
+fn signed(n: i32) -> String {
+    if n >= 0 { format!("+{}", n) } else { n.to_string() }
+}
+
+fn sentence(clause: &str) -> String {
+    format!("{}.", capitalize(clause))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ability(toml_str: &str) -> CardAbility {
+        toml::from_str(toml_str).unwrap()
+    }
+
+    #[test]
+    fn test_render_bolt() {
+        let bolt = ability(r#"
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "3", target = "choose:player" }
+        "#);
+
+        assert_eq!(render_ability_text(&bolt, &CardRegistry::new()), "Deal 3 damage to target player.");
+
+        let swarm = ability(r#"
+            trigger = "on_play"
+            effect = "damage"
+            params = { amount = "count(creatures_controlled_by(controller))", target = "opponent" }
+        "#);
+        assert_eq!(
+            render_ability_text(&swarm, &CardRegistry::new()),
+            "Deal damage to your opponent equal to the number of creatures you control."
+        );
+    }
+
+    #[test]
+    fn test_render_mana_ability() {
+        let land = ability(r#"
+            trigger = "activated"
+            mana_ability = true
+            effect = "gain_resource"
+            params = { resource = "mana", amount = "1" }
+        "#);
+
+        assert_eq!(render_ability_text(&land, &CardRegistry::new()), "Tap: you gain 1 mana.");
+    }
+
+    #[test]
+    fn test_render_cantrip_and_trigger() {
+        let cantrip = ability(r#"
+            trigger = "on_play"
+
+            [[effects]]
+            effect = "damage"
+            params = { amount = "2", target = "opponent" }
+
+            [[effects]]
+            effect = "draw"
+            params = { amount = "1" }
+        "#);
+        assert_eq!(
+            render_ability_text(&cantrip, &CardRegistry::new()),
+            "Deal 2 damage to your opponent. Draw a card.",
+        );
+
+        let healer = ability(r#"
+            trigger = "etb"
+            effect = "gain_life"
+            params = { amount = "3" }
+        "#);
+        assert_eq!(
+            render_ability_text(&healer, &CardRegistry::new()),
+            "When this enters the field, you gain 3 life.",
+        );
+    }
+
+    #[test]
+    fn test_render_coin_flip() {
+        let gamble = ability(r#"
+            trigger = "on_play"
+
+            [[effects]]
+            effect = "coin_flip"
+
+            [[effects]]
+            effect = "damage"
+            params = { amount = "4", target = "opponent" }
+            condition = "coin_flip() == heads"
+        "#);
+        assert_eq!(
+            render_ability_text(&gamble, &CardRegistry::new()),
+            "Flip a coin. If heads, deal 4 damage to your opponent.",
+        );
+    }
+
+    #[test]
+    fn test_render_until_end_of_turn() {
+        let leash = ability(r#"
+            trigger = "on_play"
+            effect = "gain_control"
+            params = { target = "choose:creature", duration = "end_of_turn" }
+        "#);
+        assert_eq!(
+            render_ability_text(&leash, &CardRegistry::new()),
+            "Gain control of target creature until end of turn.",
+        );
+    }
+
+    #[test]
+    fn test_render_each_player() {
+        let mine = ability(r#"
+            trigger = "on_play"
+            effect = "draw"
+            params = { amount = "1", player = "each" }
+        "#);
+        assert_eq!(render_ability_text(&mine, &CardRegistry::new()), "Each player draws a card.");
+
+        let siphon = ability(r#"
+            trigger = "on_play"
+            effect = "lose_life"
+            params = { amount = "2", target = "all_opponents" }
+        "#);
+        assert_eq!(render_ability_text(&siphon, &CardRegistry::new()), "Each opponent loses 2 life.");
+
+        let sieve = ability(r#"
+            trigger = "on_play"
+            effect = "mill"
+            params = { amount = "2", target = "opponent" }
+        "#);
+        assert_eq!(render_ability_text(&sieve, &CardRegistry::new()), "Your opponent mills 2 cards.");
+
+        let seize = ability(r#"
+            trigger = "on_play"
+            effect = "discard_chosen_from"
+            params = { target = "opponent" }
+        "#);
+        assert_eq!(
+            render_ability_text(&seize, &CardRegistry::new()),
+            "Look at your opponent's hand and choose a card from it. They discard that card."
+        );
+    }
+
+    #[test]
+    fn test_render_names_registry_cards() {
+        let mut registry = CardRegistry::new();
+        registry.insert(7, toml::from_str(r#"
+            id = "7"
+            name = "Grizzly Bear"
+            card_type = "creature"
+        "#).unwrap());
+
+        let pump = ability(r#"
+            trigger = "on_play"
+            effect = "grant_keyword"
+            params = { card = "7", keyword = "flying" }
+        "#);
+        assert_eq!(render_ability_text(&pump, &registry), "Grizzly Bear gains flying.");
+    }
+}
@@ -3,8 +3,9 @@ use crate::{
     ids::PlayerId,
     model::action::Action,
     model::event::Event,
-    model::command::Command,
+    model::command::{AllowedTargets, ChoiceKind, Command, EffectRef, PendingChoice, StackItem},
     error::CardinalError,
+    rules::schema::AdditionalCost,
 };
 
 /// Apply an action to the game state, returning events that occurred.
@@ -33,21 +34,11 @@ pub fn apply(engine: &mut GameEngine, player: PlayerId, action: Action) -> Resul
             Ok(vec![Event::PriorityPassed { by: player }])
         }
         Action::Concede => {
-            // Handle concede - determine winner as the other player (or None if no valid winner)
-            let winner = engine.state.players.iter()
-                .find(|p| p.id != player)
-                .map(|p| p.id);
-            
-            // Mark game as ended
-            engine.state.ended = Some(crate::state::gamestate::GameEnd {
-                winner,
-                reason: format!("Player {:?} conceded", player),
-            });
-            
-            Ok(vec![Event::GameEnded { 
-                winner, 
-                reason: format!("Player {:?} conceded", player),
-            }])
+            // The player is out; check_game_end decides whether that ends the game
+            if let Some(state) = engine.state.players.iter_mut().find(|p| p.id == player) {
+                state.conceded = true;
+            }
+            Ok(vec![Event::PlayerConceded { player }])
         }
         Action::PlayCard { card, from } => {
             // Look up the play_card action definition to find target zone
@@ -77,48 +68,152 @@ pub fn apply(engine: &mut GameEngine, player: PlayerId, action: Action) -> Resul
             let target_zone_box: Box<str> = target_zone_id.into_boxed_str();
             let target_zone = crate::ids::ZoneId(Box::leak(target_zone_box));
             
-            // Generate commands to move the card
-            let commands = vec![
-                Command::MoveCard { card, from, to: target_zone },
-            ];
-            
+            // Pay the card's cost, mana and life, in the same batch as the move
+            let mut commands = Vec::new();
+            if engine.cards.contains_key(&engine.state.definition_of(card).0) {
+                commands.extend(crate::engine::costs::pay_cost(engine, player, card)?);
+            }
+            commands.extend(crate::engine::cards::with_entry_modifiers(
+                vec![Command::MoveCard { card, from, to: target_zone, position: None }],
+                &engine.cards,
+                &engine.state,
+            ));
+
+            // Count the play against its type's per-turn limit, now that it's paid for
+            if let Some(def) = engine.cards.get(&engine.state.definition_of(card).0)
+                && let Some(p) = engine.state.players.iter_mut().find(|p| p.id == player)
+            {
+                *p.plays_this_turn.entry(def.card_type.clone()).or_insert(0) += 1;
+            }
+
             // Commit commands to state and collect events
             let mut events = crate::engine::events::commit_commands(&mut engine.state, &commands);
+
+            // A card with an additional cost isn't played until that's paid:
+            // the payment waits on the stack for the player to pick a card,
+            // and resolving it plays the card
+            let additional_cost = engine.cards.get(&engine.state.definition_of(card).0)
+                .and_then(|def| def.additional_cost);
+            if let Some(cost) = additional_cost {
+                let (kind, allowed, prompt) = match cost {
+                    AdditionalCost::SacrificeCreature => (
+                        "sacrifice",
+                        AllowedTargets::OwnCreatureOnField { except: Some(card) },
+                        "Choose a creature to sacrifice",
+                    ),
+                    AdditionalCost::Discard => (
+                        "discard",
+                        AllowedTargets::CardInZone(crate::ids::ZoneId(Box::leak(format!("hand@{}", player.0).into_boxed_str()))),
+                        "Choose a card to discard",
+                    ),
+                };
+                let effect = format!("additional_cost_{}_{}_player_{}_target", kind, card.0, player.0);
+                let stack_item = engine.next_stack_id();
+                let payment = [
+                    Command::PushStack {
+                        item: StackItem {
+                            id: stack_item,
+                            source: Some(card),
+                            controller: player,
+                            effect: EffectRef::Builtin(Box::leak(effect.into_boxed_str())),
+                        },
+                    },
+                    Command::RequestChoice {
+                        player,
+                        choice: PendingChoice {
+                            id: engine.next_choice_id(),
+                            prompt: prompt.to_string(),
+                            kind: ChoiceKind::ChooseTarget { allowed },
+                            stack_item: Some(stack_item),
+                        },
+                    },
+                ];
+                let trigger_events = crate::engine::triggers::fire_triggers(engine, &events);
+                events.extend(trigger_events);
+                events.extend(crate::engine::events::commit_commands(&mut engine.state, &payment));
+                return Ok(events);
+            }
             
             // Add the CardPlayed event
             let card_played_event = Event::CardPlayed { player, card };
-            events.push(card_played_event.clone());
-            
-            // Evaluate triggers from CardPlayed event
-            let trigger_commands = crate::engine::triggers::evaluate_triggers(engine, &card_played_event);
-            let trigger_events = crate::engine::events::commit_commands(&mut engine.state, &trigger_commands);
+
+            // The play and its zone changes trigger together; on_play abilities fire first
+            let mut triggering = vec![card_played_event.clone()];
+            triggering.extend(events.iter().cloned());
+            events.push(card_played_event);
+            let trigger_events = crate::engine::triggers::fire_triggers(engine, &triggering);
             events.extend(trigger_events);
             
-            // Evaluate triggers from CardMoved events (extract them first to avoid borrow issues)
-            let card_moved_events: Vec<Event> = events.iter()
-                .filter(|e| matches!(e, Event::CardMoved { .. }))
-                .cloned()
-                .collect();
-            
-            for event in card_moved_events {
-                let trigger_commands = crate::engine::triggers::evaluate_triggers(engine, &event);
-                let trigger_events = crate::engine::events::commit_commands(&mut engine.state, &trigger_commands);
-                events.extend(trigger_events);
+            Ok(events)
+        }
+        Action::ChooseTarget { choice_id: _, target } => {
+            // Clear the pending choice (legality already checked the target)
+            let choice = engine.state.pending_choice.take();
+
+            // Bind the target into the stack item waiting on this choice;
+            // it resolves once the stack is free to resolve again
+            if let Some(stack_id) = choice.and_then(|c| c.stack_item)
+                && let Some(item) = engine.state.stack.iter_mut().find(|i| i.id == stack_id)
+            {
+                item.effect = crate::engine::cards::bind_effect_target(&item.effect, &target);
             }
-            
+
+            Ok(vec![])
+        }
+        Action::ActivateAbility { card, ability } => {
+            // Mana abilities skip the stack: tap the card, pay the ability's
+            // cost and apply the effect now. The tap, payment and effect are
+            // committed as one batch, so an effect that fails to compute or
+            // apply leaves the cost unpaid and the card untapped.
+            let effect = crate::engine::cards::mana_ability_effect(card, ability, player, &engine.cards, &engine.builtins, &engine.state)
+                .ok_or_else(|| CardinalError(format!("Card {} has no usable mana ability {}", card.0, ability)))?;
+            let mut commands = vec![Command::SetTapped { card, tapped: true }];
+            commands.extend(crate::engine::costs::pay_ability_cost(engine, player, card, ability)?);
+            commands.extend(crate::engine::effect_executor::execute_effect_with_cards(
+                &effect,
+                Some(card),
+                player,
+                &engine.state,
+                Some(&engine.scripting),
+                Some(&engine.cards),
+                Some(&engine.builtins),
+            )?);
+
+            let mut events = crate::engine::events::try_commit_commands(&mut engine.state, &commands)?;
+            events.push(Event::ManaAbilityActivated { player, card, ability });
+            let trigger_events = crate::engine::triggers::fire_triggers(engine, &events);
+            events.extend(trigger_events);
+
             Ok(events)
         }
-        Action::ChooseTarget { choice_id: _, target: _ } => {
-            // Clear the pending choice and emit appropriate event
-            // For now, just remove the choice without applying effects
-            // (effect handling will be part of the trigger system)
+        Action::ChooseCards { choice_id: _, cards } => {
+            let choice = engine.state.pending_choice.take();
+
+            // Like a chosen target, the picks are bound into the waiting stack item
+            if let Some(stack_id) = choice.and_then(|c| c.stack_item)
+                && let Some(item) = engine.state.stack.iter_mut().find(|i| i.id == stack_id)
+            {
+                item.effect = crate::engine::cards::bind_chosen_cards(&item.effect, &cards);
+            }
+
+            Ok(vec![])
+        }
+        Action::OrderTriggers { choice_id: _, order } => {
             engine.state.pending_choice = None;
-            
-            // In a full implementation, this would:
-            // 1. Validate the target against the choice's allowed targets
-            // 2. Generate commands based on the effect
-            // 3. Apply those commands
-            
+
+            // Refill the slots these items hold so the first in `order` ends up on top
+            let slots: Vec<usize> = engine.state.stack.iter()
+                .enumerate()
+                .filter(|(_, item)| order.contains(&item.id))
+                .map(|(index, _)| index)
+                .collect();
+            let items: Vec<_> = order.iter().rev()
+                .filter_map(|id| engine.state.stack.iter().find(|item| item.id == *id).cloned())
+                .collect();
+            for (slot, item) in slots.into_iter().zip(items) {
+                engine.state.stack[slot] = item;
+            }
+
             Ok(vec![])
         }
     }
@@ -1,7 +1,11 @@
-use rhai::{Engine, AST, Scope, Dynamic};
-use std::collections::HashMap;
+use rhai::{CallFnOptions, Dynamic, Engine, NativeCallContext, Scope, AST};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 use crate::error::CardinalError;
 
+/// Where `log` calls of one script execution collect their messages
+type LogBuffer = Arc<Mutex<Vec<String>>>;
+
 /// Wrapper around Rhai engine for executing card scripts
 /// Configured for deterministic, safe execution
 pub struct RhaiEngine {
@@ -30,6 +34,67 @@ impl RhaiEngine {
     
     /// Register helper functions available to card scripts
     fn register_helpers(engine: &mut Engine) {
+        // ==============================================
+        // STATE QUERIES (read-only)
+        // ==============================================
+        
+        // Helper: top_cards(player: i32, zone: &str, n: i32) -> Array
+        // Up to n card IDs from the top of a player's zone, without moving them
+        engine.register_fn("top_cards", |ctx: NativeCallContext, player: i32, zone: &str, n: i32| {
+            let zone_id = format!("{}@{}", zone, player);
+            let cards = snapshot(&ctx, "zones", &zone_id)
+                .and_then(|cards| cards.try_cast::<rhai::Array>())
+                .unwrap_or_default();
+            cards.into_iter().take(n.max(0) as usize).collect::<rhai::Array>()
+        });
+        
+        // Helper: last_in_zone(player: i32, zone: &str) -> i32
+        // The card that arrived in a player's zone most recently, e.g. the last
+        // creature to die for "graveyard" (-1 if the zone is empty)
+        engine.register_fn("last_in_zone", |ctx: NativeCallContext, player: i32, zone: &str| {
+            let zone_id = format!("{}@{}", zone, player);
+            snapshot(&ctx, "zones", &zone_id)
+                .and_then(|cards| cards.try_cast::<rhai::Array>())
+                .and_then(|cards| cards.last().and_then(|card| card.as_int().ok()))
+                .unwrap_or(-1)
+        });
+        
+        // Helper: counter_count(card: i32, counter_type: &str) -> i32
+        // Number of counters of a type on a card (0 if it has none)
+        engine.register_fn("counter_count", |ctx: NativeCallContext, card: i32, counter_type: &str| {
+            snapshot(&ctx, "cards", &card.to_string())
+                .and_then(|card| card.try_cast::<rhai::Map>())
+                .and_then(|card| card.get("counters").cloned())
+                .and_then(|counters| counters.try_cast::<rhai::Map>())
+                .and_then(|counters| counters.get(counter_type).and_then(|n| n.as_int().ok()))
+                .unwrap_or(0)
+        });
+        
+        // Helper: power_of(card: i32) -> i32, toughness_of(card: i32) -> i32
+        // A card's current power/toughness, with pumps and counters (0 if unknown)
+        engine.register_fn("power_of", |ctx: NativeCallContext, card: i32| {
+            card_stat(&ctx, card, "power")
+        });
+        engine.register_fn("toughness_of", |ctx: NativeCallContext, card: i32| {
+            card_stat(&ctx, card, "toughness")
+        });
+        
+        // ==============================================
+        // DEBUGGING
+        // ==============================================
+        
+        // Helper: log(message)
+        // Record a message for the script's author, returned alongside the
+        // effects by `execute_ability_logged`. It never changes the effects.
+        engine.register_fn("log", |ctx: NativeCallContext, message: Dynamic| {
+            let buffer = ctx.tag()
+                .and_then(|tag| tag.read_lock::<rhai::Map>().and_then(|tag| tag.get("log").cloned()))
+                .and_then(|log| log.try_cast::<LogBuffer>());
+            if let Some(buffer) = buffer {
+                buffer.lock().expect("script log lock").push(message.to_string());
+            }
+        });
+        
         // ==============================================
         // DAMAGE & LIFE HELPERS
         // ==============================================
@@ -43,6 +108,65 @@ impl RhaiEngine {
             Dynamic::from(map)
         });
         
+        // Helper: prevent_damage(player: i32, amount: i32) -> Dynamic
+        engine.register_fn("prevent_damage", |player: i32, amount: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("prevent_damage"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("amount".into(), Dynamic::from(amount));
+            Dynamic::from(map)
+        });
+        
+        // Helper: coin_flip(player: i32) -> Dynamic
+        engine.register_fn("coin_flip", |player: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("coin_flip"));
+            map.insert("player".into(), Dynamic::from(player));
+            Dynamic::from(map)
+        });
+        
+        // Helper: extra_turn(player: i32) -> Dynamic
+        // The player takes another turn after this one
+        engine.register_fn("extra_turn", |player: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("extra_turn"));
+            map.insert("player".into(), Dynamic::from(player));
+            Dynamic::from(map)
+        });
+        
+        // Helper: skip_next(player: i32, step: &str) -> Dynamic
+        // The player skips the next step or phase with this ID on their turn
+        engine.register_fn("skip_next", |player: i32, step: &str| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("skip_next"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("step".into(), Dynamic::from(step.to_string()));
+            Dynamic::from(map)
+        });
+        
+        // Helper: cascade(player: i32, filter: &str) -> Dynamic
+        // Reveal from the top of the player's deck until a card matches the
+        // filter, cast it for free and put the rest into the graveyard
+        engine.register_fn("cascade", |player: i32, filter: &str| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("cascade"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("filter".into(), Dynamic::from(filter.to_string()));
+            map.insert("cast".into(), Dynamic::from(true));
+            Dynamic::from(map)
+        });
+
+        // Helper: cascade(player: i32, filter: &str, cast: bool) -> Dynamic
+        // As above; with cast = false the hit goes to the player's hand instead
+        engine.register_fn("cascade", |player: i32, filter: &str, cast: bool| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("cascade"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("filter".into(), Dynamic::from(filter.to_string()));
+            map.insert("cast".into(), Dynamic::from(cast));
+            Dynamic::from(map)
+        });
+        
         // Helper: gain_life(player: i32, amount: i32) -> Dynamic
         engine.register_fn("gain_life", |player: i32, amount: i32| {
             let mut map = rhai::Map::new();
@@ -53,7 +177,7 @@ impl RhaiEngine {
         });
         
         // Helper: lose_life(player: i32, amount: i32) -> Dynamic
-        /// Loss of life (doesn't trigger damage effects, unlike deal_damage)
+        // Loss of life (doesn't trigger damage effects, unlike deal_damage)
         engine.register_fn("lose_life", |player: i32, amount: i32| {
             let mut map = rhai::Map::new();
             map.insert("type".into(), Dynamic::from("lose_life"));
@@ -105,6 +229,35 @@ impl RhaiEngine {
             Dynamic::from(map)
         });
         
+        // Helper: discard_random(player: i32, count: i32) -> Dynamic
+        // Discard cards picked at random from the player's hand
+        engine.register_fn("discard_random", |player: i32, count: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("discard_random"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("count".into(), Dynamic::from(count));
+            Dynamic::from(map)
+        });
+        
+        // Helper: discard_choose(player: i32, count: i32) -> Dynamic
+        // Let the player pick which cards to discard from their hand
+        engine.register_fn("discard_choose", |player: i32, count: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("discard_choose"));
+            map.insert("player".into(), Dynamic::from(player));
+            map.insert("count".into(), Dynamic::from(count));
+            Dynamic::from(map)
+        });
+        
+        // Helper: discard_chosen_from(player: i32) -> Dynamic
+        // Look at the player's hand and pick a card from it for them to discard
+        engine.register_fn("discard_chosen_from", |player: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("discard_chosen_from"));
+            map.insert("player".into(), Dynamic::from(player));
+            Dynamic::from(map)
+        });
+        
         // Helper: move_card(card: i32, from_zone: &str, to_zone: &str) -> Dynamic
         // General purpose card movement between zones
         engine.register_fn("move_card", |card: i32, from_zone: &str, to_zone: &str| {
@@ -116,6 +269,81 @@ impl RhaiEngine {
             Dynamic::from(map)
         });
         
+        // Helper: move_card(card: i32, from_zone: &str, to_zone: &str, position: &str) -> Dynamic
+        // Same as move_card, placing the card at "top" or "bottom" of the destination
+        engine.register_fn("move_card", |card: i32, from_zone: &str, to_zone: &str, position: &str| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("move_card"));
+            map.insert("card".into(), Dynamic::from(card));
+            map.insert("from_zone".into(), Dynamic::from(from_zone.to_string()));
+            map.insert("to_zone".into(), Dynamic::from(to_zone.to_string()));
+            map.insert("position".into(), Dynamic::from(position.to_string()));
+            Dynamic::from(map)
+        });
+        
+        // Helper: shuffle_into_deck(card: i32) -> Dynamic
+        // Move a card from any zone into its owner's deck, then shuffle the deck
+        engine.register_fn("shuffle_into_deck", |card: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("shuffle_into_deck"));
+            map.insert("card".into(), Dynamic::from(card));
+            Dynamic::from(map)
+        });
+        
+        // Helper: gain_control(card: i32, player: i32) -> Dynamic
+        // The player takes control of a permanent until it leaves the field
+        engine.register_fn("gain_control", |card: i32, player: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("gain_control"));
+            map.insert("card".into(), Dynamic::from(card));
+            map.insert("player".into(), Dynamic::from(player));
+            Dynamic::from(map)
+        });
+        
+        // Helper: until_end_of_turn(effect: Map) -> Dynamic
+        // Make an effect (a pump, a keyword, gained control) wear off as the turn ends
+        engine.register_fn("until_end_of_turn", |mut effect: rhai::Map| {
+            effect.insert("duration".into(), Dynamic::from("end_of_turn"));
+            Dynamic::from(effect)
+        });
+        
+        // Helper: tap(card: i32) -> Dynamic
+        // Tap a permanent on the field
+        engine.register_fn("tap", |card: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("tap"));
+            map.insert("card".into(), Dynamic::from(card));
+            Dynamic::from(map)
+        });
+        
+        // Helper: transform(card: i32, into: i32) -> Dynamic
+        // Turn a card into another card definition, keeping its counters
+        engine.register_fn("transform", |card: i32, into: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("transform"));
+            map.insert("card".into(), Dynamic::from(card));
+            map.insert("into".into(), Dynamic::from(into));
+            Dynamic::from(map)
+        });
+        
+        // Helper: untap(card: i32) -> Dynamic
+        // Untap a permanent on the field
+        engine.register_fn("untap", |card: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("untap"));
+            map.insert("card".into(), Dynamic::from(card));
+            Dynamic::from(map)
+        });
+        
+        // Helper: counter(stack_item: i32) -> Dynamic
+        // Remove a stack item without resolving it
+        engine.register_fn("counter", |stack_item: i32| {
+            let mut map = rhai::Map::new();
+            map.insert("type".into(), Dynamic::from("counter"));
+            map.insert("stack_item".into(), Dynamic::from(stack_item));
+            Dynamic::from(map)
+        });
+        
         // Helper: shuffle_zone(player: i32, zone: &str) -> Dynamic
         // Shuffle a zone (typically deck)
         engine.register_fn("shuffle_zone", |player: i32, zone: &str| {
@@ -280,8 +508,8 @@ impl RhaiEngine {
         // ==============================================
         
         // Helper: bolt(target: i32, damage: i32) -> Dynamic
-        /// Common TCG pattern: simple direct damage
-        /// Note: Intentionally aliases deal_damage for familiar terminology
+        // Common TCG pattern: simple direct damage
+        // Note: Intentionally aliases deal_damage for familiar terminology
         engine.register_fn("bolt", |target: i32, damage: i32| {
             let mut map = rhai::Map::new();
             map.insert("type".into(), Dynamic::from("damage"));
@@ -334,6 +562,12 @@ impl RhaiEngine {
     /// Execute a card script's ability
     /// Returns a list of effect descriptions as Dynamic values
     pub fn execute_ability(&self, card_id: &str, context: ScriptContext) -> Result<Vec<Dynamic>, CardinalError> {
+        self.execute_ability_logged(card_id, context).map(|output| output.effects)
+    }
+    
+    /// Same as [`execute_ability`](Self::execute_ability), also returning the
+    /// messages the script passed to `log`, in call order
+    pub fn execute_ability_logged(&self, card_id: &str, context: ScriptContext) -> Result<ScriptOutput, CardinalError> {
         let ast = self.scripts.get(card_id)
             .ok_or_else(|| CardinalError(format!("No script registered for card {}", card_id)))?;
         
@@ -354,17 +588,44 @@ impl RhaiEngine {
             scope.push("phase", phase.clone());
         }
         
+        // Zone contents and card stats travel as the call's tag so read-only
+        // helpers can see them
+        let mut zones = rhai::Map::new();
+        for (zone, cards) in context.zones.iter().flatten() {
+            let cards: rhai::Array = cards.iter().map(|&c| Dynamic::from(c as i32)).collect();
+            zones.insert(zone.as_str().into(), Dynamic::from(cards));
+        }
+        let mut cards = rhai::Map::new();
+        for (id, card) in context.cards.iter().flatten() {
+            let counters: rhai::Map = card.counters.iter()
+                .map(|(kind, &n)| (kind.as_str().into(), Dynamic::from(n as rhai::INT)))
+                .collect();
+            let mut map = rhai::Map::new();
+            map.insert("power".into(), Dynamic::from(card.power as rhai::INT));
+            map.insert("toughness".into(), Dynamic::from(card.toughness as rhai::INT));
+            map.insert("counters".into(), Dynamic::from(counters));
+            cards.insert(id.to_string().into(), Dynamic::from(map));
+        }
+        let log = LogBuffer::default();
+        let mut tag = rhai::Map::new();
+        tag.insert("zones".into(), Dynamic::from(zones));
+        tag.insert("cards".into(), Dynamic::from(cards));
+        tag.insert("log".into(), Dynamic::from(log.clone()));
+        let options = CallFnOptions::new().with_tag(tag);
+        
         // Call the execute_ability function in the script
-        match self.engine.call_fn::<Dynamic>(&mut scope, ast, "execute_ability", ()) {
+        match self.engine.call_fn_with_options::<Dynamic>(options, &mut scope, ast, "execute_ability", ()) {
             Ok(result) => {
                 // Convert result to Vec<Dynamic>
                 // Script should return an array of command maps
-                if let Some(arr) = result.clone().try_cast::<rhai::Array>() {
-                    Ok(arr)
+                let effects = if let Some(arr) = result.clone().try_cast::<rhai::Array>() {
+                    arr
                 } else {
                     // Single command, wrap in array
-                    Ok(vec![result])
-                }
+                    vec![result]
+                };
+                let log = std::mem::take(&mut *log.lock().expect("script log lock"));
+                Ok(ScriptOutput { effects, log })
             }
             Err(err) => {
                 Err(CardinalError(format!("Script execution failed for card {}: {}", card_id, err)))
@@ -373,6 +634,15 @@ impl RhaiEngine {
     }
 }
 
+/// What one script execution produced
+#[derive(Debug, Clone)]
+pub struct ScriptOutput {
+    /// Effect descriptions, as returned by `execute_ability`
+    pub effects: Vec<Dynamic>,
+    /// Messages passed to `log`, in call order
+    pub log: Vec<String>,
+}
+
 /// Context passed to script execution
 /// Contains runtime information scripts need to make decisions
 #[derive(Debug, Clone)]
@@ -387,6 +657,41 @@ pub struct ScriptContext {
     pub turn_number: Option<u32>,
     /// Optional: current phase ID
     pub phase: Option<String>,
+    /// Optional: card IDs in each zone keyed by zone ID (e.g. "deck@0"), top first.
+    /// Read by `top_cards` and `last_in_zone`; without it scripts see every
+    /// zone as empty.
+    pub zones: Option<HashMap<String, Vec<u32>>>,
+    /// Optional: current stats and counters of cards keyed by card ID. Read by
+    /// `counter_count`, `power_of` and `toughness_of`, which see 0 without it.
+    pub cards: Option<HashMap<u32, CardSnapshot>>,
+}
+
+/// What scripts can read about a card
+#[derive(Debug, Clone, Default)]
+pub struct CardSnapshot {
+    /// Current power and toughness (see `effective_stats`)
+    pub power: i32,
+    pub toughness: i32,
+    /// Counters on the card by type
+    pub counters: BTreeMap<String, i32>,
+}
+
+/// One entry of a section ("zones" or "cards") of the snapshot passed as
+/// the script call's tag
+fn snapshot(ctx: &NativeCallContext, section: &str, key: &str) -> Option<Dynamic> {
+    ctx.tag()
+        .and_then(|tag| tag.read_lock::<rhai::Map>())
+        .and_then(|tag| tag.get(section).cloned())
+        .and_then(|section| section.try_cast::<rhai::Map>())
+        .and_then(|entries| entries.get(key).cloned())
+}
+
+/// A card's stat from the snapshot, 0 if the card or stat isn't there
+fn card_stat(ctx: &NativeCallContext, card: i32, stat: &str) -> rhai::INT {
+    snapshot(ctx, "cards", &card.to_string())
+        .and_then(|card| card.try_cast::<rhai::Map>())
+        .and_then(|card| card.get(stat).and_then(|n| n.as_int().ok()))
+        .unwrap_or(0)
 }
 
 impl Default for RhaiEngine {
@@ -436,6 +741,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("test_card", context);
@@ -465,6 +772,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("test_card", context);
@@ -502,6 +811,8 @@ mod tests {
             active_player: Some(0),
             turn_number: Some(3),
             phase: Some("main1".to_string()),
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("advanced_card", context);
@@ -537,6 +848,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("drain_card", context);
@@ -563,6 +876,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("cantrip_card", context);
@@ -594,6 +909,8 @@ mod tests {
             active_player: Some(0),
             turn_number: Some(5),
             phase: Some("main1".to_string()),
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("context_card", context);
@@ -622,6 +939,8 @@ mod tests {
             active_player: None,  // Optional fields not provided
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("safe_card", context);
@@ -648,6 +967,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("set_life_card", context);
@@ -682,6 +1003,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("zone_card", context);
@@ -691,6 +1014,142 @@ mod tests {
         assert_eq!(commands.len(), 2);
     }
     
+    #[test]
+    fn test_top_cards_reads_zone_snapshot() {
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                let top = top_cards(controller, "deck", 3);
+                let all = top_cards(controller, "deck", 10);
+                let none = top_cards(controller, "exile", 2);
+                [#{ type: "peek", top: top, all: all.len(), none: none.len() }]
+            }
+        "#;
+        
+        engine.register_script("peek_card".to_string(), script).unwrap();
+        
+        let context = ScriptContext {
+            controller: 1,
+            source_card: 1,
+            active_player: None,
+            turn_number: None,
+            phase: None,
+            zones: Some(HashMap::from([
+                ("deck@1".to_string(), vec![5, 4, 3, 2, 1]),
+                ("deck@0".to_string(), vec![9]),
+            ])),
+            cards: None,
+        };
+        
+        let commands = engine.execute_ability("peek_card", context).unwrap();
+        let map = commands[0].clone().try_cast::<rhai::Map>().unwrap();
+        let top: Vec<i32> = map["top"].clone().into_typed_array().unwrap();
+        assert_eq!(top, vec![5, 4, 3]);
+        assert_eq!(map["all"].as_int().unwrap(), 5);
+        assert_eq!(map["none"].as_int().unwrap(), 0);
+    }
+    
+    #[test]
+    fn test_last_in_zone_reads_zone_snapshot() {
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                [#{ type: "peek", last: last_in_zone(controller, "graveyard"), none: last_in_zone(controller, "exile") }]
+            }
+        "#;
+        
+        engine.register_script("recall_card".to_string(), script).unwrap();
+        
+        let context = ScriptContext {
+            controller: 0,
+            source_card: 1,
+            active_player: None,
+            turn_number: None,
+            phase: None,
+            zones: Some(HashMap::from([("graveyard@0".to_string(), vec![7, 3, 9])])),
+            cards: None,
+        };
+        
+        let commands = engine.execute_ability("recall_card", context).unwrap();
+        let map = commands[0].clone().try_cast::<rhai::Map>().unwrap();
+        assert_eq!(map["last"].as_int().unwrap(), 9);
+        assert_eq!(map["none"].as_int().unwrap(), -1);
+    }
+    
+    #[test]
+    fn test_log_messages_come_back_with_effects() {
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                if controller == 0 {
+                    log("hit branch A");
+                } else {
+                    log("hit branch B");
+                }
+                log(source_card);
+                gain_life(controller, 2)
+            }
+        "#;
+        
+        engine.register_script("chatty_card".to_string(), script).unwrap();
+        
+        let context = ScriptContext {
+            controller: 0,
+            source_card: 7,
+            active_player: None,
+            turn_number: None,
+            phase: None,
+            zones: None,
+            cards: None,
+        };
+        
+        let output = engine.execute_ability_logged("chatty_card", context.clone()).unwrap();
+        assert_eq!(output.log, vec!["hit branch A".to_string(), "7".to_string()]);
+        assert_eq!(output.effects.len(), 1);
+        
+        // Each execution starts with an empty log, and logging doesn't
+        // change the effects
+        let again = engine.execute_ability_logged("chatty_card", context.clone()).unwrap();
+        assert_eq!(again.log, output.log);
+        assert_eq!(engine.execute_ability("chatty_card", context).unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_card_state_queries() {
+        let mut engine = RhaiEngine::new();
+        let script = r#"
+            fn execute_ability() {
+                [#{ type: "read", power: power_of(source_card), toughness: toughness_of(source_card),
+                    charge: counter_count(source_card, "charge"), none: counter_count(source_card, "+1/+1"),
+                    unknown: power_of(99) }]
+            }
+        "#;
+        
+        engine.register_script("reader".to_string(), script).unwrap();
+        
+        let context = ScriptContext {
+            controller: 0,
+            source_card: 3,
+            active_player: None,
+            turn_number: None,
+            phase: None,
+            zones: None,
+            cards: Some(HashMap::from([(3, CardSnapshot {
+                power: 4,
+                toughness: 5,
+                counters: BTreeMap::from([("charge".to_string(), 2)]),
+            })])),
+        };
+        
+        let commands = engine.execute_ability("reader", context).unwrap();
+        let map = commands[0].clone().try_cast::<rhai::Map>().unwrap();
+        assert_eq!(map["power"].as_int().unwrap(), 4);
+        assert_eq!(map["toughness"].as_int().unwrap(), 5);
+        assert_eq!(map["charge"].as_int().unwrap(), 2);
+        assert_eq!(map["none"].as_int().unwrap(), 0);
+        assert_eq!(map["unknown"].as_int().unwrap(), 0);
+    }
+    
     #[test]
     fn test_stat_helpers() {
         let mut engine = RhaiEngine::new();
@@ -711,6 +1170,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("stat_card", context);
@@ -737,6 +1198,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("remove_kw_card", context);
@@ -763,6 +1226,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("resource_card", context);
@@ -789,6 +1254,8 @@ mod tests {
             active_player: None,
             turn_number: None,
             phase: None,
+            zones: None,
+            cards: None,
         };
         
         let result = engine.execute_ability("bolt_card", context);
@@ -1,37 +1,142 @@
 use crate::{
+    ids::{CardId, PlayerId},
     model::event::Event,
-    model::command::Command,
+    model::command::{ChoiceKind, Command, PendingChoice},
     engine::core::GameEngine,
 };
 
+/// Fire the triggers for every event in `events` (plays, zone changes and life changes)
+/// as one simultaneous batch, ordered as by [`order_simultaneous`], then
+/// commit the resulting commands and return their events
+pub fn fire_triggers(engine: &mut GameEngine, events: &[Event]) -> Vec<Event> {
+    let mut commands = Vec::new();
+    for event in events {
+        commands.extend(collect_triggers(engine, event));
+    }
+    let commands = order_simultaneous(engine, commands);
+    crate::engine::events::commit_commands(&mut engine.state, &commands)
+}
+
 /// Evaluate which triggers should fire in response to an event.
-/// Returns commands to execute (typically PushStack for triggered effects).
-/// This uses card definitions to determine which abilities should fire.
+/// Returns commands to execute (typically PushStack for triggered effects),
+/// ordered as by [`order_simultaneous`].
 pub fn evaluate_triggers(
     engine: &mut GameEngine,
     event: &Event,
+) -> Vec<Command> {
+    let commands = collect_triggers(engine, event);
+    order_simultaneous(engine, commands)
+}
+
+/// Order the commands of triggers that fired together ("APNAP")
+///
+/// Each trigger is its `PushStack` plus the `RequestChoice` for its target,
+/// if any. The active player's triggers go on the stack first, then each
+/// other player's in turn order, so the last player's resolve first. Within
+/// a player, triggers go on by source card ID, lowest first, and one card's
+/// triggers in the order they fired, which for a single event is the order
+/// the card declares its abilities. The order only depends on the game's
+/// structure, never on the seed or on where cards sit in their zones, so
+/// replays resolve triggers identically. State-based actions use the same
+/// player order (see `GameEngine::check_game_end`).
+///
+/// When the active player has more than one trigger, they are asked to
+/// reorder them with a `ChoiceKind::OrderTriggers` choice. A batch in which
+/// a trigger waits on a target isn't offered for ordering, since only one
+/// choice can be pending at a time.
+pub fn order_simultaneous(engine: &mut GameEngine, commands: Vec<Command>) -> Vec<Command> {
+    let active = engine.state.turn.active_player;
+    let num_players = engine.state.players.len().max(1) as u8;
+
+    let mut triggers: Vec<(PlayerId, Option<CardId>, Vec<Command>)> = Vec::new();
+    for command in commands {
+        match (&command, triggers.last_mut()) {
+            (Command::PushStack { item }, _) => triggers.push((item.controller, item.source, vec![command])),
+            (_, Some((_, _, trigger))) => trigger.push(command),
+            (_, None) => triggers.push((active, None, vec![command])),
+        }
+    }
+    // Stable, so each card's triggers keep their firing order
+    triggers.sort_by_key(|(controller, source, _)| (
+        (controller.0 + num_players - active.0) % num_players,
+        source.map_or(u32::MAX, |card| card.0),
+    ));
+
+    let active_items: Vec<u32> = triggers.iter()
+        .filter(|(controller, _, _)| *controller == active)
+        .flat_map(|(_, _, commands)| commands.iter().filter_map(|c| match c {
+            Command::PushStack { item } => Some(item.id),
+            _ => None,
+        }))
+        .collect();
+    let targeting = triggers.iter()
+        .any(|(_, _, commands)| commands.iter().any(|c| matches!(c, Command::RequestChoice { .. })));
+
+    let mut ordered: Vec<Command> = triggers.into_iter().flat_map(|(_, _, commands)| commands).collect();
+    if active_items.len() > 1 && !targeting {
+        ordered.push(Command::RequestChoice {
+            player: active,
+            choice: PendingChoice {
+                id: engine.next_choice_id(),
+                prompt: "Choose the order your triggers resolve in".to_string(),
+                kind: ChoiceKind::OrderTriggers { items: active_items },
+                stack_item: None,
+            },
+        });
+    }
+    ordered
+}
+
+/// The commands of every ability `event` triggers, in the order they fired
+fn collect_triggers(
+    engine: &mut GameEngine,
+    event: &Event,
 ) -> Vec<Command> {
     let mut commands = Vec::new();
-    let mut next_stack_id = engine.next_stack_id();
 
     match event {
         // CardMoved events can trigger "enters the battlefield" effects (ETB triggers)
-        Event::CardMoved { card, to, .. } => {
-            // Check if moving TO the field zone indicates "enters"
-            if to.0.starts_with("field") {
-                // Find the controller (zone owner)
-                if let Some(zone) = engine.state.zones.iter().find(|z| z.id == *to) {
-                    if let Some(controller) = zone.owner {
-                        // Look up card's abilities and fire matching triggers
-                        let ability_commands = crate::engine::cards::generate_ability_commands(
-                            *card,
-                            "etb",
-                            controller,
-                            &engine.cards,
-                            &mut next_stack_id,
-                        );
-                        commands.extend(ability_commands);
-                    }
+        // Check if moving TO the field zone indicates "enters"
+        Event::CardMoved { card, to, .. } if to.0.starts_with("field") => {
+            // Find the controller (zone owner)
+            if let Some(zone) = engine.state.zones.iter().find(|z| z.id == *to)
+                && let Some(controller) = zone.owner
+            {
+                // Look up card's abilities and fire matching triggers
+                let ability_commands = crate::engine::cards::generate_ability_commands(
+                    *card,
+                    "etb",
+                    controller,
+                    &engine.cards,
+                    &engine.builtins,
+                    &engine.state,
+                    &mut engine.next_stack_id,
+                    &mut engine.next_choice_id,
+                );
+                commands.extend(ability_commands);
+            }
+        }
+        // Leaving the field fires "on_leave", and "on_death" when it went to a graveyard.
+        // The card is gone by now, so its controller is the owner of the zone it left.
+        Event::CardLeftZone { card, from, to } => {
+            if let Some(zone) = engine.state.zones.iter().find(|z| z.id == *from)
+                && let Some(controller) = zone.owner
+            {
+                let mut triggers = vec!["on_leave"];
+                if to.0.starts_with("graveyard") {
+                    triggers.push("on_death");
+                }
+                for trigger in triggers {
+                    commands.extend(crate::engine::cards::generate_ability_commands(
+                        *card,
+                        trigger,
+                        controller,
+                        &engine.cards,
+                        &engine.builtins,
+                        &engine.state,
+                        &mut engine.next_stack_id,
+                        &mut engine.next_choice_id,
+                    ));
                 }
             }
         }
@@ -43,10 +148,36 @@ pub fn evaluate_triggers(
                 "on_play",
                 *player,
                 &engine.cards,
-                &mut next_stack_id,
+                &engine.builtins,
+                &engine.state,
+                &mut engine.next_stack_id,
+                &mut engine.next_choice_id,
             );
             commands.extend(ability_commands);
         }
+        // A life change fires "on_gain_life" or "on_lose_life", then "on_life_change",
+        // for the cards on the fields of the player whose life changed. Their
+        // effect params can use `{delta}` (negative for a loss) and `{amount}`.
+        Event::LifeChanged { player, delta } if *delta != 0 => {
+            let direction = if *delta > 0 { "on_gain_life" } else { "on_lose_life" };
+            let vars = [("delta", delta.to_string()), ("amount", delta.abs().to_string())];
+            let cards = engine.state.controlled_cards(*player);
+            for card in cards {
+                for trigger in [direction, "on_life_change"] {
+                    commands.extend(crate::engine::cards::generate_ability_commands_with_vars(
+                        card,
+                        trigger,
+                        *player,
+                        &engine.cards,
+                        &engine.builtins,
+                        &engine.state,
+                        &mut engine.next_stack_id,
+                        &mut engine.next_choice_id,
+                        &vars,
+                    ));
+                }
+            }
+        }
         _ => {
             // Other events don't trigger anything yet
         }
@@ -11,37 +11,50 @@ pub mod pack;
 pub mod validation;
 pub mod compile;
 pub mod testing;
+pub mod bot;
 
-pub use engine::core::{GameEngine, StepResult};
-pub use engine::init::initialize_game;
+pub use engine::core::{DeadlockPolicy, GameEngine, GameResult, StepResult, TimeoutPolicy};
+pub use engine::costs::{auto_pay, can_afford, cost_of, pay_cost, pay_life, Cost};
+pub use engine::init::{
+    choose_first_player, draw_opening_hands, grant_starting_resources, initialize_game,
+    initialize_game_with_events, initialize_game_with_options, initialize_game_with_report,
+    shuffle_all_decks, swap_sideboard, FirstPlayerReason, InitOptions, PlayerSetup, SetupReport,
+};
 pub use error::{EngineError, LegalityError};
 pub use model::action::Action;
 pub use model::command::Command;
 pub use model::event::Event;
-pub use rules::schema::Ruleset;
+pub use rules::schema::{Ruleset, RulesetSummary};
+pub use rules::query::{effective_keywords, effective_stats, CardQuery};
 pub use rules::card_loader::CardSource;
+pub use rules::deck::{load_deck, DeckList};
 pub use rules::RulesModule;
 pub use state::gamestate::GameState;
+pub use state::view::{GameStateView, RevealedView, StackEntryView};
 pub use util::rng::GameRng;
 pub use display::{GameDisplay, LogEntry};
+pub use bot::{Bot, DefaultTargetPolicy, TargetPolicy};
+pub use engine::ai::SearchBot;
 
 use std::fs;
 use std::path::Path;
 
 use crate::error::CardinalError;
 use crate::rules::schema::Ruleset as RulesetToml;
-use crate::rules::card_loader::{load_cards_from_sources, validate_unique_card_ids};
+use crate::rules::card_loader::{load_cards_from_sources, merge_card_definitions, parse_with_templates, validate_unique_card_ids};
 
 /// Load a `Ruleset` from a TOML file. Returns a conservative `CardinalError` on failure.
 pub fn load_rules<P: AsRef<Path>>(path: P) -> Result<RulesetToml, CardinalError> {
     let content = fs::read_to_string(path).map_err(|e| CardinalError(format!("Failed to read rules file: {}", e)))?;
-    toml::from_str(&content).map_err(|e| CardinalError(format!("Failed to parse TOML: {}", e)))
+    parse_with_templates(&content)
+        .map_err(|e| CardinalError(format!("Failed to parse TOML: {:#}", e)))
 }
 
 /// Load a complete game configuration with rules and cards from separate sources
 ///
 /// This function loads the rules from a TOML file and then loads cards from the specified sources.
-/// The cards are validated for unique IDs and then added to the ruleset.
+/// The cards are validated for unique IDs and then merged with any cards the rules
+/// file defines inline; a card ID defined differently in both places is an error.
 ///
 /// # Arguments
 /// * `rules_path` - Path to the rules.toml file
@@ -94,8 +107,10 @@ pub fn load_game_config<P: AsRef<Path>>(
     validate_unique_card_ids(&cards)
         .map_err(|e| CardinalError(format!("Card validation failed: {}", e)))?;
     
-    // Add cards to ruleset
-    ruleset.cards = cards;
+    // Reconcile with any cards defined inline in the rules file
+    let inline = std::mem::take(&mut ruleset.cards);
+    ruleset.cards = merge_card_definitions(inline, cards)
+        .map_err(|e| CardinalError(format!("Card validation failed: {}", e)))?;
     
     Ok(ruleset)
 }
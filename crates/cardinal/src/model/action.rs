@@ -16,9 +16,28 @@ pub enum Action {
         choice_id: u32,
         target: TargetRef,
     },
+
+    // Answer a pending trigger ordering: stack item IDs, the first to resolve first
+    OrderTriggers {
+        choice_id: u32,
+        order: Vec<u32>,
+    },
+
+    // Answer a pending card selection with the picked cards (possibly none)
+    ChooseCards {
+        choice_id: u32,
+        cards: Vec<CardId>,
+    },
+
+    // Tap a card to activate one of its mana abilities (by index in its
+    // definition). It resolves immediately, even while a choice is pending.
+    ActivateAbility {
+        card: CardId,
+        ability: usize,
+    },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TargetRef {
     Player(PlayerId),
     Card(CardId),
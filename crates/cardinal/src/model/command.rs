@@ -1,18 +1,27 @@
 use crate::ids::{CardId, PlayerId, ZoneId};
+use crate::model::action::TargetRef;
+use crate::rules::query::CardQuery;
 
 #[derive(Debug, Clone)]
 pub enum Command {
-    MoveCard { card: CardId, from: ZoneId, to: ZoneId },
+    /// `position: None` uses the zone's default: top for decks, appended elsewhere.
+    /// Cards always go on the end of a graveyard, so it stays in arrival order.
+    MoveCard { card: CardId, from: ZoneId, to: ZoneId, position: Option<ZonePosition> },
     ChangeLife { player: PlayerId, delta: i32 },
+    /// Damage to a player (life loss) or card ("damage" stat), after prevention
+    /// shields on the target absorb what they can
+    DealDamage { target: TargetRef, amount: i32, combat: bool },
+    /// Shield `target` from the next `amount` damage (combat damage only if `combat_only`)
+    PreventDamage { target: TargetRef, amount: i32, combat_only: bool },
     SetLife { player: PlayerId, amount: i32 },
     PushStack { item: StackItem },
     RequestChoice { player: PlayerId, choice: PendingChoice },
     ShuffleZone { zone: ZoneId },
-    ModifyStats { card: CardId, power: i32, toughness: i32 },
+    ModifyStats { card: CardId, power: i32, toughness: i32, duration: Duration },
     SetStats { card: CardId, power: i32, toughness: i32 },
     ModifyStat { card: CardId, stat_name: String, delta: i32 },
     SetStat { card: CardId, stat_name: String, value: String },
-    GrantKeyword { card: CardId, keyword: String },
+    GrantKeyword { card: CardId, keyword: String, duration: Duration },
     RemoveKeyword { card: CardId, keyword: String },
     GainResource { player: PlayerId, resource: String, amount: i32 },
     SpendResource { player: PlayerId, resource: String, amount: i32 },
@@ -20,8 +29,66 @@ pub enum Command {
     CreateToken { player: PlayerId, token_type: String, zone: ZoneId },
     AddCounter { card: CardId, counter_type: String, amount: i32 },
     RemoveCounter { card: CardId, counter_type: String, amount: i32 },
+    SetTapped { card: CardId, tapped: bool },
+    /// Counter a stack item: it leaves the stack without resolving
+    RemoveStackItem { id: u32 },
+    /// Discard `count` cards picked at random (with the game RNG) from the player's hand
+    DiscardRandom { player: PlayerId, count: usize },
+    /// Move up to `count` cards from the top of the player's deck to their hand
+    DrawCards { player: PlayerId, count: u32 },
+    /// Flip a coin with the game RNG; the result is kept as `GameState::last_coin_flip`
+    FlipCoin { player: PlayerId },
+    /// Show every card in the player's hand to the other players until the turn ends
+    RevealHand { player: PlayerId },
+    /// Show every card in the player's hand to `viewer` alone until the turn ends
+    RevealHandTo { player: PlayerId, viewer: PlayerId },
+    /// Show these cards to every player; unlike `RevealHand` nothing stays revealed
+    RevealCards { player: PlayerId, cards: Vec<CardId> },
+    /// The player casts a card that is already where it resolves to (e.g. a
+    /// card put onto the field by an effect), without paying its cost
+    CastCard { player: PlayerId, card: CardId },
+    /// `player` controls the card, which stays in its owner's field zone,
+    /// until it leaves the field
+    GainControl { card: CardId, player: PlayerId },
+    /// Hand control of the card back to its owner (see `GainControl`)
+    ReleaseControl { card: CardId },
+    /// Apply `command` as the turn ends, e.g. to undo an "until end of turn"
+    /// effect
+    AtEndOfTurn { command: Box<Command> },
+    /// Turn the card into another definition, keeping its counters, tap state
+    /// and other changes made to the instance
+    Transform { card: CardId, into: CardId },
+    /// Queue an extra turn for the player after the current one (and any
+    /// extra turns already queued)
+    AddExtraTurn { player: PlayerId },
+    /// The player skips the step or phase with this ID the next time they
+    /// would begin it on their turn
+    SkipNext { player: PlayerId, id: String },
 }
 
+/// Where a moved card lands in its destination zone.
+/// Zones are ordered Vecs whose front (index 0) is the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZonePosition {
+    Top,
+    Bottom,
+    /// Index from the top, clamped to the zone size
+    Index(usize),
+}
+
+/// How long a stat or keyword change lasts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Duration {
+    #[default]
+    Permanent,
+    /// Kept apart on the card instance and cleared as the turn ends
+    UntilEndOfTurn,
+}
+
+/// Stack items and choices created by effects carry this ID until the engine
+/// assigns a real one as it commits them
+pub const PLACEHOLDER_ID: u32 = 0;
+
 #[derive(Debug, Clone)]
 pub struct StackItem {
     pub id: u32,
@@ -34,6 +101,9 @@ pub struct StackItem {
 pub enum EffectRef {
     Builtin(&'static str),
     Scripted(String), // mod-defined
+    Sequence(Vec<EffectRef>), // resolved in order as one stack item
+    /// Runs the effect only if the condition holds when it resolves
+    Conditional(String, Box<EffectRef>),
 }
 
 #[derive(Debug, Clone)]
@@ -41,16 +111,29 @@ pub struct PendingChoice {
     pub id: u32,
     pub prompt: String,
     pub kind: ChoiceKind,
+    /// Stack item waiting on this choice; its effect is bound to the chosen target
+    pub stack_item: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
 pub enum ChoiceKind {
     ChooseTarget { allowed: AllowedTargets },
+    /// Put simultaneous triggers in order: answered with `Action::OrderTriggers`
+    /// listing exactly these stack item IDs, the first to resolve first
+    OrderTriggers { items: Vec<u32> },
+    /// Pick up to `max` cards in `zone` matching `filter`: answered with
+    /// `Action::ChooseCards`
+    ChooseCards { zone: ZoneId, filter: CardQuery, max: usize },
 }
 
 #[derive(Debug, Clone)]
 pub enum AllowedTargets {
     AnyCreatureOnField,
     AnyPlayer,
+    /// Any card currently in the given zone
+    CardInZone(ZoneId),
+    /// A creature on the chooser's own field other than `except`, e.g. one
+    /// to sacrifice
+    OwnCreatureOnField { except: Option<CardId> },
     // etc
 }
@@ -1,11 +1,23 @@
 use crate::ids::{CardId, PlayerId, ZoneId, PhaseId, StepId};
+use crate::model::action::TargetRef;
 
 #[derive(Debug, Clone)]
 pub enum Event {
+    GameStarted,
+    DeckShuffled { player: PlayerId },
+    CardDrawn { player: PlayerId, card: CardId },
+    /// `player` was to draw `requested` cards but their deck only had `drawn`
+    DeckRanOut { player: PlayerId, requested: u32, drawn: u32 },
     PhaseAdvanced { phase: PhaseId, step: StepId },
     PriorityPassed { by: PlayerId },
     CardMoved { card: CardId, from: ZoneId, to: ZoneId },
+    /// A card left a field zone; follows the matching `CardMoved`
+    CardLeftZone { card: CardId, from: ZoneId, to: ZoneId },
     CardPlayed { player: PlayerId, card: CardId },
+    /// `player` conceded; whether that ends the game is decided like any other loss
+    PlayerConceded { player: PlayerId },
+    /// `player` tapped `card` for its mana ability at index `ability`, which resolved at once
+    ManaAbilityActivated { player: PlayerId, card: CardId, ability: usize },
     LifeChanged { player: PlayerId, delta: i32 },
     LifeSet { player: PlayerId, amount: i32 },
     StackPushed { item_id: u32 },
@@ -25,4 +37,37 @@ pub enum Event {
     TokenCreated { player: PlayerId, token_type: String, card: CardId, zone: ZoneId },
     CounterAdded { card: CardId, counter_type: String, amount: i32 },
     CounterRemoved { card: CardId, counter_type: String, amount: i32 },
+    CardTapped { card: CardId },
+    CardUntapped { card: CardId },
+    SpellCountered { item_id: u32 },
+    /// The engine gave up on a choice nobody could answer (see `DeadlockPolicy`)
+    ChoiceSkipped { choice_id: u32 },
+    DamageShieldAdded { target: TargetRef, amount: i32 },
+    /// A shield absorbed `amount` damage that would have been dealt to `target`
+    DamagePrevented { target: TargetRef, amount: i32 },
+    /// `player` flipped a coin using the game RNG
+    CoinFlipped { player: PlayerId, result: CoinFlip },
+    /// `player` showed these cards to the other players
+    CardsRevealed { player: PlayerId, cards: Vec<CardId> },
+    /// `player` showed these cards to `viewer` only
+    CardsRevealedTo { player: PlayerId, viewer: PlayerId, cards: Vec<CardId> },
+    /// `player` gained control of `card` from `from`
+    ControlChanged { card: CardId, from: Option<PlayerId>, player: PlayerId },
+    /// A cleanup was queued for the end of the turn (see `Command::AtEndOfTurn`)
+    CleanupScheduled,
+    /// `card` now uses the definition `into` instead of `from`
+    CardTransformed { card: CardId, from: CardId, into: CardId },
+    /// `player` will take an extra turn
+    ExtraTurnAdded { player: PlayerId },
+    /// `player` will skip their next step or phase `id`
+    SkipAdded { player: PlayerId, id: String },
+    /// The active player skipped step or phase `id` of this turn
+    Skipped { player: PlayerId, id: String },
+}
+
+/// Outcome of a coin flip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoinFlip {
+    Heads,
+    Tails,
 }
@@ -5,25 +5,103 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use super::lock::{PackLock, LOCK_FILE};
 use super::metadata::{FileEntry, Manifest, PackMeta};
 
+/// Options controlling which files end up in a pack
+///
+/// Patterns are matched against forward-slash paths relative to the pack root.
+/// A pattern without a `/` matches any single path component (e.g. `*.md`
+/// or `draft`); a pattern with a `/` matches the whole path (e.g.
+/// `docs/**/*.md`). Supported wildcards are `*` (within a component),
+/// `**` (across components) and `?` (a single character).
+///
+/// Exclude patterns are layered on top of the built-in exclusions (hidden
+/// files, build directories, editor backups). Include patterns take priority
+/// over both, so they can re-add a file that would otherwise be excluded.
+#[derive(Debug, Clone, Default)]
+pub struct PackBuildOptions {
+    /// Additional glob patterns for files to leave out of the pack
+    pub exclude: Vec<String>,
+    /// Glob patterns for files to keep even if they would be excluded
+    pub include: Vec<String>,
+}
+
+impl PackBuildOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an exclude pattern
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Add an include pattern
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Decide whether a relative (forward-slash) path belongs in the pack
+    fn keeps(&self, relative_path: &str) -> bool {
+        if self.include.iter().any(|p| matches_pattern(p, relative_path)) {
+            return true;
+        }
+
+        let default_excluded = relative_path.split('/').any(is_excluded_name);
+        let user_excluded = self.exclude.iter().any(|p| matches_pattern(p, relative_path));
+
+        !default_excluded && !user_excluded
+    }
+
+    /// Decide whether any include pattern could match a path under a directory
+    fn reaches_into(&self, relative_dir: &str) -> bool {
+        self.include.iter().any(|p| pattern_reaches_into(p, relative_dir))
+    }
+}
+
+/// Build a .ccpack file from a directory using the default file selection
+///
+/// See [`build_pack_with_options`] for control over included files.
+pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q) -> Result<()> {
+    build_pack_with_options(input_dir, output_file, &PackBuildOptions::default())
+}
+
 /// Build a .ccpack file from a directory
 ///
 /// # Arguments
 /// * `input_dir` - Path to the directory containing pack.toml, cards/, scripts/, etc.
 /// * `output_file` - Path where the .ccpack file will be written
+/// * `options` - Include/exclude patterns applied on top of the defaults
 ///
 /// # Returns
 /// Result indicating success or detailed error
 ///
 /// # Process
 /// 1. Validate that pack.toml exists and parse it
-/// 2. Walk directory and collect all valid files (sorted)
+/// 2. Walk directory, collect all valid files and sort them by normalized path
 /// 3. Compute SHA-256 for each file
-/// 4. Generate manifest.toml
-/// 5. Create tar archive with all files + manifest
+/// 4. Generate manifest.toml, and a `ccpack.lock` pinning the dependency
+///    packs (see [`PackLock`]) if the pack has any
+/// 5. Create tar archive with all files + manifest (+ lock)
 /// 6. Compress with zstd
-pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q) -> Result<()> {
+///
+/// Dependencies are looked up as `<pack_id>.ccpack` next to `output_file`;
+/// the build fails if one is missing.
+///
+/// # Determinism
+/// Two builds of the same directory produce byte-identical packs, regardless
+/// of platform or file timestamps. Paths are normalized to forward slashes
+/// *before* sorting, so manifest and tar entry order is the byte order of the
+/// normalized paths. Tar headers carry fixed mode/mtime/owner values rather
+/// than the host file's metadata.
+pub fn build_pack_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
+    input_dir: P,
+    output_file: Q,
+    options: &PackBuildOptions,
+) -> Result<()> {
     let input_dir = input_dir.as_ref();
     let output_file = output_file.as_ref();
 
@@ -40,14 +118,14 @@ pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q)
         .with_context(|| format!("Failed to parse pack.toml at {}", pack_toml_path.display()))?;
 
     // Step 2: Collect all files, excluding unwanted ones
-    let mut file_paths = collect_files(input_dir)?;
+    let file_paths = collect_files(input_dir, options)?;
 
-    // Sort for deterministic builds
-    file_paths.sort();
+    // Sort on the normalized form for deterministic builds across platforms
+    let file_paths = order_pack_paths(file_paths);
 
     // Step 3: Generate file entries with hashes
     let mut file_entries = Vec::new();
-    for file_path in &file_paths {
+    for (normalized_path, file_path) in &file_paths {
         let full_path = input_dir.join(file_path);
         let metadata = std::fs::metadata(&full_path)
             .with_context(|| format!("Failed to read metadata for {}", full_path.display()))?;
@@ -56,11 +134,8 @@ pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q)
         let hash = compute_sha256(&full_path)
             .with_context(|| format!("Failed to compute hash for {}", full_path.display()))?;
 
-        // Normalize path to use forward slashes
-        let normalized_path = file_path.to_string_lossy().replace('\\', "/");
-
         file_entries.push(FileEntry {
-            path: normalized_path,
+            path: normalized_path.clone(),
             size,
             sha256: hash,
         });
@@ -74,9 +149,18 @@ pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q)
 
     let manifest_toml = toml::to_string_pretty(&manifest)
         .context("Failed to serialize manifest to TOML")?;
+    let mut generated = vec![("manifest.toml", manifest_toml)];
+
+    if !pack_meta.dependencies.is_empty() {
+        let pack_dir = output_file.parent().unwrap_or(Path::new("."));
+        let lock = PackLock::resolve(pack_dir, &pack_meta.dependencies)?;
+        let lock_toml = toml::to_string_pretty(&lock)
+            .context("Failed to serialize lock to TOML")?;
+        generated.push((LOCK_FILE, lock_toml));
+    }
 
     // Step 5: Create tar archive
-    let tar_data = create_tar_archive(input_dir, &file_paths, &manifest_toml)
+    let tar_data = create_tar_archive(input_dir, &file_paths, &generated)
         .context("Failed to create tar archive")?;
 
     // Step 6: Compress with zstd
@@ -96,13 +180,23 @@ pub fn build_pack<P: AsRef<Path>, Q: AsRef<Path>>(input_dir: P, output_file: Q)
 }
 
 /// Collect all files from the input directory, excluding unwanted files
-fn collect_files(input_dir: &Path) -> Result<Vec<PathBuf>> {
+pub(crate) fn collect_files(input_dir: &Path, options: &PackBuildOptions) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
-    for entry in WalkDir::new(input_dir)
-        .into_iter()
-        .filter_entry(|e| !is_excluded(e))
-    {
+    // Skip default-excluded directories unless an include pattern could
+    // match something beneath them; files are left to `keeps` below.
+    let descend = |entry: &walkdir::DirEntry| {
+        if entry.depth() == 0 || !entry.file_type().is_dir() || !is_excluded(entry) {
+            return true;
+        }
+        entry
+            .path()
+            .strip_prefix(input_dir)
+            .map(|dir| options.reaches_into(&normalize_pack_path(dir)))
+            .unwrap_or(false)
+    };
+
+    for entry in WalkDir::new(input_dir).into_iter().filter_entry(descend) {
         let entry = entry.context("Failed to read directory entry")?;
 
         // Skip directories
@@ -117,16 +211,41 @@ fn collect_files(input_dir: &Path) -> Result<Vec<PathBuf>> {
             .context("Failed to compute relative path")?
             .to_path_buf();
 
+        if !options.keeps(&normalize_pack_path(&relative_path)) {
+            continue;
+        }
+
         files.push(relative_path);
     }
 
     Ok(files)
 }
 
+/// Normalize a relative path to the forward-slash form stored in packs
+fn normalize_pack_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Pair each path with its normalized form, sorted by the normalized form
+///
+/// Sorting `PathBuf`s directly compares components using the host's separator
+/// rules, which can order entries differently on Windows and POSIX.
+fn order_pack_paths(paths: Vec<PathBuf>) -> Vec<(String, PathBuf)> {
+    let mut ordered: Vec<(String, PathBuf)> = paths
+        .into_iter()
+        .map(|path| (normalize_pack_path(&path), path))
+        .collect();
+    ordered.sort_by(|a, b| a.0.cmp(&b.0));
+    ordered
+}
+
 /// Check if a directory entry should be excluded
 fn is_excluded(entry: &walkdir::DirEntry) -> bool {
-    let name = entry.file_name().to_string_lossy();
+    is_excluded_name(&entry.file_name().to_string_lossy())
+}
 
+/// Check if a single path component is excluded by default
+fn is_excluded_name(name: &str) -> bool {
     // Exclude hidden files and directories
     if name.starts_with('.') {
         return true;
@@ -143,7 +262,7 @@ fn is_excluded(entry: &walkdir::DirEntry) -> bool {
         "Thumbs.db",
     ];
 
-    if excluded_names.contains(&name.as_ref()) {
+    if excluded_names.contains(&name) {
         return true;
     }
 
@@ -155,6 +274,74 @@ fn is_excluded(entry: &walkdir::DirEntry) -> bool {
     false
 }
 
+/// Match a pack glob pattern against a forward-slash relative path
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.contains('/') {
+        let pattern: Vec<&str> = pattern.split('/').collect();
+        let path: Vec<&str> = path.split('/').collect();
+        // Matching any leading run of components lets `draft/old` cover `draft/old/card.toml`
+        (1..=path.len()).any(|n| glob_segments(&pattern, &path[..n]))
+    } else {
+        path.split('/').any(|component| glob_component(pattern.as_bytes(), component.as_bytes()))
+    }
+}
+
+/// Check whether a pack glob pattern could match some path under a directory
+///
+/// A pattern without a `/` only reaches into the directory when it matches one
+/// of the directory's own components; otherwise it would force a walk of every
+/// excluded directory just in case a file name matched.
+fn pattern_reaches_into(pattern: &str, dir: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.contains('/') {
+        let pattern: Vec<&str> = pattern.split('/').collect();
+        let dir: Vec<&str> = dir.split('/').collect();
+        glob_prefix(&pattern, &dir)
+    } else {
+        dir.split('/').any(|component| glob_component(pattern.as_bytes(), component.as_bytes()))
+    }
+}
+
+/// Match pattern segments against the leading directory segments of a path,
+/// succeeding if the rest of the pattern could still match deeper segments
+fn glob_prefix(pattern: &[&str], dir: &[&str]) -> bool {
+    match (pattern.split_first(), dir.split_first()) {
+        (None, _) | (_, None) => true,
+        (Some((&"**", rest)), _) => (0..=dir.len()).any(|skip| glob_prefix(rest, &dir[skip..])),
+        (Some((first, rest)), Some((segment, dir_rest))) => {
+            glob_component(first.as_bytes(), segment.as_bytes()) && glob_prefix(rest, dir_rest)
+        }
+    }
+}
+
+/// Match pattern segments against path segments, where `**` spans any number of segments
+fn glob_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_segments(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                glob_component(first.as_bytes(), segment.as_bytes()) && glob_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match a single path component against `*` and `?` wildcards
+fn glob_component(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.split_first(), text.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            glob_component(rest, text) || (!text.is_empty() && glob_component(pattern, &text[1..]))
+        }
+        (Some((b'?', rest)), Some((_, text_rest))) => glob_component(rest, text_rest),
+        (Some((p, rest)), Some((t, text_rest))) if p == t => glob_component(rest, text_rest),
+        _ => false,
+    }
+}
+
 /// Compute SHA-256 hash of a file
 fn compute_sha256(path: &Path) -> Result<String> {
     let mut file = File::open(path)?;
@@ -172,42 +359,233 @@ fn compute_sha256(path: &Path) -> Result<String> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-/// Create a tar archive containing all files plus the generated manifest
+/// Create a tar archive containing all files plus the generated ones
+/// (manifest and lock), given as (name, content)
 fn create_tar_archive(
     input_dir: &Path,
-    file_paths: &[PathBuf],
-    manifest_toml: &str,
+    file_paths: &[(String, PathBuf)],
+    generated: &[(&str, String)],
 ) -> Result<Vec<u8>> {
     let mut tar_data = Vec::new();
     {
         let mut tar = tar::Builder::new(&mut tar_data);
 
         // Add all collected files
-        for file_path in file_paths {
+        for (normalized_path, file_path) in file_paths {
             let full_path = input_dir.join(file_path);
-            let mut file = File::open(&full_path)
+            let content = std::fs::read(&full_path)
                 .with_context(|| format!("Failed to open file {}", full_path.display()))?;
 
-            // Normalize path to use forward slashes
-            let normalized_path = file_path.to_string_lossy().replace('\\', "/");
-
-            tar.append_file(&normalized_path, &mut file)
+            let header = deterministic_header(normalized_path, content.len() as u64)?;
+            tar.append(&header, &content[..])
                 .with_context(|| format!("Failed to add {} to archive", normalized_path))?;
         }
 
-        // Add manifest.toml
-        let manifest_bytes = manifest_toml.as_bytes();
-        let mut header = tar::Header::new_gnu();
-        header.set_path("manifest.toml")?;
-        header.set_size(manifest_bytes.len() as u64);
-        header.set_mode(0o644);
-        header.set_cksum();
-
-        tar.append(&header, manifest_bytes)
-            .context("Failed to add manifest.toml to archive")?;
+        // Add manifest.toml and the lock
+        for (name, content) in generated {
+            let header = deterministic_header(name, content.len() as u64)?;
+            tar.append(&header, content.as_bytes())
+                .with_context(|| format!("Failed to add {} to archive", name))?;
+        }
 
         tar.finish().context("Failed to finalize tar archive")?;
     }
 
     Ok(tar_data)
 }
+
+/// Build a tar header that doesn't depend on host file metadata
+fn deterministic_header(path: &str, size: u64) -> Result<tar::Header> {
+    let mut header = tar::Header::new_gnu();
+    header.set_path(path)?;
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_cksum();
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::loader::load_pack;
+    use std::fs;
+
+    fn write_test_pack(dir: &Path) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir.join("cards")).unwrap();
+        fs::create_dir_all(dir.join("draft")).unwrap();
+
+        fs::write(dir.join("pack.toml"), "pack_id = \"glob-pack\"\nversion = \"1.0.0\"\n").unwrap();
+        fs::write(dir.join("cards/goblin.toml"), "name = \"Goblin\"\n").unwrap();
+        fs::write(dir.join("draft/wip.toml"), "name = \"WIP\"\n").unwrap();
+        fs::write(dir.join("README.md"), "# Pack\n").unwrap();
+        fs::write(dir.join("cards/goblin.toml.bak"), "name = \"Old Goblin\"\n").unwrap();
+    }
+
+    fn manifest_paths(pack_path: &Path) -> Vec<String> {
+        let (manifest, _files) = load_pack(pack_path).unwrap();
+        manifest.files.into_iter().map(|f| f.path).collect()
+    }
+
+    #[test]
+    fn test_exclude_glob_drops_files() {
+        let temp_dir = std::env::temp_dir().join("test_pack_exclude_glob");
+        write_test_pack(&temp_dir);
+        let pack_path = std::env::temp_dir().join("test_pack_exclude_glob.ccpack");
+
+        let options = PackBuildOptions::new().exclude("draft/**").exclude("*.md");
+        build_pack_with_options(&temp_dir, &pack_path, &options).unwrap();
+
+        let paths = manifest_paths(&pack_path);
+        assert!(paths.contains(&"cards/goblin.toml".to_string()));
+        assert!(!paths.contains(&"draft/wip.toml".to_string()));
+        assert!(!paths.contains(&"README.md".to_string()));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::remove_file(&pack_path);
+    }
+
+    #[test]
+    fn test_include_glob_readds_excluded_file() {
+        let temp_dir = std::env::temp_dir().join("test_pack_include_glob");
+        write_test_pack(&temp_dir);
+        let pack_path = std::env::temp_dir().join("test_pack_include_glob.ccpack");
+
+        // Backups are excluded by default
+        build_pack(&temp_dir, &pack_path).unwrap();
+        assert!(!manifest_paths(&pack_path).contains(&"cards/goblin.toml.bak".to_string()));
+
+        let options = PackBuildOptions::new().include("cards/*.bak");
+        build_pack_with_options(&temp_dir, &pack_path, &options).unwrap();
+
+        let paths = manifest_paths(&pack_path);
+        assert!(paths.contains(&"cards/goblin.toml.bak".to_string()));
+        assert!(paths.contains(&"draft/wip.toml".to_string()));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::remove_file(&pack_path);
+    }
+
+    #[test]
+    fn test_include_glob_only_walks_excluded_dirs_it_can_reach() {
+        let temp_dir = std::env::temp_dir().join("test_pack_include_reach");
+        write_test_pack(&temp_dir);
+        fs::create_dir_all(temp_dir.join(".github")).unwrap();
+        fs::write(temp_dir.join(".github/notes.md"), "notes\n").unwrap();
+        fs::create_dir_all(temp_dir.join("target")).unwrap();
+        fs::write(temp_dir.join("target/keep.toml"), "name = \"Kept\"\n").unwrap();
+        fs::write(temp_dir.join("target/other.toml"), "name = \"Other\"\n").unwrap();
+
+        let options = PackBuildOptions::new().include("target/keep.toml");
+        let files: Vec<String> = collect_files(&temp_dir, &options)
+            .unwrap()
+            .iter()
+            .map(|p| normalize_pack_path(p))
+            .collect();
+
+        assert!(files.contains(&"target/keep.toml".to_string()));
+        assert!(!files.contains(&"target/other.toml".to_string()));
+        assert!(!files.contains(&".github/notes.md".to_string()));
+        assert!(!files.contains(&"cards/goblin.toml.bak".to_string()));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_pattern_reaches_into() {
+        assert!(pattern_reaches_into("target/keep.toml", "target"));
+        assert!(pattern_reaches_into("**/*.md", ".github"));
+        assert!(pattern_reaches_into("docs/**", "docs/.hidden"));
+        assert!(pattern_reaches_into(".github", ".github"));
+        assert!(!pattern_reaches_into("target/keep.toml", ".github"));
+        assert!(!pattern_reaches_into("cards/*.bak", "node_modules"));
+        assert!(!pattern_reaches_into("*.bak", "node_modules"));
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(matches_pattern("*.md", "docs/README.md"));
+        assert!(matches_pattern("draft", "draft/wip.toml"));
+        assert!(matches_pattern("draft/", "draft/wip.toml"));
+        assert!(matches_pattern("docs/**/*.md", "docs/a/b/c.md"));
+        assert!(matches_pattern("docs/**/*.md", "docs/c.md"));
+        assert!(matches_pattern("card?.toml", "cards/card1.toml"));
+        assert!(!matches_pattern("cards/*.toml", "cards/sub/x.toml.bak"));
+        assert!(!matches_pattern("*.md", "cards/goblin.toml"));
+    }
+
+    #[test]
+    fn test_windows_paths_sort_like_posix() {
+        let posix = vec![
+            PathBuf::from("cards/goblin.toml"),
+            PathBuf::from("cards-extra/ogre.toml"),
+            PathBuf::from("cards/a/knight.toml"),
+            PathBuf::from("pack.toml"),
+            PathBuf::from("scripts/bolt.rhai"),
+        ];
+        // Same files as a Windows walk would report them, in a different order
+        let windows = vec![
+            PathBuf::from("scripts\\bolt.rhai"),
+            PathBuf::from("pack.toml"),
+            PathBuf::from("cards\\a\\knight.toml"),
+            PathBuf::from("cards-extra\\ogre.toml"),
+            PathBuf::from("cards\\goblin.toml"),
+        ];
+
+        let posix_order: Vec<String> = order_pack_paths(posix).into_iter().map(|(p, _)| p).collect();
+        let windows_order: Vec<String> = order_pack_paths(windows).into_iter().map(|(p, _)| p).collect();
+
+        assert_eq!(posix_order, windows_order);
+        assert_eq!(
+            posix_order,
+            vec![
+                "cards-extra/ogre.toml",
+                "cards/a/knight.toml",
+                "cards/goblin.toml",
+                "pack.toml",
+                "scripts/bolt.rhai",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builds_are_byte_identical() {
+        let temp_dir = std::env::temp_dir().join("test_pack_byte_identical");
+        write_test_pack(&temp_dir);
+        let first = std::env::temp_dir().join("test_pack_byte_identical_1.ccpack");
+        let second = std::env::temp_dir().join("test_pack_byte_identical_2.ccpack");
+
+        build_pack(&temp_dir, &first).unwrap();
+
+        // Touching a file must not change the output
+        let file = File::options().write(true).open(temp_dir.join("cards/goblin.toml")).unwrap();
+        file.set_modified(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(86_400))
+            .unwrap();
+        drop(file);
+
+        build_pack(&temp_dir, &second).unwrap();
+
+        assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+
+        // Manifest order matches tar entry order
+        let (manifest, _files) = load_pack(&first).unwrap();
+        let tar_data = zstd::decode_all(&fs::read(&first).unwrap()[..]).unwrap();
+        let mut archive = tar::Archive::new(&tar_data[..]);
+        let tar_order: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string())
+            .filter(|p| p != "manifest.toml")
+            .collect();
+        let manifest_order: Vec<String> = manifest.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(tar_order, manifest_order);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        let _ = fs::remove_file(&first);
+        let _ = fs::remove_file(&second);
+    }
+}
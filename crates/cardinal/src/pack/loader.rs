@@ -1,26 +1,92 @@
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Component, Path};
 
 use super::metadata::Manifest;
 
+/// Largest decompressed pack [`load_pack`] accepts: 256 MiB
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Limits applied while loading a pack, for packs from untrusted sources
+#[derive(Debug, Clone)]
+pub struct PackLoadOptions {
+    /// Loading fails once the decompressed archive grows past this many bytes
+    pub max_decompressed_size: u64,
+    /// Check the pack's `ccpack.lock` against the dependency packs next to
+    /// it, failing if one has changed since the pack was built
+    pub check_lock: bool,
+}
+
+impl Default for PackLoadOptions {
+    fn default() -> Self {
+        Self { max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE, check_lock: false }
+    }
+}
+
+impl PackLoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the decompressed size limit in bytes
+    pub fn max_decompressed_size(mut self, bytes: u64) -> Self {
+        self.max_decompressed_size = bytes;
+        self
+    }
+
+    /// Check the pack's lock against its dependency packs
+    pub fn check_lock(mut self, check: bool) -> Self {
+        self.check_lock = check;
+        self
+    }
+}
+
+/// Load a .ccpack file into memory and return the manifest and file contents
+///
+/// Uses the default [`PackLoadOptions`]; see [`load_pack_with_options`].
+pub fn load_pack<P: AsRef<Path>>(ccpack_path: P) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
+    load_pack_with_options(ccpack_path, &PackLoadOptions::default())
+}
+
 /// Load a .ccpack file into memory and return the manifest and file contents
 ///
 /// # Arguments
 /// * `ccpack_path` - Path to the .ccpack file
+/// * `options` - Limits checked while reading
 ///
 /// # Returns
 /// A tuple of (Manifest, HashMap<path, content_bytes>)
-pub fn load_pack<P: AsRef<Path>>(ccpack_path: P) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
+///
+/// Decompression is streamed and stops with an error as soon as the output
+/// exceeds `options.max_decompressed_size`, so a small pack that inflates to
+/// gigabytes is rejected without being fully expanded.
+pub fn load_pack_with_options<P: AsRef<Path>>(
+    ccpack_path: P,
+    options: &PackLoadOptions,
+) -> Result<(Manifest, HashMap<String, Vec<u8>>)> {
     let ccpack_path = ccpack_path.as_ref();
 
     // Read and decompress the pack file
-    let compressed_data = std::fs::read(ccpack_path)
+    let file = std::fs::File::open(ccpack_path)
         .with_context(|| format!("Failed to read pack file {}", ccpack_path.display()))?;
+    let decoder = zstd::stream::read::Decoder::new(file)
+        .context("Failed to decompress pack file with zstd")?;
 
-    let tar_data = zstd::decode_all(&compressed_data[..])
+    // Read one byte past the limit to tell "exactly at" from "over"
+    let limit = options.max_decompressed_size;
+    let mut tar_data = Vec::new();
+    decoder
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut tar_data)
         .context("Failed to decompress pack file with zstd")?;
+    if tar_data.len() as u64 > limit {
+        anyhow::bail!(
+            "Pack file {} decompresses to more than the {} byte limit",
+            ccpack_path.display(),
+            limit
+        );
+    }
 
     // Extract tar archive
     let mut archive = tar::Archive::new(&tar_data[..]);
@@ -29,11 +95,9 @@ pub fn load_pack<P: AsRef<Path>>(ccpack_path: P) -> Result<(Manifest, HashMap<St
 
     for entry in archive.entries().context("Failed to read tar entries")? {
         let mut entry = entry.context("Failed to read tar entry")?;
-        let path = entry
-            .path()
-            .context("Failed to get entry path")?
-            .to_string_lossy()
-            .to_string();
+        let entry_path = entry.path().context("Failed to get entry path")?;
+        check_entry_path(&entry_path)?;
+        let path = entry_path.to_string_lossy().to_string();
 
         let mut content = Vec::new();
         entry
@@ -57,6 +121,10 @@ pub fn load_pack<P: AsRef<Path>>(ccpack_path: P) -> Result<(Manifest, HashMap<St
     let manifest: Manifest = toml::from_str(&manifest_str)
         .context("Failed to parse manifest.toml")?;
 
+    if options.check_lock {
+        super::lock::check_pack_lock(ccpack_path, &files)?;
+    }
+
     Ok((manifest, files))
 }
 
@@ -123,6 +191,7 @@ pub fn unpack_pack<P: AsRef<Path>, Q: AsRef<Path>>(ccpack_path: P, output_dir: Q
 
     // Extract all files
     for (path, content) in &files {
+        check_entry_path(Path::new(path))?;
         let output_path = output_dir.join(path);
 
         // Create parent directories if needed
@@ -142,6 +211,25 @@ pub fn unpack_pack<P: AsRef<Path>, Q: AsRef<Path>>(ccpack_path: P, output_dir: Q
     Ok(())
 }
 
+/// Reject archive paths that would land outside the directory a pack is
+/// read or extracted into: absolute paths and any `..` component
+fn check_entry_path(path: &Path) -> Result<()> {
+    for component in path.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            Component::ParentDir => anyhow::bail!(
+                "Unsafe path '{}' in pack: '..' components are not allowed",
+                path.display()
+            ),
+            Component::RootDir | Component::Prefix(_) => anyhow::bail!(
+                "Unsafe path '{}' in pack: absolute paths are not allowed",
+                path.display()
+            ),
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +280,100 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    /// Write a pack whose tar entries use the given raw names, bypassing the
+    /// checks `tar::Builder` makes so hostile paths can be produced
+    fn write_raw_pack(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, *content).unwrap();
+        }
+        let tar_data = builder.into_inner().unwrap();
+        fs::write(path, zstd::encode_all(&tar_data[..], 0).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_pack_with_unsafe_paths_is_rejected() {
+        let temp_dir = std::env::temp_dir().join("test_pack_unsafe_paths");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let manifest = "[pack]\npack_id = \"evil\"\nversion = \"1.0.0\"\n";
+
+        for (name, reason) in [("../escape.txt", "'..'"), ("cards/../../escape.txt", "'..'"), ("/tmp/escape.txt", "absolute")] {
+            let pack_path = temp_dir.join("evil.ccpack");
+            write_raw_pack(&pack_path, &[("manifest.toml", manifest.as_bytes()), (name, b"pwned")]);
+
+            let err = load_pack(&pack_path).unwrap_err();
+            assert!(err.to_string().contains(name) && err.to_string().contains(reason), "{}: {}", name, err);
+
+            let err = unpack_pack(&pack_path, temp_dir.join("out")).unwrap_err();
+            assert!(format!("{:#}", err).contains("Unsafe path"), "{}: {:#}", name, err);
+        }
+        assert!(!temp_dir.join("escape.txt").exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_unpack_pack_extracts_files() {
+        let temp_dir = std::env::temp_dir().join("test_pack_unpack");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("pack/cards")).unwrap();
+        fs::write(temp_dir.join("pack/pack.toml"), "pack_id = \"safe\"\nversion = \"1.0.0\"\n").unwrap();
+        fs::write(temp_dir.join("pack/cards/card.toml"), "name = \"Card\"\n").unwrap();
+
+        let pack_path = temp_dir.join("safe.ccpack");
+        build_pack(temp_dir.join("pack"), &pack_path).unwrap();
+        unpack_pack(&pack_path, temp_dir.join("out")).unwrap();
+
+        assert_eq!(fs::read_to_string(temp_dir.join("out/cards/card.toml")).unwrap(), "name = \"Card\"\n");
+        assert!(temp_dir.join("out/manifest.toml").exists());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    fn build_padded_pack(name: &str, padding: usize) -> (std::path::PathBuf, std::path::PathBuf) {
+        let temp_dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("pack")).unwrap();
+        fs::write(temp_dir.join("pack/pack.toml"), "pack_id = \"padded\"\nversion = \"1.0.0\"\n").unwrap();
+        // Zeros compress to almost nothing, like a decompression bomb
+        fs::write(temp_dir.join("pack/padding.bin"), vec![0u8; padding]).unwrap();
+
+        let pack_path = temp_dir.join("padded.ccpack");
+        build_pack(temp_dir.join("pack"), &pack_path).unwrap();
+        (temp_dir, pack_path)
+    }
+
+    #[test]
+    fn test_pack_under_size_limit_loads() {
+        let (temp_dir, pack_path) = build_padded_pack("test_pack_under_limit", 64 * 1024);
+
+        let options = PackLoadOptions::new().max_decompressed_size(1024 * 1024);
+        let (manifest, files) = load_pack_with_options(&pack_path, &options).unwrap();
+        assert_eq!(manifest.pack.pack_id, "padded");
+        assert_eq!(files["padding.bin"].len(), 64 * 1024);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_oversized_pack_is_rejected() {
+        let (temp_dir, pack_path) = build_padded_pack("test_pack_over_limit", 4 * 1024 * 1024);
+        assert!(fs::metadata(&pack_path).unwrap().len() < 64 * 1024);
+
+        let options = PackLoadOptions::new().max_decompressed_size(1024 * 1024);
+        let err = load_pack_with_options(&pack_path, &options).unwrap_err();
+        assert!(err.to_string().contains("more than the 1048576 byte limit"), "{}", err);
+
+        // The default limit is far above this
+        assert!(load_pack(&pack_path).is_ok());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::loader::load_pack;
+
+/// Name of the lock file stored inside a pack that has dependencies
+pub const LOCK_FILE: &str = "ccpack.lock";
+
+/// The dependency packs a pack was built against, pinned by version and
+/// manifest hash
+///
+/// A dependency `some-id` is resolved to `some-id.ccpack` in the directory
+/// the pack is built into (and later loaded from).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackLock {
+    #[serde(default)]
+    pub dependencies: Vec<LockedDependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedDependency {
+    pub pack_id: String,
+    pub version: String,
+    /// SHA-256 of the dependency's manifest.toml, which itself lists the
+    /// hash of every file in it
+    pub manifest_sha256: String,
+}
+
+impl PackLock {
+    /// Resolve each of `dependencies` in `pack_dir` and pin what's there now
+    pub fn resolve(pack_dir: &Path, dependencies: &[String]) -> Result<Self> {
+        let dependencies = dependencies.iter()
+            .map(|pack_id| resolve_dependency(pack_dir, pack_id))
+            .collect::<Result<_>>()?;
+        Ok(Self { dependencies })
+    }
+
+    /// Fail if a dependency in `pack_dir` is missing or no longer matches
+    /// the version and manifest it was locked at
+    pub fn check(&self, pack_dir: &Path) -> Result<()> {
+        for locked in &self.dependencies {
+            let current = resolve_dependency(pack_dir, &locked.pack_id)?;
+            if current != *locked {
+                anyhow::bail!(
+                    "Dependency pack '{}' has changed since it was locked (version {} -> {}, manifest {} -> {})",
+                    locked.pack_id,
+                    locked.version,
+                    current.version,
+                    locked.manifest_sha256,
+                    current.manifest_sha256
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check the lock among a loaded pack's `files` (if it has one) against the
+/// dependency packs next to `ccpack_path`
+pub(crate) fn check_pack_lock(ccpack_path: &Path, files: &HashMap<String, Vec<u8>>) -> Result<()> {
+    let Some(content) = files.get(LOCK_FILE) else {
+        return Ok(());
+    };
+    let content = std::str::from_utf8(content)
+        .with_context(|| format!("{} is not valid UTF-8", LOCK_FILE))?;
+    let lock: PackLock = toml::from_str(content)
+        .with_context(|| format!("Failed to parse {}", LOCK_FILE))?;
+    lock.check(ccpack_path.parent().unwrap_or(Path::new(".")))
+}
+
+fn resolve_dependency(pack_dir: &Path, pack_id: &str) -> Result<LockedDependency> {
+    let path = pack_dir.join(format!("{}.ccpack", pack_id));
+    if !path.exists() {
+        anyhow::bail!("Dependency pack '{}' not found at {}", pack_id, path.display());
+    }
+    let (manifest, files) = load_pack(&path)
+        .with_context(|| format!("Failed to load dependency pack '{}'", pack_id))?;
+    let manifest_toml = files.get("manifest.toml")
+        .ok_or_else(|| anyhow::anyhow!("manifest.toml not found in dependency pack '{}'", pack_id))?;
+
+    Ok(LockedDependency {
+        pack_id: pack_id.to_string(),
+        version: manifest.pack.version,
+        manifest_sha256: format!("{:x}", Sha256::digest(manifest_toml)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::{build_pack, load_pack_with_options, PackLoadOptions};
+    use std::fs;
+
+    fn write_pack_dir(dir: &Path, pack_toml: &str, card: &str) {
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir.join("cards")).unwrap();
+        fs::write(dir.join("pack.toml"), pack_toml).unwrap();
+        fs::write(dir.join("cards/card.toml"), card).unwrap();
+    }
+
+    #[test]
+    fn test_lock_pins_dependencies_and_detects_changes() {
+        let temp_dir = std::env::temp_dir().join("test_pack_lock");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let out = temp_dir.join("out");
+        fs::create_dir_all(&out).unwrap();
+
+        write_pack_dir(&temp_dir.join("base"), "pack_id = \"base\"\nversion = \"1.0.0\"\n", "name = \"Base\"\n");
+        build_pack(temp_dir.join("base"), out.join("base.ccpack")).unwrap();
+        write_pack_dir(
+            &temp_dir.join("expansion"),
+            "pack_id = \"expansion\"\nversion = \"1.0.0\"\ndependencies = [\"base\"]\n",
+            "name = \"Expansion\"\n",
+        );
+        build_pack(temp_dir.join("expansion"), out.join("expansion.ccpack")).unwrap();
+
+        let (_, files) = load_pack(out.join("expansion.ccpack")).unwrap();
+        let lock: PackLock = toml::from_str(std::str::from_utf8(&files[LOCK_FILE]).unwrap()).unwrap();
+        assert_eq!(lock.dependencies.len(), 1);
+        assert_eq!(lock.dependencies[0].pack_id, "base");
+        assert_eq!(lock.dependencies[0].version, "1.0.0");
+
+        let checked = PackLoadOptions::new().check_lock(true);
+        assert!(load_pack_with_options(out.join("expansion.ccpack"), &checked).is_ok());
+
+        // Rebuilding the dependency with different content breaks the lock
+        fs::write(temp_dir.join("base/cards/card.toml"), "name = \"Base, revised\"\n").unwrap();
+        build_pack(temp_dir.join("base"), out.join("base.ccpack")).unwrap();
+        let err = load_pack_with_options(out.join("expansion.ccpack"), &checked).unwrap_err();
+        assert!(err.to_string().contains("'base' has changed"), "{}", err);
+        // Loading without the check still works
+        assert!(load_pack(out.join("expansion.ccpack")).is_ok());
+
+        // A dependency that isn't there fails the build
+        fs::remove_file(out.join("base.ccpack")).unwrap();
+        let err = build_pack(temp_dir.join("expansion"), out.join("expansion.ccpack")).unwrap_err();
+        assert!(err.to_string().contains("'base' not found"), "{}", err);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -11,6 +11,8 @@
 //! - `cards/*.toml`: Card definition files
 //! - `scripts/*.rhai`: Rhai script files
 //! - `manifest.toml`: Auto-generated file list with hashes
+//! - `ccpack.lock`: Auto-generated for packs with dependencies, pinning the
+//!   version and manifest hash of each dependency pack it was built against
 //!
 //! # Format
 //!
@@ -18,7 +20,8 @@
 //! - Deterministic: Same input → same output
 //! - Portable: Single file, no extraction needed
 //! - Inspectable: Can list contents without extraction
-//! - Verifiable: Includes SHA-256 hashes for all files
+//! - Verifiable: Includes SHA-256 hashes for all files, and can be signed
+//!   with an Ed25519 key (a detached `<pack>.sig` next to the pack)
 //!
 //! # Example Usage
 //!
@@ -38,8 +41,12 @@
 pub mod metadata;
 pub mod builder;
 pub mod loader;
+pub mod signing;
+pub mod lock;
 
 // Re-export main API
 pub use metadata::{PackMeta, FileEntry, Manifest};
-pub use builder::build_pack;
-pub use loader::{load_pack, list_pack, unpack_pack};
+pub use builder::{build_pack, build_pack_with_options, PackBuildOptions};
+pub use loader::{load_pack, load_pack_with_options, list_pack, unpack_pack, PackLoadOptions, DEFAULT_MAX_DECOMPRESSED_SIZE};
+pub use signing::{generate_keypair, sign_pack, verify_pack, signature_path};
+pub use lock::{LockedDependency, PackLock, LOCK_FILE};
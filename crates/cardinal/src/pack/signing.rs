@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand::RngCore;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Generate an Ed25519 keypair for signing packs
+///
+/// Writes the secret key to `out` and the public key to `out.pub`, each as a
+/// single line of hex. The secret key file is created fresh (readable only by
+/// its owner on Unix), so this fails rather than overwrite an existing key.
+/// Returns the path of the public key file.
+pub fn generate_keypair<P: AsRef<Path>>(out: P) -> Result<PathBuf> {
+    let out = out.as_ref();
+
+    let mut seed = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut seed);
+    let public = SigningKey::from_bytes(&seed).verifying_key().to_bytes();
+
+    let pub_path = public_key_path(out);
+    write_secret_key(out, &format!("{}\n", to_hex(&seed)))
+        .with_context(|| format!("Failed to write secret key {}", out.display()))?;
+    std::fs::write(&pub_path, format!("{}\n", to_hex(&public)))
+        .with_context(|| format!("Failed to write public key {}", pub_path.display()))?;
+
+    Ok(pub_path)
+}
+
+/// Sign a .ccpack file with a secret key written by [`generate_keypair`]
+///
+/// The signature covers the pack file's bytes and is written next to it as
+/// `<pack>.sig`. Returns the path of the signature file.
+pub fn sign_pack<P: AsRef<Path>, K: AsRef<Path>>(ccpack_path: P, key_path: K) -> Result<PathBuf> {
+    let ccpack_path = ccpack_path.as_ref();
+    let seed: [u8; 32] = read_hex_file(key_path.as_ref(), "secret key")?;
+
+    let data = std::fs::read(ccpack_path)
+        .with_context(|| format!("Failed to read pack file {}", ccpack_path.display()))?;
+    let signature = SigningKey::from_bytes(&seed).sign(&data).to_bytes();
+
+    let sig_path = signature_path(ccpack_path);
+    std::fs::write(&sig_path, format!("{}\n", to_hex(&signature)))
+        .with_context(|| format!("Failed to write signature {}", sig_path.display()))?;
+
+    Ok(sig_path)
+}
+
+/// Check a .ccpack file's `<pack>.sig` against a public key
+///
+/// Fails if the signature is missing, malformed, or doesn't match the pack
+/// and key.
+pub fn verify_pack<P: AsRef<Path>, K: AsRef<Path>>(ccpack_path: P, pubkey_path: K) -> Result<()> {
+    let ccpack_path = ccpack_path.as_ref();
+    let public: [u8; 32] = read_hex_file(pubkey_path.as_ref(), "public key")?;
+    let signature: [u8; 64] = read_hex_file(&signature_path(ccpack_path), "signature")?;
+
+    let data = std::fs::read(ccpack_path)
+        .with_context(|| format!("Failed to read pack file {}", ccpack_path.display()))?;
+
+    let public = VerifyingKey::from_bytes(&public).map_err(|_| {
+        anyhow::anyhow!("Public key {} is not a valid Ed25519 key", pubkey_path.as_ref().display())
+    })?;
+    let signature = Signature::from_bytes(&signature);
+
+    if public.verify_strict(&data, &signature).is_err() {
+        anyhow::bail!(
+            "Signature for {} does not match public key {}",
+            ccpack_path.display(),
+            pubkey_path.as_ref().display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Where the detached signature for a pack lives (`<pack>.sig`)
+pub fn signature_path<P: AsRef<Path>>(ccpack_path: P) -> PathBuf {
+    append_extension(ccpack_path.as_ref(), "sig")
+}
+
+/// Where the public half of a secret key lives (`<key>.pub`)
+fn public_key_path(key_path: &Path) -> PathBuf {
+    append_extension(key_path, "pub")
+}
+
+/// Create a secret key file that didn't exist before, with owner-only permissions
+fn write_secret_key(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Read a file holding exactly `N` bytes of hex
+fn read_hex_file<const N: usize>(path: &Path, what: &str) -> Result<[u8; N]> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {} {}", what, path.display()))?;
+    let text = content.trim();
+
+    if text.len() != N * 2 || !text.is_ascii() {
+        anyhow::bail!("{} {} is not {} bytes of hex", what, path.display(), N);
+    }
+
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16)
+            .with_context(|| format!("{} {} is not valid hex", what, path.display()))?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::builder::build_pack;
+    use std::fs;
+
+    fn build_test_pack(name: &str) -> (PathBuf, PathBuf) {
+        let temp_dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("pack")).unwrap();
+        fs::write(
+            temp_dir.join("pack/pack.toml"),
+            "pack_id = \"signed-pack\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+
+        let pack_path = temp_dir.join("signed.ccpack");
+        build_pack(temp_dir.join("pack"), &pack_path).unwrap();
+        (temp_dir, pack_path)
+    }
+
+    #[test]
+    fn test_sign_and_verify_pack() {
+        let (temp_dir, pack_path) = build_test_pack("test_sign_pack");
+        let key_path = temp_dir.join("key");
+
+        let pub_path = generate_keypair(&key_path).unwrap();
+        assert_eq!(pub_path, temp_dir.join("key.pub"));
+
+        let sig_path = sign_pack(&pack_path, &key_path).unwrap();
+        assert_eq!(sig_path, temp_dir.join("signed.ccpack.sig"));
+        verify_pack(&pack_path, &pub_path).unwrap();
+
+        // Any change to the pack invalidates the signature
+        let mut data = fs::read(&pack_path).unwrap();
+        data.push(0);
+        fs::write(&pack_path, data).unwrap();
+        assert!(verify_pack(&pack_path, &pub_path).is_err());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_generate_keypair_protects_the_secret_key() {
+        let temp_dir = std::env::temp_dir().join("test_keypair_secret");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let key_path = temp_dir.join("key");
+
+        generate_keypair(&key_path).unwrap();
+        let secret = fs::read_to_string(&key_path).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // An existing key is never overwritten
+        assert!(generate_keypair(&key_path).is_err());
+        assert_eq!(fs::read_to_string(&key_path).unwrap(), secret);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_rfc8032_vector() {
+        // RFC 8032 section 7.1, test 2
+        let temp_dir = std::env::temp_dir().join("test_sign_rfc8032");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("key"), "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb\n").unwrap();
+        fs::write(temp_dir.join("key.pub"), "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c\n").unwrap();
+        fs::write(temp_dir.join("msg"), [0x72]).unwrap();
+
+        let sig_path = sign_pack(temp_dir.join("msg"), temp_dir.join("key")).unwrap();
+        assert_eq!(
+            fs::read_to_string(&sig_path).unwrap().trim(),
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+        );
+        verify_pack(temp_dir.join("msg"), temp_dir.join("key.pub")).unwrap();
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_verify_pack_wrong_key_fails() {
+        let (temp_dir, pack_path) = build_test_pack("test_sign_pack_wrong_key");
+        let key_path = temp_dir.join("key");
+        let other_key_path = temp_dir.join("other");
+
+        generate_keypair(&key_path).unwrap();
+        let other_pub = generate_keypair(&other_key_path).unwrap();
+        sign_pack(&pack_path, &key_path).unwrap();
+
+        let err = verify_pack(&pack_path, &other_pub).unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
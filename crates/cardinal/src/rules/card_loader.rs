@@ -69,6 +69,53 @@ pub fn load_cards_from_dir<P: AsRef<Path>>(cards_dir: P) -> Result<Vec<CardDef>>
     Ok(cards)
 }
 
+/// Every card in a pack as a JSON array of `CardDef`s, sorted by ID, for
+/// deckbuilders, wikis and other tools
+///
+/// Templates are already applied. Card IDs must be unique and each card's
+/// keywords non-empty and listed once; if the pack carries a `rules.toml`,
+/// its keywords must also be declared there.
+pub fn export_card_database<P: AsRef<Path>>(ccpack_path: P) -> Result<String> {
+    #[derive(serde::Deserialize)]
+    struct PackRules {
+        #[serde(default)]
+        keywords: Vec<crate::rules::schema::Keyword>,
+    }
+
+    let ccpack_path = ccpack_path.as_ref();
+    let (_manifest, mut files) = load_pack(ccpack_path)
+        .with_context(|| format!("Failed to load pack: {}", ccpack_path.display()))?;
+    let declared = match files.remove("rules.toml") {
+        Some(content) => {
+            let rules: PackRules = toml::from_str(&String::from_utf8(content).context("rules.toml is not valid UTF-8")?)
+                .context("Failed to parse the pack's rules.toml")?;
+            Some(rules.keywords.into_iter().map(|k| k.id).collect::<Vec<_>>())
+        }
+        None => None,
+    };
+
+    let mut cards = cards_from_pack_files(files)?;
+    validate_unique_card_ids(&cards)?;
+    for card in &cards {
+        for (index, keyword) in card.keywords.iter().enumerate() {
+            if keyword.is_empty() {
+                anyhow::bail!("Card '{}' has an empty keyword", card.id);
+            }
+            if card.keywords[..index].contains(keyword) {
+                anyhow::bail!("Card '{}' lists keyword '{}' more than once", card.id, keyword);
+            }
+            if let Some(declared) = &declared
+                && !declared.contains(keyword)
+            {
+                anyhow::bail!("Card '{}' uses keyword '{}', which the pack's rules.toml doesn't declare", card.id, keyword);
+            }
+        }
+    }
+
+    cards.sort_by(|a, b| a.id.cmp(&b.id));
+    serde_json::to_string_pretty(&cards).context("Failed to serialize cards")
+}
+
 /// Load card definitions from a single TOML file containing a [[cards]] array
 ///
 /// This function loads multiple cards from a single TOML file that uses the [[cards]] array format.
@@ -101,24 +148,130 @@ pub fn load_cards_from_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<CardDef>
         return Ok(Vec::new());
     }
 
+    let content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read cards file: {}", file_path.display()))?;
+    
+    parse_cards_file(&content)
+        .with_context(|| format!("Failed to parse cards file: {}", file_path.display()))
+}
+
+/// Parse the contents of a consolidated `[[cards]]` file
+fn parse_cards_file(content: &str) -> Result<Vec<CardDef>> {
     #[derive(serde::Deserialize)]
     struct CardsFile {
         cards: Vec<CardDef>,
     }
 
-    let content = std::fs::read_to_string(file_path)
-        .with_context(|| format!("Failed to read cards file: {}", file_path.display()))?;
-    
-    let cards_file: CardsFile = toml::from_str(&content)
-        .with_context(|| format!("Failed to parse cards file: {}", file_path.display()))?;
-    
+    let cards_file: CardsFile = parse_with_templates(content)?;
     Ok(cards_file.cards)
 }
 
+/// Parse a document holding `[[cards]]` (a rules file or a cards file),
+/// first filling in its cards from the `[[templates]]` it declares (see
+/// [`resolve_templates`])
+pub(crate) fn parse_with_templates<T: serde::de::DeserializeOwned>(content: &str) -> Result<T> {
+    let mut document: toml::Table = toml::from_str(content)?;
+    let uses_templates = document.contains_key("templates")
+        || document.get("cards")
+            .and_then(|cards| cards.as_array())
+            .is_some_and(|cards| cards.iter().any(|card| card.get("base").is_some()));
+    if !uses_templates {
+        // Straight from the text, so errors keep their line numbers
+        return Ok(toml::from_str(content)?);
+    }
+
+    resolve_templates(&mut document)?;
+    Ok(toml::Value::Table(document).try_into()?)
+}
+
+/// Fill in a document's `[[cards]]` from its `[[templates]]`
+///
+/// A card with `base = "template_id"` takes every field of that template it
+/// doesn't set itself. Tables such as `stats` are merged key by key; other
+/// values, arrays included, are replaced whole. A template may have a
+/// `base` of its own naming a template declared before it. The `templates`
+/// table and the `base` keys are removed.
+///
+/// ```toml
+/// [[templates]]
+/// id = "vanilla_creature"
+/// card_type = "creature"
+/// stats = { power = "1", toughness = "1" }
+///
+/// [[cards]]
+/// id = "7"
+/// name = "Bear"
+/// base = "vanilla_creature"
+/// stats = { power = "2" }  # toughness 1 comes from the template
+/// ```
+pub fn resolve_templates(document: &mut toml::Table) -> Result<()> {
+    let mut templates: HashMap<String, toml::Table> = HashMap::new();
+    if let Some(declared) = document.remove("templates") {
+        let declared = match declared {
+            toml::Value::Array(declared) => declared,
+            _ => anyhow::bail!("`templates` must be an array of tables"),
+        };
+        for template in declared {
+            let toml::Value::Table(mut template) = template else {
+                anyhow::bail!("Each template must be a table");
+            };
+            let id = match template.remove("id") {
+                Some(toml::Value::String(id)) => id,
+                _ => anyhow::bail!("Each template needs a string `id`"),
+            };
+            let template = apply_base(template, &templates)
+                .with_context(|| format!("Template '{}'", id))?;
+            if templates.insert(id.clone(), template).is_some() {
+                anyhow::bail!("Template '{}' is declared more than once", id);
+            }
+        }
+    }
+
+    if let Some(toml::Value::Array(cards)) = document.get_mut("cards") {
+        for card in cards.iter_mut() {
+            let toml::Value::Table(table) = card else { continue };
+            let id = table.get("id").and_then(|id| id.as_str()).unwrap_or("?").to_string();
+            *table = apply_base(std::mem::take(table), &templates)
+                .with_context(|| format!("Card '{}'", id))?;
+        }
+    }
+    Ok(())
+}
+
+/// `table` on top of the template its `base` names, if it has one
+fn apply_base(mut table: toml::Table, templates: &HashMap<String, toml::Table>) -> Result<toml::Table> {
+    let Some(base) = table.remove("base") else {
+        return Ok(table);
+    };
+    let base = base.as_str()
+        .ok_or_else(|| anyhow::anyhow!("`base` must be a template ID string"))?;
+    let mut merged = templates.get(base)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("unknown template '{}'", base))?;
+    merge_table(&mut merged, table);
+    Ok(merged)
+}
+
+/// Overlay `overrides` onto `base`, merging tables key by key
+fn merge_table(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge_table(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Paths inside a pack that hold a consolidated `[[cards]]` file rather than a single card
+const PACK_CARDS_FILES: &[&str] = &["cards.toml", "cards/cards.toml"];
+
 /// Load card definitions from a .ccpack file
 ///
 /// Extracts all `.toml` files from the `cards/` directory within the pack
-/// and parses them as CardDef structs.
+/// and parses them as CardDef structs. A `cards.toml` at the pack root or in
+/// `cards/` is read as a `[[cards]]` array and merged with the individual files.
 ///
 /// # Arguments
 /// * `ccpack_path` - Path to the .ccpack file
@@ -130,17 +283,30 @@ pub fn load_cards_from_pack<P: AsRef<Path>>(ccpack_path: P) -> Result<Vec<CardDe
     
     let (_manifest, files) = load_pack(ccpack_path)
         .with_context(|| format!("Failed to load pack: {}", ccpack_path.display()))?;
+    cards_from_pack_files(files)
+}
 
+/// The cards among a loaded pack's files (see [`load_cards_from_pack`])
+fn cards_from_pack_files(files: HashMap<String, Vec<u8>>) -> Result<Vec<CardDef>> {
     let mut cards = Vec::new();
 
     for (path, content) in files {
+        let consolidated = PACK_CARDS_FILES.contains(&path.as_str());
+
         // Only process files in the cards/ directory
-        if !path.starts_with("cards/") || !path.ends_with(".toml") {
+        if !consolidated && (!path.starts_with("cards/") || !path.ends_with(".toml")) {
             continue;
         }
 
         let content_str = String::from_utf8(content)
             .with_context(|| format!("Card file is not valid UTF-8: {}", path))?;
+
+        if consolidated {
+            let file_cards = parse_cards_file(&content_str)
+                .with_context(|| format!("Failed to parse cards file from pack: {}", path))?;
+            cards.extend(file_cards);
+            continue;
+        }
         
         let card: CardDef = toml::from_str(&content_str)
             .with_context(|| format!("Failed to parse card from pack: {}", path))?;
@@ -208,11 +374,125 @@ pub fn validate_unique_card_ids(cards: &[CardDef]) -> Result<()> {
     Ok(())
 }
 
+/// Merge cards defined inline in the ruleset with cards loaded from external sources
+///
+/// A card ID present in both sets is kept once if the two definitions are
+/// identical. If they differ, the merge fails with a conflict naming the card,
+/// since silently preferring either side hides a stale definition.
+///
+/// # Arguments
+/// * `inline` - Cards from the ruleset's own `cards` list
+/// * `loaded` - Cards loaded from external sources
+///
+/// # Returns
+/// The combined card list, inline cards first
+pub fn merge_card_definitions(inline: Vec<CardDef>, loaded: Vec<CardDef>) -> Result<Vec<CardDef>> {
+    let mut inline_by_id = HashMap::new();
+    for card in &inline {
+        let value = toml::Value::try_from(card)
+            .with_context(|| format!("Failed to compare card '{}'", card.id))?;
+        inline_by_id.insert(card.id.clone(), value);
+    }
+
+    let mut merged = inline;
+    for card in loaded {
+        if let Some(existing) = inline_by_id.get(&card.id) {
+            let value = toml::Value::try_from(&card)
+                .with_context(|| format!("Failed to compare card '{}'", card.id))?;
+            if *existing != value {
+                return Err(anyhow::anyhow!(
+                    "Card ID '{}' conflicts: the ruleset and a card source define it differently",
+                    card.id
+                ));
+            }
+            continue;
+        }
+        merged.push(card);
+    }
+
+    Ok(merged)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_export_card_database_of_example_pack() {
+        let pack = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/example-pack.ccpack");
+        let json = export_card_database(&pack).unwrap();
+
+        let exported: serde_json::Value = serde_json::from_str(&json).expect("export is valid JSON");
+        let ids: Vec<&str> = exported.as_array().expect("a JSON array")
+            .iter()
+            .map(|card| card["id"].as_str().expect("every card has an ID"))
+            .collect();
+        let mut expected: Vec<String> = load_cards_from_pack(&pack).unwrap().into_iter().map(|c| c.id).collect();
+        expected.sort();
+        assert!(!expected.is_empty());
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_cards_inherit_from_templates() {
+        let cards = parse_cards_file(r#"
+[[templates]]
+id = "vanilla_creature"
+card_type = "creature"
+cost = "1"
+stats = { power = "1", toughness = "1" }
+
+[[templates]]
+id = "big_creature"
+base = "vanilla_creature"
+cost = "5"
+stats = { power = "5", toughness = "5" }
+
+[[cards]]
+id = "1"
+name = "Plain Bear"
+base = "vanilla_creature"
+
+[[cards]]
+id = "2"
+name = "Strong Bear"
+base = "vanilla_creature"
+stats = { power = "3" }
+
+[[cards]]
+id = "3"
+name = "Giant"
+base = "big_creature"
+card_type = "artifact_creature"
+
+[[cards]]
+id = "4"
+name = "Spell"
+card_type = "spell"
+"#).unwrap();
+
+        assert_eq!(cards[0].card_type, "creature");
+        assert_eq!(cards[0].cost.as_deref(), Some("1"));
+        assert_eq!(cards[0].stats.get("power").map(String::as_str), Some("1"));
+        assert_eq!(cards[0].stats.get("toughness").map(String::as_str), Some("1"));
+
+        // Overridden stats merge into the template's
+        assert_eq!(cards[1].stats.get("power").map(String::as_str), Some("3"));
+        assert_eq!(cards[1].stats.get("toughness").map(String::as_str), Some("1"));
+
+        // Templates can build on earlier ones
+        assert_eq!(cards[2].card_type, "artifact_creature");
+        assert_eq!(cards[2].cost.as_deref(), Some("5"));
+        assert_eq!(cards[2].stats.get("toughness").map(String::as_str), Some("5"));
+
+        assert_eq!(cards[3].card_type, "spell");
+        assert!(cards[3].stats.is_empty());
+
+        let err = parse_cards_file("[[cards]]\nid = \"1\"\nname = \"X\"\nbase = \"missing\"\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("Card '1': unknown template 'missing'"), "{:#}", err);
+    }
+
     #[test]
     fn test_load_cards_from_dir() {
         // Create a temporary test directory
@@ -252,7 +532,14 @@ description = "A test card"
             abilities: vec![],
             script_path: None,
             keywords: vec![],
+            colors: vec![],
             stats: HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
 
         let card2 = CardDef {
@@ -264,7 +551,14 @@ description = "A test card"
             abilities: vec![],
             script_path: None,
             keywords: vec![],
+            colors: vec![],
             stats: HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
 
         // Test valid cards
@@ -280,12 +574,48 @@ description = "A test card"
             abilities: vec![],
             script_path: None,
             keywords: vec![],
+            colors: vec![],
             stats: HashMap::new(),
+            enters_tapped: false,
+            enters_with: None,
+            tests: vec![],
+            produces: None,
+            cost_modifiers: vec![],
+            additional_cost: None,
         };
 
         assert!(validate_unique_card_ids(&[card1, card_duplicate]).is_err());
     }
 
+    fn parse_card(toml_str: &str) -> CardDef {
+        toml::from_str(toml_str).unwrap()
+    }
+
+    #[test]
+    fn test_merge_card_definitions_clean() {
+        let shared = r#"
+id = "shared"
+name = "Shared Card"
+card_type = "creature"
+cost = "1"
+"#;
+        let inline = vec![parse_card(shared), parse_card("id = \"inline\"\nname = \"Inline\"\ncard_type = \"spell\"")];
+        let loaded = vec![parse_card(shared), parse_card("id = \"loaded\"\nname = \"Loaded\"\ncard_type = \"spell\"")];
+
+        let merged = merge_card_definitions(inline, loaded).unwrap();
+        let ids: Vec<_> = merged.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["shared", "inline", "loaded"]);
+    }
+
+    #[test]
+    fn test_merge_card_definitions_conflict() {
+        let inline = vec![parse_card("id = \"7\"\nname = \"Old Name\"\ncard_type = \"creature\"")];
+        let loaded = vec![parse_card("id = \"7\"\nname = \"New Name\"\ncard_type = \"creature\"")];
+
+        let err = merge_card_definitions(inline, loaded).unwrap_err();
+        assert!(err.to_string().contains("'7'"), "unexpected error: {}", err);
+    }
+
     #[test]
     fn test_load_cards_from_file() {
         // Create a temporary test file
@@ -357,7 +687,7 @@ description = "Third card from file"
         let cards = load_cards_from_pack(&pack_path).unwrap();
         
         // The example pack should have cards
-        assert!(cards.len() > 0, "Pack should contain at least one card");
+        assert!(!cards.is_empty(), "Pack should contain at least one card");
         
         // Verify cards have valid structure
         for card in &cards {
@@ -366,6 +696,47 @@ description = "Third card from file"
         }
     }
 
+    #[test]
+    fn test_load_cards_from_pack_with_cards_toml() {
+        let temp_dir = std::env::temp_dir().join("test_pack_cards_toml");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src/cards")).unwrap();
+
+        fs::write(temp_dir.join("src/pack.toml"), "pack_id = \"cards-toml-pack\"\nversion = \"1.0.0\"\n").unwrap();
+        fs::write(temp_dir.join("src/cards.toml"), r#"
+[[cards]]
+id = "root_file_1"
+name = "Root File One"
+card_type = "creature"
+
+[[cards]]
+id = "root_file_2"
+name = "Root File Two"
+card_type = "spell"
+"#).unwrap();
+        fs::write(temp_dir.join("src/cards/cards.toml"), r#"
+[[cards]]
+id = "nested_file_1"
+name = "Nested File One"
+card_type = "spell"
+"#).unwrap();
+        fs::write(temp_dir.join("src/cards/single.toml"), r#"
+id = "single_1"
+name = "Single Card"
+card_type = "creature"
+"#).unwrap();
+
+        let pack_path = temp_dir.join("cards.ccpack");
+        crate::pack::build_pack(temp_dir.join("src"), &pack_path).unwrap();
+
+        let cards = load_cards_from_pack(&pack_path).unwrap();
+        let mut ids: Vec<_> = cards.iter().map(|c| c.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["nested_file_1", "root_file_1", "root_file_2", "single_1"]);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[test]
     fn test_load_cards_from_sources_single() {
         // Create a temporary test directory
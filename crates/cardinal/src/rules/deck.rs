@@ -0,0 +1,113 @@
+//! Deck lists
+//!
+//! A deck file names cards by ID with a count for each, in the order they
+//! go into the deck before it's shuffled:
+//!
+//! ```toml
+//! name = "Red Aggro"
+//!
+//! [[cards]]
+//! id = "1"
+//! count = 4
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::CardinalError;
+use crate::ids::CardId;
+use crate::rules::schema::Ruleset;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckList {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub cards: Vec<DeckEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckEntry {
+    /// ID of a card defined by the ruleset
+    pub id: String,
+    /// Copies of the card (defaults to 1)
+    #[serde(default = "default_count")]
+    pub count: usize,
+}
+
+fn default_count() -> usize {
+    1
+}
+
+impl DeckList {
+    /// The deck's cards, one ID per copy, checked against `ruleset`: every
+    /// card must be defined there and the deck size must be within the
+    /// ruleset's `min_deck_size` and `max_deck_size`
+    pub fn card_ids(&self, ruleset: &Ruleset) -> Result<Vec<CardId>, CardinalError> {
+        let mut cards = Vec::new();
+        for entry in &self.cards {
+            if !ruleset.cards.iter().any(|c| c.id == entry.id) {
+                return Err(CardinalError(format!("Deck card '{}' is not defined by the ruleset", entry.id)));
+            }
+            let id = entry.id.parse::<u32>()
+                .map_err(|_| CardinalError(format!("Deck card ID '{}' is not numeric", entry.id)))?;
+            cards.extend(std::iter::repeat_n(CardId(id), entry.count));
+        }
+
+        let (min, max) = (ruleset.players.min_deck_size, ruleset.players.max_deck_size);
+        if cards.len() < min || cards.len() > max {
+            return Err(CardinalError(format!(
+                "Deck has {} cards; the rules require {} to {}",
+                cards.len(), min, max
+            )));
+        }
+        Ok(cards)
+    }
+}
+
+/// Load a deck file and resolve it against `ruleset` (see [`DeckList::card_ids`])
+pub fn load_deck<P: AsRef<Path>>(path: P, ruleset: &Ruleset) -> Result<Vec<CardId>, CardinalError> {
+    let content = std::fs::read_to_string(path.as_ref())
+        .map_err(|e| CardinalError(format!("Failed to read deck file: {}", e)))?;
+    let deck: DeckList = toml::from_str(&content)
+        .map_err(|e| CardinalError(format!("Failed to parse deck file: {}", e)))?;
+    deck.card_ids(ruleset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_rules() -> Ruleset {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        crate::load_game_config(root.join("rules.toml"), None).expect("repo rules load")
+    }
+
+    #[test]
+    fn test_deck_list_card_ids() {
+        let rules = repo_rules();
+        let deck: DeckList = toml::from_str(r#"
+            [[cards]]
+            id = "1"
+            count = 38
+
+            [[cards]]
+            id = "3"
+            count = 2
+        "#).unwrap();
+        let cards = deck.card_ids(&rules).unwrap();
+        assert_eq!(cards.len(), 40);
+        assert_eq!(cards.iter().filter(|c| **c == CardId(3)).count(), 2);
+
+        let small: DeckList = toml::from_str("[[cards]]\nid = \"1\"").unwrap();
+        assert!(small.card_ids(&rules).is_err());
+        let unknown: DeckList = toml::from_str("[[cards]]\nid = \"9999\"\ncount = 40").unwrap();
+        assert!(unknown.card_ids(&rules).is_err());
+    }
+
+    #[test]
+    fn test_example_deck_loads() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let cards = load_deck(root.join("examples/decks/starter.toml"), &repo_rules()).unwrap();
+        assert_eq!(cards.len(), 40);
+    }
+}
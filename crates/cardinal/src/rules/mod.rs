@@ -1,6 +1,7 @@
 pub mod schema;
 pub mod query;
 pub mod card_loader;
+pub mod deck;
 
 use crate::rules::schema::Ruleset;
 use crate::state::gamestate::GameState;
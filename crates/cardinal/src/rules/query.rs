@@ -1,2 +1,512 @@
 // Rules query functions
 
+use crate::engine::cards::CardRegistry;
+use crate::error::CardinalError;
+use crate::ids::{CardId, PlayerId};
+use crate::model::event::CoinFlip;
+use crate::rules::schema::CardDef;
+use crate::state::gamestate::GameState;
+
+/// Current life total of a player, if they exist
+pub fn life_of(state: &GameState, player: PlayerId) -> Option<i32> {
+    state.players.iter().find(|p| p.id == player).map(|p| p.life)
+}
+
+/// Number of cards in a player's zone (e.g. `zone_size(state, "hand", p)`)
+pub fn zone_size(state: &GameState, zone: &str, player: PlayerId) -> usize {
+    let zone_id = format!("{}@{}", zone, player.0);
+    state.zones.iter()
+        .find(|z| z.id.0 == zone_id)
+        .map(|z| z.cards.len())
+        .unwrap_or(0)
+}
+
+/// A card's current power and toughness.
+///
+/// Starts from its base stats (values set on the instance by `SetStats`,
+/// otherwise those of its current definition), then adds its stat modifiers (pumps and other
+/// continuous changes, including those lasting until end of turn) and one per "+1/+1" counter, less one per "-1/-1"
+/// counter. A stat the card doesn't have counts as 0.
+pub fn effective_stats(card: CardId, registry: &CardRegistry, state: &GameState) -> (i32, i32) {
+    let instance = state.card_instances.get(&card);
+    let counter = |kind: &str| instance
+        .and_then(|i| i.counters.get(kind))
+        .copied()
+        .unwrap_or(0);
+    let from_counters = counter("+1/+1") - counter("-1/-1");
+
+    let stat = |key: &str| {
+        let base = instance
+            .and_then(|i| i.stats.get(key))
+            .and_then(|s| s.parse::<i32>().ok())
+            .or_else(|| registry.get(&state.definition_of(card).0)
+                .and_then(|def| crate::engine::cards::get_card_stat_i32(def, key)))
+            .unwrap_or(0);
+        let modifier = instance
+            .map(|i| i.stat_modifiers.get(key).copied().unwrap_or(0)
+                + i.temporary_stat_modifiers.get(key).copied().unwrap_or(0))
+            .unwrap_or(0);
+        base + modifier + from_counters
+    };
+
+    (stat("power"), stat("toughness"))
+}
+
+/// A card's current keywords: those printed on its current definition, then
+/// those granted to it, permanently or until end of turn, without repeats
+pub fn effective_keywords(card: CardId, registry: &CardRegistry, state: &GameState) -> Vec<String> {
+    let mut keywords: Vec<String> = registry.get(&state.definition_of(card).0)
+        .map(|def| def.keywords.clone())
+        .unwrap_or_default();
+    if let Some(instance) = state.card_instances.get(&card) {
+        for keyword in instance.keywords.iter().chain(&instance.temporary_keywords) {
+            if !keywords.contains(keyword) {
+                keywords.push(keyword.clone());
+            }
+        }
+    }
+    keywords
+}
+
+/// Players other than `player`, in seat order starting after them
+pub fn opponents_of(state: &GameState, player: PlayerId) -> Vec<PlayerId> {
+    let count = state.players.len() as u8;
+    (1..count)
+        .map(|offset| PlayerId((player.0 + offset) % count))
+        .collect()
+}
+
+/// Evaluate a TOML ability condition against the game state
+///
+/// Conditions are comparisons joined by `and`/`or` (`and` binds tighter):
+///
+/// ```text
+/// life_of(opponent) <= 10
+/// hand_size(self) == 0 or cards_in(graveyard, self) >= 7
+/// ```
+///
+/// Values are integer literals or one of `life_of(p)`, `hand_size(p)`,
+/// `deck_size(p)`, `cards_in(zone, p)`, `turn()` and `coin_flip()` (the last
+/// flip, compared against `heads` or `tails`). Players are `self`
+/// (alias `you`/`controller`), `opponent` (the next player in seat order)
+/// or a numeric player ID. Comparison operators are `<`, `<=`, `>`, `>=`,
+/// `==` and `!=`.
+pub fn evaluate_condition(
+    condition: &str,
+    state: &GameState,
+    controller: PlayerId,
+) -> Result<bool, CardinalError> {
+    let condition = condition.trim();
+    if condition.is_empty() {
+        return Err(CardinalError("Condition is empty".to_string()));
+    }
+
+    for alternative in condition.split(" or ") {
+        let mut all = true;
+        for clause in alternative.split(" and ") {
+            if !evaluate_comparison(clause.trim(), state, controller)? {
+                all = false;
+            }
+        }
+        if all {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn evaluate_comparison(
+    clause: &str,
+    state: &GameState,
+    controller: PlayerId,
+) -> Result<bool, CardinalError> {
+    // Two-character operators first so "<=" isn't read as "<"
+    const OPERATORS: [&str; 6] = ["<=", ">=", "==", "!=", "<", ">"];
+
+    for op in OPERATORS {
+        if let Some((lhs, rhs)) = clause.split_once(op) {
+            let lhs = evaluate_value(lhs.trim(), state, controller)?;
+            let rhs = evaluate_value(rhs.trim(), state, controller)?;
+            return Ok(match op {
+                "<=" => lhs <= rhs,
+                ">=" => lhs >= rhs,
+                "==" => lhs == rhs,
+                "!=" => lhs != rhs,
+                "<" => lhs < rhs,
+                _ => lhs > rhs,
+            });
+        }
+    }
+
+    Err(CardinalError(format!("Condition '{}' has no comparison operator", clause)))
+}
+
+fn evaluate_value(
+    value: &str,
+    state: &GameState,
+    controller: PlayerId,
+) -> Result<i64, CardinalError> {
+    if let Ok(n) = value.parse::<i64>() {
+        return Ok(n);
+    }
+    match value {
+        "heads" => return Ok(1),
+        "tails" => return Ok(0),
+        _ => {}
+    }
+
+    let (name, args) = value
+        .strip_suffix(')')
+        .and_then(|v| v.split_once('('))
+        .ok_or_else(|| CardinalError(format!("Unrecognized value '{}' in condition", value)))?;
+    let args: Vec<&str> = args.split(',').map(str::trim).filter(|a| !a.is_empty()).collect();
+
+    match (name.trim(), args.as_slice()) {
+        ("life_of", [player]) => {
+            let player = resolve_player(player, state, controller)?;
+            life_of(state, player)
+                .map(i64::from)
+                .ok_or_else(|| CardinalError(format!("Player {} does not exist", player.0)))
+        }
+        ("hand_size", [player]) => {
+            Ok(zone_size(state, "hand", resolve_player(player, state, controller)?) as i64)
+        }
+        ("deck_size", [player]) => {
+            Ok(zone_size(state, "deck", resolve_player(player, state, controller)?) as i64)
+        }
+        ("cards_in", [zone, player]) => {
+            Ok(zone_size(state, zone, resolve_player(player, state, controller)?) as i64)
+        }
+        ("turn", []) => Ok(i64::from(state.turn.number)),
+        ("coin_flip", []) => match state.last_coin_flip {
+            Some(CoinFlip::Heads) => Ok(1),
+            Some(CoinFlip::Tails) => Ok(0),
+            None => Err(CardinalError("Condition reads coin_flip() but no coin has been flipped".to_string())),
+        },
+        (other, _) => Err(CardinalError(format!(
+            "Unknown condition function '{}' with {} argument(s)",
+            other,
+            args.len()
+        ))),
+    }
+}
+
+/// Evaluate a numeric effect param written as an expression, e.g.
+/// `count(creatures_controlled_by(controller))`
+///
+/// Takes any value a condition can compare (see [`evaluate_condition`]) and
+/// `count(creatures_controlled_by(p))`, the number of creatures on `p`'s
+/// field. Cards without a definition in `registry` aren't counted.
+pub fn evaluate_amount(
+    expression: &str,
+    state: &GameState,
+    controller: PlayerId,
+    registry: &CardRegistry,
+) -> Result<i64, CardinalError> {
+    let expression = expression.trim();
+    let Some(inner) = expression.strip_prefix("count(").and_then(|e| e.strip_suffix(')')) else {
+        return evaluate_value(expression, state, controller);
+    };
+
+    let (name, player) = inner.trim()
+        .strip_suffix(')')
+        .and_then(|v| v.split_once('('))
+        .ok_or_else(|| CardinalError(format!("Unrecognized count '{}'", expression)))?;
+    match name.trim() {
+        "creatures_controlled_by" => {
+            let player = resolve_player(player.trim(), state, controller)?;
+            let creatures = state.controlled_cards(player).into_iter()
+                .filter(|card| registry.get(&state.definition_of(*card).0).is_some_and(|def| def.card_type == "creature"))
+                .count();
+            Ok(creatures as i64)
+        }
+        other => Err(CardinalError(format!("Unknown count '{}'", other))),
+    }
+}
+
+fn resolve_player(
+    player: &str,
+    state: &GameState,
+    controller: PlayerId,
+) -> Result<PlayerId, CardinalError> {
+    match player {
+        "self" | "you" | "controller" => Ok(controller),
+        "opponent" => opponents_of(state, controller)
+            .first()
+            .copied()
+            .ok_or_else(|| CardinalError("Condition refers to an opponent but there is none".to_string())),
+        other => other
+            .parse::<u8>()
+            .map(PlayerId)
+            .map_err(|_| CardinalError(format!("Unknown player '{}' in condition", other))),
+    }
+}
+
+/// A filter over card definitions, e.g. for a card browser
+///
+/// Every set criterion must match; an empty query matches every card.
+#[derive(Debug, Clone, Default)]
+pub struct CardQuery {
+    /// Exact card type (e.g. "creature")
+    pub card_type: Option<String>,
+    /// Keywords the card must all have
+    pub keywords: Vec<String>,
+    /// Colors the card must all have
+    pub colors: Vec<String>,
+    /// Case-insensitive substring of the card's name
+    pub name_contains: Option<String>,
+}
+
+impl CardQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only cards of this type
+    pub fn card_type(mut self, card_type: impl Into<String>) -> Self {
+        self.card_type = Some(card_type.into());
+        self
+    }
+
+    /// Only cards with this keyword (repeatable; all are required)
+    pub fn keyword(mut self, keyword: impl Into<String>) -> Self {
+        self.keywords.push(keyword.into());
+        self
+    }
+
+    /// Only cards of this color (repeatable; all are required)
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.colors.push(color.into());
+        self
+    }
+
+    /// Only cards whose name contains this text, ignoring case
+    pub fn name_contains(mut self, text: impl Into<String>) -> Self {
+        self.name_contains = Some(text.into());
+        self
+    }
+
+    pub fn matches(&self, card: &CardDef) -> bool {
+        self.card_type.as_ref().is_none_or(|t| *t == card.card_type)
+            && self.keywords.iter().all(|k| card.keywords.contains(k))
+            && self.colors.iter().all(|c| card.colors.contains(c))
+            && self.name_contains.as_ref()
+                .is_none_or(|text| card.name.to_lowercase().contains(&text.to_lowercase()))
+    }
+
+    /// Whether no criterion is set, so every card matches
+    pub fn is_empty(&self) -> bool {
+        self.card_type.is_none()
+            && self.keywords.is_empty()
+            && self.colors.is_empty()
+            && self.name_contains.is_none()
+    }
+
+    /// Whether the card with this ID matches, by the definition it uses in
+    /// `state` (see `GameState::definition_of`). Cards without a definition
+    /// (e.g. tokens) only match the empty query.
+    pub fn matches_card(&self, card: CardId, registry: &CardRegistry, state: &GameState) -> bool {
+        match registry.get(&state.definition_of(card).0) {
+            Some(def) => self.matches(def),
+            None => self.is_empty(),
+        }
+    }
+
+    /// The matching cards, in their original order
+    pub fn filter<'a>(&self, cards: &'a [CardDef]) -> Vec<&'a CardDef> {
+        cards.iter().filter(|card| self.matches(card)).collect()
+    }
+
+    /// Parse the text form used by effect params, e.g. `type=creature,keyword=flying`
+    ///
+    /// Criteria are `key=value` pairs separated by commas, with keys `type`,
+    /// `keyword` and `color` (both repeatable) and `name`. An empty string is
+    /// the empty query.
+    pub fn parse(text: &str) -> Result<Self, CardinalError> {
+        let mut query = Self::new();
+        for criterion in text.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            let (key, value) = criterion.split_once('=')
+                .ok_or_else(|| CardinalError(format!("Card filter '{}' is not key=value", criterion)))?;
+            let value = value.trim();
+            query = match key.trim() {
+                "type" => query.card_type(value),
+                "keyword" => query.keyword(value),
+                "color" => query.color(value),
+                "name" => query.name_contains(value),
+                other => return Err(CardinalError(format!("Unknown card filter '{}'", other))),
+            };
+        }
+        Ok(query)
+    }
+}
+
+impl std::fmt::Display for CardQuery {
+    /// The text form read by [`CardQuery::parse`]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let criteria: Vec<String> = self.card_type.iter().map(|t| format!("type={}", t))
+            .chain(self.keywords.iter().map(|k| format!("keyword={}", k)))
+            .chain(self.colors.iter().map(|c| format!("color={}", c)))
+            .chain(self.name_contains.iter().map(|n| format!("name={}", n)))
+            .collect();
+        write!(f, "{}", criteria.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{PhaseId, StepId, ZoneId};
+    use crate::state::gamestate::{PlayerState, TurnState, ZoneState};
+    use std::collections::BTreeMap;
+
+    fn two_player_state() -> GameState {
+        GameState {
+            turn: TurnState {
+                number: 3,
+                active_player: PlayerId(0),
+                priority_player: PlayerId(0),
+                phase: PhaseId("main"),
+                step: StepId("main"),
+                priority_passes: 0,
+            },
+            players: vec![
+                PlayerState { id: PlayerId(0), life: 20, resources: BTreeMap::new(), draws_this_turn: 0, plays_this_turn: BTreeMap::new(), revealed: Vec::new(), revealed_to: Vec::new(), conceded: false },
+                PlayerState { id: PlayerId(1), life: 8, resources: BTreeMap::new(), draws_this_turn: 0, plays_this_turn: BTreeMap::new(), revealed: Vec::new(), revealed_to: Vec::new(), conceded: false },
+            ],
+            zones: vec![ZoneState {
+                id: ZoneId("hand@0"),
+                owner: Some(PlayerId(0)),
+                cards: vec![CardId(1), CardId(2)],
+            }],
+            stack: vec![],
+            pending_choice: None,
+            ended: None,
+            card_instances: BTreeMap::new(),
+            damage_shields: Vec::new(),
+            last_coin_flip: None,
+            rng: crate::util::rng::GameRng::new(0),
+            resource_caps: BTreeMap::new(),
+            max_hand_size: None,
+            extra_turns: Vec::new(),
+            skips: Vec::new(),
+            end_of_turn: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition() {
+        let state = two_player_state();
+        let me = PlayerId(0);
+
+        assert!(evaluate_condition("life_of(opponent) <= 10", &state, me).unwrap());
+        assert!(!evaluate_condition("life_of(self) < 10", &state, me).unwrap());
+        assert!(evaluate_condition("hand_size(you) == 2 and turn() >= 3", &state, me).unwrap());
+        assert!(evaluate_condition("deck_size(self) > 0 or cards_in(hand, 0) != 0", &state, me).unwrap());
+        assert!(!evaluate_condition("life_of(1) > 8 and hand_size(self) == 2", &state, me).unwrap());
+    }
+
+    #[test]
+    fn test_evaluate_coin_flip_condition() {
+        let mut state = two_player_state();
+        let me = PlayerId(0);
+
+        // Nothing to read before the first flip
+        assert!(evaluate_condition("coin_flip() == heads", &state, me).is_err());
+
+        state.last_coin_flip = Some(CoinFlip::Tails);
+        assert!(evaluate_condition("coin_flip() == tails", &state, me).unwrap());
+        assert!(!evaluate_condition("coin_flip() == heads", &state, me).unwrap());
+    }
+
+    fn card(id: &str, card_type: &str, keywords: &[&str]) -> CardDef {
+        let keywords: Vec<String> = keywords.iter().map(|k| format!("\"{}\"", k)).collect();
+        toml::from_str(&format!(
+            "id = \"{}\"\nname = \"Card {}\"\ncard_type = \"{}\"\nkeywords = [{}]",
+            id, id, card_type, keywords.join(", "),
+        )).unwrap()
+    }
+
+    #[test]
+    fn test_card_query_filters() {
+        let cards = vec![
+            card("1", "creature", &["flying"]),
+            card("2", "creature", &[]),
+            card("3", "spell", &["flying"]),
+            card("4", "creature", &["flying", "haste"]),
+        ];
+        let ids = |found: Vec<&CardDef>| -> Vec<String> { found.iter().map(|c| c.id.clone()).collect() };
+
+        assert_eq!(ids(CardQuery::new().card_type("creature").filter(&cards)), vec!["1", "2", "4"]);
+        assert_eq!(ids(CardQuery::new().keyword("flying").filter(&cards)), vec!["1", "3", "4"]);
+        assert_eq!(ids(CardQuery::new().card_type("creature").keyword("flying").filter(&cards)), vec!["1", "4"]);
+        assert_eq!(ids(CardQuery::new().keyword("flying").keyword("haste").filter(&cards)), vec!["4"]);
+        assert_eq!(ids(CardQuery::new().name_contains("CARD 2").filter(&cards)), vec!["2"]);
+        assert_eq!(CardQuery::new().filter(&cards).len(), 4);
+    }
+
+    #[test]
+    fn test_card_query_filters_by_color() {
+        let colored = |id: &str, colors: &str| -> CardDef {
+            let mut card = card(id, "creature", &[]);
+            card.colors = colors.split_whitespace().map(String::from).collect();
+            card
+        };
+        let cards = vec![colored("1", "red"), colored("2", "blue"), colored("3", "red blue"), colored("4", "")];
+        let ids = |found: Vec<&CardDef>| -> Vec<String> { found.iter().map(|c| c.id.clone()).collect() };
+
+        assert_eq!(ids(CardQuery::new().color("red").filter(&cards)), vec!["1", "3"]);
+        assert_eq!(ids(CardQuery::new().color("red").color("blue").filter(&cards)), vec!["3"]);
+        let parsed = CardQuery::parse("color=blue").unwrap();
+        assert_eq!(ids(parsed.filter(&cards)), vec!["2", "3"]);
+        assert_eq!(parsed.to_string(), "color=blue");
+    }
+
+    #[test]
+    fn test_card_query_parse() {
+        let query = CardQuery::parse("type=creature, keyword=flying,keyword=haste").unwrap();
+        assert_eq!(query.card_type.as_deref(), Some("creature"));
+        assert_eq!(query.keywords, vec!["flying", "haste"]);
+        assert_eq!(query.to_string(), "type=creature,keyword=flying,keyword=haste");
+
+        assert!(CardQuery::parse("").unwrap().matches(&card("1", "spell", &[])));
+        assert!(CardQuery::parse("rarity=rare").is_err());
+        assert!(CardQuery::parse("creature").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_condition_errors() {
+        let state = two_player_state();
+        let me = PlayerId(0);
+
+        assert!(evaluate_condition("life_of(opponent)", &state, me).is_err());
+        assert!(evaluate_condition("mana_of(self) > 2", &state, me).is_err());
+        assert!(evaluate_condition("life_of(villain) > 2", &state, me).is_err());
+    }
+
+    #[test]
+    fn test_effective_stats_layers_counters_and_pumps() {
+        use crate::model::command::{Command, Duration};
+
+        let mut def = card("1", "creature", &[]);
+        def.stats.insert("power".to_string(), "2".to_string());
+        def.stats.insert("toughness".to_string(), "2".to_string());
+        let registry = crate::engine::cards::build_registry(&[def]);
+        let mut state = two_player_state();
+        assert_eq!(effective_stats(CardId(1), &registry, &state), (2, 2));
+
+        crate::engine::events::commit_commands(&mut state, &[
+            Command::AddCounter { card: CardId(1), counter_type: "+1/+1".to_string(), amount: 1 },
+            Command::ModifyStats { card: CardId(1), power: 1, toughness: 1, duration: Duration::Permanent },
+        ]);
+        assert_eq!(effective_stats(CardId(1), &registry, &state), (4, 4));
+
+        // Set stats replace the base, and the other layers still apply
+        crate::engine::events::commit_commands(&mut state, &[
+            Command::SetStats { card: CardId(1), power: 0, toughness: 5 },
+        ]);
+        assert_eq!(effective_stats(CardId(1), &registry, &state), (2, 7));
+
+        assert_eq!(effective_stats(CardId(2), &registry, &state), (0, 0));
+    }
+}
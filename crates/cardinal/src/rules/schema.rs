@@ -11,12 +11,69 @@ pub struct Ruleset {
     pub stack: StackRules,
     pub trigger_kinds: Vec<TriggerKind>,
     pub keywords: Vec<Keyword>,
+    /// Colors (or factions) cards may belong to
+    #[serde(default)]
+    pub colors: Vec<ColorDef>,
     pub win_conditions: Vec<WinCondition>,
     pub loss_conditions: Vec<LossCondition>,
     #[serde(default)]
     pub cards: Vec<CardDef>,
 }
 
+/// A quick "what is this game" overview of a loaded ruleset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesetSummary {
+    pub name: String,
+    pub version: String,
+    pub min_players: usize,
+    pub max_players: usize,
+    pub phases: usize,
+    /// Steps across all phases
+    pub steps: usize,
+    pub zones: usize,
+    pub resources: usize,
+    pub keywords: usize,
+    pub cards: usize,
+}
+
+impl Ruleset {
+    pub fn summary(&self) -> RulesetSummary {
+        RulesetSummary {
+            name: self.game.name.clone(),
+            version: self.game.version.clone(),
+            min_players: self.players.min_players,
+            max_players: self.players.max_players,
+            phases: self.turn.phases.len(),
+            steps: self.turn.phases.iter().map(|p| p.steps.len()).sum(),
+            zones: self.zones.len(),
+            resources: self.resources.len(),
+            keywords: self.keywords.len(),
+            cards: self.cards.len(),
+        }
+    }
+
+    /// The ruleset's cards that match `query`, in definition order
+    pub fn cards_filtered(&self, query: &crate::rules::query::CardQuery) -> Vec<&CardDef> {
+        query.filter(&self.cards)
+    }
+}
+
+impl std::fmt::Display for RulesetSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} (v{})", self.name, self.version)?;
+        if self.min_players == self.max_players {
+            writeln!(f, "  Players:   {}", self.min_players)?;
+        } else {
+            writeln!(f, "  Players:   {}-{}", self.min_players, self.max_players)?;
+        }
+        writeln!(f, "  Phases:    {} ({} steps)", self.phases, self.steps)?;
+        writeln!(f, "  Zones:     {}", self.zones)?;
+        writeln!(f, "  Resources: {}", self.resources)?;
+        writeln!(f, "  Keywords:  {}", self.keywords)?;
+        write!(f, "  Cards:     {}", self.cards)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameInfo {
     pub id: String,
@@ -37,6 +94,15 @@ pub struct PlayerRules {
     pub max_deck_size: usize,
     pub mulligan_rule: String,
     pub first_player_rule: String,
+    /// Extra cards in the opening hand of every player who doesn't go first
+    #[serde(default)]
+    pub second_player_extra_cards: usize,
+    /// Resources every player who doesn't go first starts with (e.g. `{ mana = 1 }`)
+    #[serde(default)]
+    pub second_player_starting_resources: std::collections::HashMap<String, i32>,
+    /// Let players pay life costs that take them to zero or below
+    #[serde(default)]
+    pub allow_lethal_life_payment: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,15 +138,65 @@ pub struct ResourceDef {
     pub description: String,
     pub min_value: i32,
     pub max_value: i32,
+    /// Hard cap on how much of this resource a player can hold: gains stop
+    /// there and the rest is lost. Unset means uncapped.
+    #[serde(default)]
+    pub max: Option<i32>,
+    /// Mana symbol this resource pays in card costs (e.g. "R"); resources
+    /// without one can't be used to pay mana costs
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// When unspent amounts of this resource are lost, like a mana pool
+    #[serde(default)]
+    pub empties_at: EmptiesAt,
+}
+
+/// When the turn engine zeroes a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptiesAt {
+    /// The resource persists
+    #[default]
+    Never,
+    /// Whenever a step ends (which includes the end of each phase)
+    Step,
+    /// Whenever a phase ends
+    Phase,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnStructure {
     pub priority_system: bool,
     pub skip_first_turn_draw_for_first_player: bool,
+    /// Give every player priority after something is put on the stack, resolving
+    /// the top item only once all of them pass in succession. When false the
+    /// stack resolves as soon as no choice is pending.
+    #[serde(default)]
+    pub stack_responses: bool,
+    /// Most cards a player may draw in one turn; unlimited when unset
+    #[serde(default)]
+    pub max_draws_per_turn: Option<u32>,
+    /// What happens to draws past `max_draws_per_turn`
+    #[serde(default)]
+    pub excess_draw_policy: ExcessDrawPolicy,
+    /// Most cards of each card type a player may play in one turn, e.g.
+    /// `{ land = 1 }`; types not listed are unlimited
+    #[serde(default)]
+    pub play_limits: std::collections::BTreeMap<String, u32>,
     pub phases: Vec<PhaseDef>,
 }
 
+/// How the engine handles a draw past `TurnStructure::max_draws_per_turn`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExcessDrawPolicy {
+    /// The extra draws don't happen
+    #[default]
+    Ignore,
+    /// The player who drew past the limit loses the game
+    Lose,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhaseDef {
     pub id: String,
@@ -97,6 +213,13 @@ pub struct StepDef {
     pub order: usize,
     pub allow_actions: bool,
     pub allow_triggers: bool,
+    /// Action IDs (from `actions`) permitted in this step, e.g. `["play_quick"]`
+    /// to keep sorcery-speed plays out of combat. Unset allows every action.
+    #[serde(default)]
+    pub allowed_actions: Option<Vec<String>>,
+    /// Cards the active player draws as the step begins, e.g. 1 for a draw step
+    #[serde(default)]
+    pub draws: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,6 +254,13 @@ pub struct Keyword {
     pub description: String,
 }
 
+/// A color or faction, e.g. for deck building restrictions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorDef {
+    pub id: String,
+    pub name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WinCondition {
     pub id: String,
@@ -167,9 +297,106 @@ pub struct CardDef {
     /// Keywords this card has (must reference keyword IDs from rules.keywords)
     #[serde(default)]
     pub keywords: Vec<String>,
+    /// Colors or factions this card belongs to (must reference color IDs from rules.colors)
+    #[serde(default)]
+    pub colors: Vec<String>,
     /// Card stats (e.g., power/toughness for creatures, generic key-value pairs)
     #[serde(default)]
     pub stats: std::collections::HashMap<String, String>,
+    /// Whether the card is tapped when it enters a field zone
+    #[serde(default)]
+    pub enters_tapped: bool,
+    /// Counters placed on the card when it enters a field zone
+    #[serde(default)]
+    pub enters_with: Option<EntryCounters>,
+    /// Embedded scenarios checked by `cardinal-cli test card`
+    #[serde(default)]
+    pub tests: Vec<CardTest>,
+    /// Mana the card makes when tapped to pay a cost, written like a cost (e.g. "R")
+    #[serde(default)]
+    pub produces: Option<String>,
+    /// While on its controller's field, changes what that player's cards cost
+    #[serde(default)]
+    pub cost_modifiers: Vec<CostModifier>,
+    /// Paid on top of `cost` as the card is played, with a card the player picks
+    #[serde(default)]
+    pub additional_cost: Option<AdditionalCost>,
+}
+
+/// Something a card demands besides its cost, e.g.
+/// `additional_cost = "sacrifice_creature"`. The card's `on_play` abilities
+/// wait until it's paid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdditionalCost {
+    /// Put a creature from your field into your graveyard
+    SacrificeCreature,
+    /// Discard another card from your hand
+    Discard,
+}
+
+/// A change to the generic part of a cost, e.g. "your spells cost 1 less":
+///
+/// ```toml
+/// [[cost_modifiers]]
+/// card_type = "spell"
+/// amount = -1
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostModifier {
+    /// Only cards of this type are affected; unset affects every card
+    #[serde(default)]
+    pub card_type: Option<String>,
+    /// Added to the generic cost; negative values are reductions
+    pub amount: i32,
+}
+
+/// A mini-scenario for one of the card's abilities
+///
+/// ```toml
+/// [[tests]]
+/// name = "hits the opponent"
+/// expected = "3 damage to opponent"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardTest {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Trigger to fire (defaults to the trigger of the card's first ability)
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// Player controlling the card
+    #[serde(default)]
+    pub controller: u8,
+    /// Life totals to start from, keyed by player ID (e.g. `life = { "1" = 5 }`)
+    #[serde(default)]
+    pub life: std::collections::HashMap<String, i32>,
+    /// Expected commands in order, as one description or a list
+    /// (e.g. "3 damage to opponent", "2 life to you")
+    pub expected: ExpectedCommands,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExpectedCommands {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ExpectedCommands {
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            ExpectedCommands::One(one) => vec![one.clone()],
+            ExpectedCommands::Many(many) => many.clone(),
+        }
+    }
+}
+
+/// Counters a card arrives with, e.g. `enters_with = { counter = "+1/+1", amount = 2 }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryCounters {
+    pub counter: String,
+    pub amount: i32,
 }
 
 /// An ability on a card that can be triggered
@@ -177,9 +404,55 @@ pub struct CardDef {
 pub struct CardAbility {
     /// What triggers this ability (e.g., "etb", "on_play", "on_damage")
     pub trigger: String,
-    /// What effect to execute (e.g., "damage_2", "draw_1", "pump_1_1")
+    /// What effect to execute (e.g., "damage", "draw", "script:name")
+    #[serde(default)]
     pub effect: String,
     /// Optional parameters for the effect (e.g., amount, target)
     #[serde(default)]
     pub params: std::collections::HashMap<String, String>,
+    /// Further effects resolved in order after `effect` ("deal 2, then draw 1")
+    #[serde(default)]
+    pub effects: Vec<EffectSpec>,
+    /// Optional guard checked when the ability triggers (e.g. "life_of(opponent) <= 10")
+    #[serde(default)]
+    pub condition: Option<String>,
+    /// A mana ability: activated by tapping the card while it's on its
+    /// controller's field, and resolved at once without using the stack.
+    /// Such abilities conventionally use `trigger = "activated"`.
+    #[serde(default)]
+    pub mana_ability: bool,
+    /// Cost paid on top of tapping when the ability is activated, in the
+    /// card cost syntax (e.g. "1" or "2 life")
+    #[serde(default)]
+    pub cost: Option<String>,
+}
+
+/// One step of a multi-effect ability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectSpec {
+    /// Effect kind, same vocabulary as `CardAbility::effect`
+    pub effect: String,
+    /// Parameters for this effect only
+    #[serde(default)]
+    pub params: std::collections::HashMap<String, String>,
+    /// Guard checked when this effect resolves, so it can read what earlier
+    /// effects did (e.g. "coin_flip() == heads")
+    #[serde(default)]
+    pub condition: Option<String>,
+}
+
+impl CardAbility {
+    /// All effects of this ability in resolution order: `effect` (if set) followed by `effects`
+    pub fn effect_specs(&self) -> Vec<EffectSpec> {
+        let mut specs = Vec::new();
+        if !self.effect.is_empty() {
+            specs.push(EffectSpec {
+                effect: self.effect.clone(),
+                params: self.params.clone(),
+                condition: None,
+            });
+        }
+        specs.extend(self.effects.iter().cloned());
+        specs
+    }
 }
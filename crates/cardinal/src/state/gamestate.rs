@@ -1,7 +1,12 @@
 use crate::ids::{PlayerId, ZoneId, PhaseId, StepId, CardId};
-use crate::model::command::{PendingChoice, StackItem};
+use crate::model::action::TargetRef;
+use crate::model::command::{Command, PendingChoice, StackItem};
+use crate::model::event::CoinFlip;
 use crate::rules::schema::Ruleset;
-use std::collections::HashMap;
+use crate::util::rng::GameRng;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
 
 #[derive(Debug, Clone)]
 pub struct GameState {
@@ -12,7 +17,29 @@ pub struct GameState {
     pub pending_choice: Option<PendingChoice>,
     pub ended: Option<GameEnd>,
     /// Card instance data (stats, counters, keywords)
-    pub card_instances: HashMap<CardId, CardInstanceData>,
+    pub card_instances: BTreeMap<CardId, CardInstanceData>,
+    /// Prevention shields waiting for damage, oldest first
+    pub damage_shields: Vec<DamageShield>,
+    /// Result of the most recent coin flip, for effects that depend on it
+    pub last_coin_flip: Option<CoinFlip>,
+    /// Randomness for shuffles during play. Initialization hands over the
+    /// stream it used for setup, so a seed replays the whole game.
+    pub rng: GameRng,
+    /// Most of each capped resource a player can hold, from the ruleset's
+    /// `[[resources]]` `max`; gains past it are lost
+    pub resource_caps: BTreeMap<String, i32>,
+    /// Most cards a hand can hold, from the ruleset's `max_hand_size`;
+    /// draws past it don't happen
+    pub max_hand_size: Option<usize>,
+    /// Players owed an extra turn, taken in order (first granted, first
+    /// taken) before play passes on as usual
+    pub extra_turns: Vec<PlayerId>,
+    /// Steps and phases (by ID) players will skip the next time they would
+    /// begin them on their own turn; each skip is used up once applied
+    pub skips: Vec<(PlayerId, String)>,
+    /// Cleanups that undo "until end of turn" effects (e.g. stolen
+    /// control), applied in order as the turn ends
+    pub end_of_turn: Vec<Command>,
 }
 
 #[derive(Debug, Clone)]
@@ -30,7 +57,19 @@ pub struct PlayerState {
     pub id: PlayerId,
     pub life: i32,
     /// Named resources (e.g., "mana", "action_points")
-    pub resources: HashMap<String, i32>,
+    pub resources: BTreeMap<String, i32>,
+    /// Cards drawn so far this turn; reset when the turn passes
+    pub draws_this_turn: u32,
+    /// Cards played so far this turn by card type (see
+    /// `TurnStructure::play_limits`); reset when the turn passes
+    pub plays_this_turn: BTreeMap<String, u32>,
+    /// Hand cards shown to the other players this turn; cleared when the turn passes
+    pub revealed: Vec<CardId>,
+    /// Hand cards shown to a single other player this turn, as (viewer,
+    /// card); cleared when the turn passes
+    pub revealed_to: Vec<(PlayerId, CardId)>,
+    /// The player conceded; they've lost and can't win
+    pub conceded: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -46,30 +85,154 @@ pub struct GameEnd {
     pub reason: String,
 }
 
+/// Prevents up to `amount` of the next damage dealt to `target`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageShield {
+    pub target: TargetRef,
+    pub amount: i32,
+    /// Only absorbs combat damage
+    pub combat_only: bool,
+}
+
 /// Runtime data for a card instance
 #[derive(Debug, Clone)]
 pub struct CardInstanceData {
     /// Current stats (e.g., "power", "toughness", "range")
-    pub stats: HashMap<String, String>,
+    pub stats: BTreeMap<String, String>,
     /// Temporary stat modifiers (power/toughness pumps, etc.)
-    pub stat_modifiers: HashMap<String, i32>,
+    pub stat_modifiers: BTreeMap<String, i32>,
     /// Keywords the card currently has
     pub keywords: Vec<String>,
+    /// Stat modifiers that last until end of turn
+    pub temporary_stat_modifiers: BTreeMap<String, i32>,
+    /// Keywords granted until end of turn
+    pub temporary_keywords: Vec<String>,
     /// Counters on the card (e.g., "+1/+1", "charge")
-    pub counters: HashMap<String, i32>,
+    pub counters: BTreeMap<String, i32>,
+    /// Whether the card is tapped
+    pub tapped: bool,
+    /// The definition the card uses after a transform; `None` for its own
+    pub definition: Option<CardId>,
+    /// The player who gained control of the card while it's on a field;
+    /// `None` leaves it with the owner of the zone it's in
+    pub controller: Option<PlayerId>,
 }
 
 impl GameState {
+    /// Up to `n` cards from the top (front) of a zone, without changing it.
+    /// Returns fewer when the zone holds fewer, and none for an unknown zone.
+    pub fn peek_zone(&self, zone: &str, n: usize) -> &[CardId] {
+        self.zones.iter()
+            .find(|z| z.id.0 == zone)
+            .map(|z| &z.cards[..n.min(z.cards.len())])
+            .unwrap_or(&[])
+    }
+
+    /// The card that most recently arrived in a zone that keeps arrival
+    /// order, like a graveyard (e.g. "the last creature that died"). `None`
+    /// for an empty or unknown zone.
+    pub fn last_in_zone(&self, zone: &str) -> Option<CardId> {
+        self.zones.iter()
+            .find(|z| z.id.0 == zone)
+            .and_then(|z| z.cards.last().copied())
+    }
+
+    /// The player whose zone `card` is in. Cards stay in their owner's zones
+    /// when someone else gains control of them, so this is where they go
+    /// back to (e.g. "return to its owner's hand").
+    pub fn owner_of(&self, card: CardId) -> Option<PlayerId> {
+        self.zones.iter()
+            .find(|z| z.cards.contains(&card))
+            .and_then(|z| z.owner)
+    }
+
+    /// The player who controls `card`: whoever gained control of it,
+    /// otherwise its owner (see `owner_of`)
+    pub fn controller_of(&self, card: CardId) -> Option<PlayerId> {
+        self.card_instances.get(&card)
+            .and_then(|i| i.controller)
+            .or_else(|| self.owner_of(card))
+    }
+
+    /// The cards on any field that `player` controls, in zone order
+    pub fn controlled_cards(&self, player: PlayerId) -> Vec<CardId> {
+        self.zones.iter()
+            .filter(|z| z.id.0.starts_with("field"))
+            .flat_map(|z| z.cards.iter().copied())
+            .filter(|card| self.controller_of(*card) == Some(player))
+            .collect()
+    }
+
+    /// The ID of the definition `card` currently uses: its own unless it
+    /// has transformed
+    pub fn definition_of(&self, card: CardId) -> CardId {
+        self.card_instances.get(&card)
+            .and_then(|i| i.definition)
+            .unwrap_or(card)
+    }
+
+    /// `owner`'s hand as `viewer` sees it, in hand order: `None` for each
+    /// card that is hidden from them. Owners see their whole hand; other
+    /// players only see cards the owner revealed this turn, to everyone or
+    /// to them alone.
+    pub fn hand_view(&self, owner: PlayerId, viewer: PlayerId) -> Vec<Option<CardId>> {
+        let hand_id = format!("hand@{}", owner.0);
+        let owner_state = self.players.iter().find(|p| p.id == owner);
+        let revealed = |card: &CardId| owner_state.is_some_and(|p| {
+            p.revealed.contains(card) || p.revealed_to.contains(&(viewer, *card))
+        });
+        self.zones.iter()
+            .find(|z| z.id.0 == hand_id)
+            .map(|z| z.cards.iter()
+                .map(|card| (owner == viewer || revealed(card)).then_some(*card))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// A SHA-256 digest (hex) of the whole state, including the RNG position.
+    /// Equal states give equal fingerprints however they were reached, so two
+    /// engines can cheaply check they agree.
+    pub fn fingerprint(&self) -> String {
+        let players: Vec<_> = self.players.iter()
+            .map(|p| (p.id, p.life, &p.resources, p.draws_this_turn, &p.plays_this_turn, &p.revealed, &p.revealed_to, p.conceded))
+            .collect();
+
+        let canonical = format!(
+            "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}",
+            self.turn,
+            players,
+            self.zones,
+            self.stack,
+            self.pending_choice,
+            self.ended,
+            self.card_instances,
+            self.damage_shields,
+            self.last_coin_flip,
+            self.rng,
+            self.resource_caps,
+            self.max_hand_size,
+            self.extra_turns,
+            self.skips,
+            self.end_of_turn,
+        );
+        format!("{:x}", Sha256::digest(canonical.as_bytes()))
+    }
+
     /// Build an initial `GameState` from a `Ruleset`. This is intentionally conservative
     /// and does not shuffle or populate decks; it just creates players, zones, and a starting turn.
     pub fn from_ruleset(rules: &Ruleset) -> Self {
-        let min_players = rules.players.min_players as usize;
+        let min_players = rules.players.min_players;
         let mut players = Vec::new();
         for i in 0..min_players {
             players.push(PlayerState { 
                 id: PlayerId(i as u8), 
                 life: rules.players.starting_life,
-                resources: HashMap::new(),
+                resources: BTreeMap::new(),
+                draws_this_turn: 0,
+                plays_this_turn: BTreeMap::new(),
+                revealed: Vec::new(),
+                revealed_to: Vec::new(),
+                conceded: false,
             });
         }
 
@@ -115,7 +278,17 @@ impl GameState {
             stack: Vec::new(),
             pending_choice: None,
             ended: None,
-            card_instances: HashMap::new(),
+            card_instances: BTreeMap::new(),
+            damage_shields: Vec::new(),
+            last_coin_flip: None,
+            rng: GameRng::new(0),
+            resource_caps: rules.resources.iter()
+                .filter_map(|r| r.max.map(|max| (r.id.clone(), max)))
+                .collect(),
+            max_hand_size: Some(rules.players.max_hand_size),
+            extra_turns: Vec::new(),
+            skips: Vec::new(),
+            end_of_turn: Vec::new(),
         }
     }
 }
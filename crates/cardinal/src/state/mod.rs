@@ -1,3 +1,4 @@
 pub mod gamestate;
+pub mod view;
 pub mod zones;
 
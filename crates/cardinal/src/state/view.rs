@@ -0,0 +1,138 @@
+//! What one player can see of a game, shaped for UIs rather than the engine
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::core::GameEngine,
+    ids::PlayerId,
+    model::command::{EffectRef, StackItem},
+    testing::describe_command,
+};
+
+/// A snapshot of the game from `viewer`'s seat. Hidden cards (other players'
+/// unrevealed hand cards) are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameStateView {
+    pub viewer: u8,
+    pub turn: u32,
+    pub phase: String,
+    pub step: String,
+    pub active_player: u8,
+    pub priority_player: u8,
+    pub players: Vec<PlayerView>,
+    /// The stack, bottom first: the last entry resolves next
+    pub stack: Vec<StackEntryView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub id: u8,
+    pub life: i32,
+    pub resources: BTreeMap<String, i32>,
+    /// Hand card IDs in hand order, `None` where hidden from the viewer
+    pub hand: Vec<Option<u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackEntryView {
+    pub id: u32,
+    pub source: Option<u32>,
+    /// Name of the source card's definition, if it has one
+    pub source_name: Option<String>,
+    pub controller: u8,
+    /// What resolving the entry will do, e.g. "3 damage to you"
+    pub effect: String,
+}
+
+/// A [`GameStateView`] with nothing hidden, for debugging card interactions.
+/// It derefs to the view but deliberately isn't `Serialize`, so it can't be
+/// sent to a client by mistake.
+#[derive(Debug, Clone)]
+pub struct RevealedView(GameStateView);
+
+impl std::ops::Deref for RevealedView {
+    type Target = GameStateView;
+
+    fn deref(&self) -> &GameStateView {
+        &self.0
+    }
+}
+
+impl GameStateView {
+    pub fn new(engine: &GameEngine, viewer: PlayerId) -> Self {
+        Self::build(engine, viewer, false)
+    }
+
+    /// `viewer`'s view with every player's hand shown
+    pub fn reveal_all(engine: &GameEngine, viewer: PlayerId) -> RevealedView {
+        RevealedView(Self::build(engine, viewer, true))
+    }
+
+    fn build(engine: &GameEngine, viewer: PlayerId, reveal_all: bool) -> Self {
+        let state = &engine.state;
+        Self {
+            viewer: viewer.0,
+            turn: state.turn.number,
+            phase: state.turn.phase.0.to_string(),
+            step: state.turn.step.0.to_string(),
+            active_player: state.turn.active_player.0,
+            priority_player: state.turn.priority_player.0,
+            players: state.players.iter()
+                .map(|p| PlayerView {
+                    id: p.id.0,
+                    life: p.life,
+                    resources: p.resources.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+                    // Every player sees their own hand
+                    hand: state.hand_view(p.id, if reveal_all { p.id } else { viewer })
+                        .into_iter()
+                        .map(|c| c.map(|c| c.0))
+                        .collect(),
+                })
+                .collect(),
+            stack: state.stack.iter().map(|item| stack_entry(engine, item, viewer)).collect(),
+        }
+    }
+}
+
+fn stack_entry(engine: &GameEngine, item: &StackItem, viewer: PlayerId) -> StackEntryView {
+    let source_name = item.source
+        .and_then(|card| engine.cards.get(&engine.state.definition_of(card).0))
+        .map(|def| def.name.clone());
+
+    StackEntryView {
+        id: item.id,
+        source: item.source.map(|c| c.0),
+        source_name,
+        controller: item.controller.0,
+        effect: effect_label(engine, item, viewer),
+    }
+}
+
+/// The commands the item would produce if it resolved now, described from
+/// `viewer`'s side; the raw effect name when that can't be worked out yet
+/// (e.g. a target still to be chosen)
+fn effect_label(engine: &GameEngine, item: &StackItem, viewer: PlayerId) -> String {
+    let commands = crate::engine::effect_executor::execute_effect_with_cards(
+        &item.effect,
+        item.source,
+        item.controller,
+        &engine.state,
+        Some(&engine.scripting),
+        Some(&engine.cards),
+        Some(&engine.builtins),
+    );
+    match commands {
+        Ok(commands) if !commands.is_empty() => commands.iter()
+            .map(|c| describe_command(c, &engine.state, viewer))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => match &item.effect {
+            EffectRef::Builtin(name) => name.to_string(),
+            EffectRef::Scripted(name) => format!("script {}", name),
+            EffectRef::Sequence(effects) => format!("sequence of {}", effects.len()),
+            EffectRef::Conditional(condition, _) => format!("if {}", condition),
+        },
+    }
+}
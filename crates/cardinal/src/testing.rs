@@ -4,10 +4,14 @@
 //! without requiring a full UI implementation.
 
 use anyhow::{Context, Result};
+use rayon::prelude::*;
 use std::path::Path;
 
-use crate::{GameEngine, GameState, Action, load_game_config};
-use crate::ids::PlayerId;
+use crate::{GameEngine, GameState, GameRng, Action, Ruleset, load_game_config};
+use crate::ids::{CardId, PlayerId};
+use crate::model::action::TargetRef;
+use crate::model::command::{ChoiceKind, Command};
+use crate::engine::effect_executor::TraceStep;
 use crate::error::CardinalError;
 
 /// Test configuration options
@@ -18,6 +22,8 @@ pub struct TestOptions {
     pub starting_hand_size: usize,
     /// Enable verbose output
     pub verbose: bool,
+    /// Shuffle decks during setup; off keeps test decks in order
+    pub shuffle: bool,
 }
 
 impl Default for TestOptions {
@@ -26,6 +32,7 @@ impl Default for TestOptions {
             seed: 42,
             starting_hand_size: 5,
             verbose: false,
+            shuffle: true,
         }
     }
 }
@@ -50,6 +57,9 @@ pub fn init_test_game<P: AsRef<Path>>(
         println!("Initializing test game...");
         println!("  Rules: {}", rules_path.display());
         println!("  Seed: {}", options.seed);
+        if !options.shuffle {
+            println!("  Shuffle: off (decks stay in order)");
+        }
     }
 
     // Load game configuration
@@ -73,10 +83,15 @@ pub fn init_test_game<P: AsRef<Path>>(
     }
 
     // Initialize game
-    let state = crate::initialize_game(state, &ruleset, options.seed);
+    let init_options = crate::InitOptions { shuffle_decks: options.shuffle };
+    let (state, _events, report) =
+        crate::initialize_game_with_report(state, &ruleset, options.seed, &init_options);
 
     if options.verbose {
         println!("  ✓ Game initialized");
+        for line in report.to_string().lines() {
+            println!("    {}", line);
+        }
     }
 
     // Create engine
@@ -159,6 +174,427 @@ pub fn run_basic_test<P: AsRef<Path>>(
     Ok(summary)
 }
 
+/// Outcome of one embedded card test
+#[derive(Debug, Clone)]
+pub struct CardTestResult {
+    pub name: String,
+    pub passed: bool,
+    pub expected: Vec<String>,
+    /// Descriptions of the commands the ability actually produced
+    pub actual: Vec<String>,
+    /// Set when the ability couldn't be executed at all
+    pub error: Option<String>,
+    /// Messages the card's scripts passed to `log`
+    pub log: Vec<String>,
+    /// How the ability's effects were worked out (see `execute_effect_traced`)
+    pub trace: Vec<TraceStep>,
+}
+
+/// Run the `[[tests]]` embedded in a card's definition
+///
+/// Each test fires one trigger of the card for its controller, resolves the
+/// resulting stack items and compares the produced commands, described with
+/// `describe_command`, against the expected list in order.
+pub fn run_card_tests(ruleset: &Ruleset, card_id: &str) -> Result<Vec<CardTestResult>> {
+    let card = ruleset.cards.iter()
+        .find(|c| c.id == card_id)
+        .ok_or_else(|| anyhow::anyhow!("Card '{}' not found", card_id))?;
+    let numeric_id: u32 = card.id.parse()
+        .with_context(|| format!("Card '{}' needs a numeric ID to run its tests", card_id))?;
+
+    let engine = GameEngine::from_ruleset(ruleset.clone(), 0);
+    let mut results = Vec::new();
+
+    for (index, test) in card.tests.iter().enumerate() {
+        let name = test.name.clone().unwrap_or_else(|| format!("test {}", index + 1));
+        let expected = test.expected.to_vec();
+        let controller = PlayerId(test.controller);
+
+        let mut state = engine.state.clone();
+        for (player, life) in &test.life {
+            let player: u8 = player.parse()
+                .with_context(|| format!("Test '{}' has invalid player ID '{}' in life", name, player))?;
+            if let Some(p) = state.players.iter_mut().find(|p| p.id.0 == player) {
+                p.life = *life;
+            }
+        }
+
+        let trigger = test.trigger.clone()
+            .or_else(|| card.abilities.first().map(|a| a.trigger.clone()))
+            .unwrap_or_default();
+        let commands = crate::engine::cards::generate_ability_commands(
+            CardId(numeric_id),
+            &trigger,
+            controller,
+            &engine.cards,
+            &engine.builtins,
+            &state,
+            &mut 1,
+            &mut 1,
+        );
+
+        let mut actual = Vec::new();
+        let mut error = None;
+        let mut log = Vec::new();
+        let mut trace = Vec::new();
+        for command in &commands {
+            match command {
+                Command::PushStack { item } => match crate::engine::effect_executor::execute_effect_traced(
+                    &item.effect,
+                    item.source,
+                    item.controller,
+                    &state,
+                    Some(&engine.scripting),
+                    Some(&engine.cards),
+                    Some(&engine.builtins),
+                    true,
+                ) {
+                    Ok(resolved) => {
+                        actual.extend(resolved.commands.iter().map(|c| describe_command(c, &state, controller)));
+                        log.extend(resolved.log);
+                        trace.extend(resolved.trace);
+                    }
+                    Err(e) => error = Some(e.0),
+                },
+                other => actual.push(describe_command(other, &state, controller)),
+            }
+        }
+
+        let passed = error.is_none()
+            && actual.len() == expected.len()
+            && actual.iter().zip(&expected).all(|(a, e)| normalize_description(a) == normalize_description(e));
+        results.push(CardTestResult { name, passed, expected, actual, error, log, trace });
+    }
+
+    Ok(results)
+}
+
+/// Describe a command the way card tests spell their expectations
+///
+/// Life changes read as "3 damage to opponent" or "2 life to you", and damage
+/// to a card as "3 damage to card 7". Players are "you" (the controller),
+/// "opponent" (the next player in seat order) or "player N". Other commands
+/// fall back to their debug form.
+pub fn describe_command(command: &Command, state: &GameState, controller: PlayerId) -> String {
+    let who = |player: PlayerId| {
+        if player == controller {
+            "you".to_string()
+        } else if crate::rules::query::opponents_of(state, controller).first() == Some(&player) {
+            "opponent".to_string()
+        } else {
+            format!("player {}", player.0)
+        }
+    };
+
+    match command {
+        Command::DealDamage { target: TargetRef::Player(player), amount, .. } => {
+            format!("{} damage to {}", amount, who(*player))
+        }
+        Command::DealDamage { target: TargetRef::Card(card), amount, .. } => {
+            format!("{} damage to card {}", amount, card.0)
+        }
+        Command::ChangeLife { player, delta } if *delta < 0 => format!("{} damage to {}", -delta, who(*player)),
+        Command::ChangeLife { player, delta } => format!("{} life to {}", delta, who(*player)),
+        Command::ModifyStat { card, stat_name, delta } if stat_name == "damage" => {
+            format!("{} damage to card {}", delta, card.0)
+        }
+        Command::RequestChoice { choice, .. } => format!("choice: {}", choice.prompt),
+        other => format!("{:?}", other),
+    }
+}
+
+fn normalize_description(description: &str) -> String {
+    description.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Options for a batch of simulated games
+#[derive(Debug, Clone)]
+pub struct SimulationOptions {
+    /// Seed of the first game; game `i` uses `base_seed + i`
+    pub base_seed: u64,
+    /// Number of games to play
+    pub games: usize,
+    /// Actions after which an unfinished game is abandoned as a draw
+    pub max_actions: usize,
+    /// Games per batch when measuring how much win rates swing between batches
+    pub batch_size: usize,
+    /// Length variation and win-rate deviation both at or below this mark the
+    /// game as near-deterministic
+    pub deterministic_below: f64,
+    /// Length variation or win-rate deviation at or above this marks the game
+    /// as wildly random
+    pub random_above: f64,
+    /// Worker threads to spread games across; 1 runs serially and 0 uses
+    /// rayon's global pool, one thread per available core. The report is the same whatever the count.
+    pub threads: usize,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            base_seed: 42,
+            games: 100,
+            max_actions: 500,
+            batch_size: 10,
+            deterministic_below: 0.05,
+            random_above: 0.5,
+            threads: 1,
+        }
+    }
+}
+
+/// What happened in one simulated game
+#[derive(Debug, Clone)]
+pub struct GameOutcome {
+    pub seed: u64,
+    /// Winner, or `None` for a draw or an abandoned game
+    pub winner: Option<PlayerId>,
+    /// Number of actions applied
+    pub length: usize,
+    /// Whether the game ended before `max_actions`
+    pub finished: bool,
+}
+
+/// How swingy a game looked across a batch of simulations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Volatility {
+    /// Games play out (almost) the same whatever the seed
+    NearDeterministic,
+    Normal,
+    /// Outcomes vary wildly from seed to seed
+    WildlyRandom,
+}
+
+/// Aggregate results of `run_simulations`
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub outcomes: Vec<GameOutcome>,
+    /// Wins per player, indexed by player ID
+    pub wins: Vec<usize>,
+    /// Mean game length in actions
+    pub mean_length: f64,
+    /// Population variance of game length
+    pub length_variance: f64,
+    /// Standard deviation of player 0's win rate across batches of `batch_size` games
+    pub win_rate_std_dev: f64,
+    pub volatility: Volatility,
+}
+
+/// Play `options.games` games with seeded random legal actions and report
+/// win counts and how much game length and win rate vary between seeds
+///
+/// Each player's deck is dealt from the ruleset's numeric card IDs in turn.
+/// The same ruleset and options always produce the same report: every game
+/// is seeded from its index alone, so `options.threads` only changes how fast
+/// the batch runs. Parallel runs go through rayon, on its global pool for a
+/// thread count of 0 and on a pool of their own otherwise.
+pub fn run_simulations(ruleset: &Ruleset, options: &SimulationOptions) -> SimulationReport {
+    let play = |i: usize| simulate_game(ruleset, options.base_seed.wrapping_add(i as u64), options.max_actions);
+
+    let outcomes = match options.threads {
+        1 => (0..options.games).map(play).collect(),
+        // rayon's indexed collect keeps outcomes in game order
+        0 => (0..options.games).into_par_iter().map(play).collect(),
+        n => rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build()
+            .expect("failed to start the simulation threads")
+            .install(|| (0..options.games).into_par_iter().map(play).collect()),
+    };
+
+    summarize_simulations(outcomes, ruleset.players.min_players, options)
+}
+
+fn summarize_simulations(
+    outcomes: Vec<GameOutcome>,
+    num_players: usize,
+    options: &SimulationOptions,
+) -> SimulationReport {
+    let mut wins = vec![0; num_players];
+    for winner in outcomes.iter().filter_map(|o| o.winner) {
+        if let Some(count) = wins.get_mut(winner.0 as usize) {
+            *count += 1;
+        }
+    }
+
+    let lengths: Vec<f64> = outcomes.iter().map(|o| o.length as f64).collect();
+    let (mean_length, length_variance) = mean_and_variance(&lengths);
+
+    let batch_win_rates: Vec<f64> = outcomes
+        .chunks(options.batch_size.max(1))
+        .map(|batch| {
+            let won = batch.iter().filter(|o| o.winner == Some(PlayerId(0))).count();
+            won as f64 / batch.len() as f64
+        })
+        .collect();
+    let win_rate_std_dev = if batch_win_rates.len() < 2 {
+        0.0
+    } else {
+        mean_and_variance(&batch_win_rates).1.sqrt()
+    };
+
+    // Coefficient of variation keeps long and short games comparable
+    let length_variation = if mean_length > 0.0 { length_variance.sqrt() / mean_length } else { 0.0 };
+    let volatility = if length_variation >= options.random_above || win_rate_std_dev >= options.random_above {
+        Volatility::WildlyRandom
+    } else if length_variation <= options.deterministic_below && win_rate_std_dev <= options.deterministic_below {
+        Volatility::NearDeterministic
+    } else {
+        Volatility::Normal
+    };
+
+    SimulationReport { outcomes, wins, mean_length, length_variance, win_rate_std_dev, volatility }
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance)
+}
+
+/// Play one game to completion (or `max_actions`) with seeded random choices
+fn simulate_game(ruleset: &Ruleset, seed: u64, max_actions: usize) -> GameOutcome {
+    let mut state = GameState::from_ruleset(ruleset);
+    populate_simulation_decks(&mut state, ruleset);
+    let state = crate::initialize_game(state, ruleset, seed);
+    let mut engine = GameEngine::new(ruleset.clone(), seed, state);
+    let mut rng = GameRng::new(seed);
+
+    let mut length = 0;
+    while engine.state.ended.is_none() && length < max_actions {
+        let Some((player, action)) = choose_random_action(&engine, &mut rng) else {
+            break;
+        };
+        if engine.apply_action(player, action).is_err() {
+            break;
+        }
+        length += 1;
+    }
+
+    GameOutcome {
+        seed,
+        winner: engine.state.ended.as_ref().and_then(|end| end.winner),
+        length,
+        finished: engine.state.ended.is_some(),
+    }
+}
+
+/// Deal the ruleset's numeric card IDs round-robin into the players' decks
+fn populate_simulation_decks(state: &mut GameState, ruleset: &Ruleset) {
+    let mut card_ids: Vec<u32> = ruleset.cards.iter()
+        .filter_map(|c| c.id.parse().ok())
+        .collect();
+    card_ids.sort_unstable();
+
+    let num_players = state.players.len().max(1);
+    for (i, card) in card_ids.into_iter().enumerate() {
+        let deck_zone_id = format!("deck@{}", i % num_players);
+        if let Some(deck) = state.zones.iter_mut().find(|z| z.id.0 == deck_zone_id) {
+            deck.cards.push(CardId(card));
+        }
+    }
+}
+
+/// Pick a random legal action: answer a pending choice, otherwise play a
+/// card from hand or pass priority
+fn choose_random_action(engine: &GameEngine, rng: &mut GameRng) -> Option<(PlayerId, Action)> {
+    let legal = |player, action: &Action| crate::engine::legality::validate(engine, player, action).is_ok();
+
+    if let Some(choice) = &engine.state.pending_choice {
+        // Orderings and card selections: whoever may answer picks one of their answers
+        if !matches!(choice.kind, ChoiceKind::ChooseTarget { .. }) {
+            return engine.state.players.iter()
+                .find_map(|p| pick(rng, engine.legal_actions(p.id)).map(|action| (p.id, action)));
+        }
+        let chooser = choice.stack_item
+            .and_then(|id| engine.state.stack.iter().find(|item| item.id == id))
+            .map(|item| item.controller)
+            .unwrap_or(engine.state.turn.active_player);
+        let targets = engine.state.players.iter()
+            .map(|p| TargetRef::Player(p.id))
+            .chain(engine.state.zones.iter()
+                .flat_map(|z| z.cards.iter().map(|c| TargetRef::Card(*c))));
+        let options: Vec<Action> = targets
+            .map(|target| Action::ChooseTarget { choice_id: choice.id, target })
+            .filter(|action| legal(chooser, action))
+            .collect();
+        return pick(rng, options).map(|action| (chooser, action));
+    }
+
+    let player = engine.state.turn.priority_player;
+    let hand_id = format!("hand@{}", player.0);
+    let mut options = vec![Action::PassPriority];
+    if let Some(hand) = engine.state.zones.iter().find(|z| z.id.0 == hand_id) {
+        options.extend(hand.cards.iter()
+            .map(|card| Action::PlayCard { card: *card, from: hand.id.clone() })
+            .filter(|action| legal(player, action)));
+    }
+    pick(rng, options).map(|action| (player, action))
+}
+
+/// Where cards ended up over a batch of seeded shuffles
+#[derive(Debug, Clone)]
+pub struct DistributionReport {
+    /// Number of shuffles run, one per seed
+    pub shuffles: usize,
+    pub deck_size: usize,
+    /// `counts[card][position]`: how often the card that started at index
+    /// `card` ended up at `position`
+    pub counts: Vec<Vec<usize>>,
+    /// What every count would be under a perfectly uniform shuffle
+    pub expected: f64,
+    /// Largest relative difference between any count and `expected`
+    pub max_deviation: f64,
+    /// Pearson's chi-squared statistic over all card/position counts
+    pub chi_squared: f64,
+}
+
+impl DistributionReport {
+    /// Whether every count is within `tolerance` (a fraction, e.g. 0.1) of `expected`
+    pub fn is_uniform(&self, tolerance: f64) -> bool {
+        self.max_deviation <= tolerance
+    }
+}
+
+/// Shuffle a `deck_size`-card deck with `GameRng::shuffle` once per seed in
+/// `seeds` and count where each card lands, to check the shuffle is fair
+pub fn shuffle_distribution(seeds: std::ops::Range<u64>, deck_size: usize) -> DistributionReport {
+    let mut counts = vec![vec![0usize; deck_size]; deck_size];
+    let mut shuffles = 0;
+    for seed in seeds {
+        let mut deck: Vec<usize> = (0..deck_size).collect();
+        GameRng::new(seed).shuffle(&mut deck);
+        for (position, card) in deck.into_iter().enumerate() {
+            counts[card][position] += 1;
+        }
+        shuffles += 1;
+    }
+
+    let expected = if deck_size == 0 { 0.0 } else { shuffles as f64 / deck_size as f64 };
+    let cells = counts.iter().flatten().map(|&count| count as f64);
+    let (max_deviation, chi_squared) = if expected > 0.0 {
+        cells.fold((0.0f64, 0.0), |(max, chi), count| {
+            let diff = count - expected;
+            (max.max(diff.abs() / expected), chi + diff * diff / expected)
+        })
+    } else {
+        (0.0, 0.0)
+    };
+
+    DistributionReport { shuffles, deck_size, counts, expected, max_deviation, chi_squared }
+}
+
+fn pick<T>(rng: &mut GameRng, mut options: Vec<T>) -> Option<T> {
+    if options.is_empty() {
+        return None;
+    }
+    let index = rng.generate::<u64>() as usize % options.len();
+    Some(options.swap_remove(index))
+}
+
 /// Populate test decks with cards
 fn populate_test_decks(state: &mut GameState, num_cards: usize) {
     let num_players = state.players.len() as u8;
@@ -240,4 +676,121 @@ mod tests {
         assert_eq!(options.starting_hand_size, 5);
         assert!(!options.verbose);
     }
+
+    fn load_repo_rules() -> Ruleset {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        load_game_config(root.join("rules.toml"), None).expect("repo rules load")
+    }
+
+    fn rules_with_bolt(expected: &str) -> Ruleset {
+        let mut rules = load_repo_rules();
+        let bolt = format!(r#"
+            id = "930"
+            name = "Test Bolt"
+            card_type = "spell"
+
+            [[abilities]]
+            trigger = "on_play"
+            effect = "damage"
+            params = {{ amount = "3", target = "opponent" }}
+
+            [[tests]]
+            name = "hits the opponent"
+            expected = "{}"
+        "#, expected);
+        rules.cards.push(toml::from_str(&bolt).unwrap());
+        rules
+    }
+
+    #[test]
+    fn test_embedded_card_test_passes() {
+        let results = run_card_tests(&rules_with_bolt("3 damage to opponent"), "930").unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "hits the opponent");
+        assert!(results[0].passed, "actual: {:?}", results[0].actual);
+    }
+
+    #[test]
+    fn test_embedded_card_test_wrong_expectation_fails() {
+        let results = run_card_tests(&rules_with_bolt("3 damage to you"), "930").unwrap();
+
+        assert!(!results[0].passed);
+        assert_eq!(results[0].actual, vec!["3 damage to opponent".to_string()]);
+        assert!(run_card_tests(&rules_with_bolt("x"), "missing").is_err());
+    }
+
+    #[test]
+    fn test_simulation_variance_fields() {
+        let rules = load_repo_rules();
+        let options = SimulationOptions { games: 20, max_actions: 60, batch_size: 5, ..Default::default() };
+
+        let report = run_simulations(&rules, &options);
+
+        assert_eq!(report.outcomes.len(), 20);
+        assert!(report.mean_length > 0.0);
+        assert!(report.length_variance >= 0.0);
+        assert!(report.win_rate_std_dev >= 0.0);
+        assert!(report.wins.iter().sum::<usize>() <= 20);
+
+        // Same seeds, same report
+        let again = run_simulations(&rules, &options);
+        assert_eq!(again.length_variance, report.length_variance);
+        assert_eq!(again.volatility, report.volatility);
+    }
+
+    #[test]
+    fn test_parallel_simulations_match_serial() {
+        let rules = load_repo_rules();
+        let serial = SimulationOptions { games: 12, max_actions: 60, batch_size: 4, ..Default::default() };
+        let expected = run_simulations(&rules, &serial);
+
+        for threads in [0, 2, 5] {
+            let report = run_simulations(&rules, &SimulationOptions { threads, ..serial.clone() });
+            let summary = |r: &SimulationReport| -> Vec<(u64, Option<PlayerId>, usize)> {
+                r.outcomes.iter().map(|o| (o.seed, o.winner, o.length)).collect()
+            };
+            assert_eq!(summary(&report), summary(&expected), "threads = {}", threads);
+            assert_eq!(report.wins, expected.wins);
+            assert_eq!(report.length_variance, expected.length_variance);
+            assert_eq!(report.win_rate_std_dev, expected.win_rate_std_dev);
+        }
+    }
+
+    #[test]
+    fn test_shuffle_distribution_is_uniform() {
+        let report = shuffle_distribution(0..20_000, 8);
+
+        assert_eq!(report.shuffles, 20_000);
+        assert_eq!(report.expected, 2_500.0);
+        // Every card lands in every position, each about 1/8 of the time
+        for card in &report.counts {
+            assert_eq!(card.iter().sum::<usize>(), 20_000);
+        }
+        assert!(report.is_uniform(0.1), "max deviation {}", report.max_deviation);
+        // 49 degrees of freedom; 100 is far beyond any plausible fair outcome
+        assert!(report.chi_squared < 100.0, "chi-squared {}", report.chi_squared);
+    }
+
+    #[test]
+    fn test_volatility_flags() {
+        let outcome = |winner: Option<u8>, length| GameOutcome {
+            seed: 0,
+            winner: winner.map(PlayerId),
+            length,
+            finished: winner.is_some(),
+        };
+        let options = SimulationOptions { batch_size: 2, ..Default::default() };
+
+        let same = (0..4).map(|_| outcome(Some(0), 10)).collect();
+        let report = summarize_simulations(same, 2, &options);
+        assert_eq!(report.length_variance, 0.0);
+        assert_eq!(report.volatility, Volatility::NearDeterministic);
+
+        let swingy = vec![outcome(Some(0), 2), outcome(Some(0), 40), outcome(Some(1), 3), outcome(Some(1), 90)];
+        let report = summarize_simulations(swingy, 2, &options);
+        assert_eq!(report.wins, vec![2, 2]);
+        assert!((report.win_rate_std_dev - 0.5).abs() < 1e-9);
+        assert_eq!(report.volatility, Volatility::WildlyRandom);
+    }
 }
@@ -1,6 +1,7 @@
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
 
+#[derive(Debug, Clone)]
 pub struct GameRng {
     rng: Pcg64,
 }
@@ -18,4 +19,12 @@ impl GameRng {
     {
         self.rng.r#gen()
     }
+
+    /// Fisher-Yates shuffle in place
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j: usize = self.generate::<u32>() as usize % (i + 1);
+            items.swap(i, j);
+        }
+    }
 }
@@ -16,6 +16,7 @@ use std::path::Path;
 use crate::rules::schema::{Ruleset, CardDef};
 use crate::rules::card_loader::{load_cards_from_dir, load_cards_from_file, validate_unique_card_ids};
 use crate::pack::metadata::PackMeta;
+use crate::pack::builder::{collect_files, PackBuildOptions};
 
 /// Validation result with detailed diagnostics
 #[derive(Debug)]
@@ -73,10 +74,10 @@ pub fn validate_rules<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read rules file: {}", path.display()))?;
 
-    let ruleset: Ruleset = match toml::from_str(&content) {
+    let ruleset: Ruleset = match crate::rules::card_loader::parse_with_templates(&content) {
         Ok(r) => r,
         Err(e) => {
-            result.add_error(format!("Failed to parse rules TOML: {}", e));
+            result.add_error(format!("Failed to parse rules TOML: {:#}", e));
             return Ok(result);
         }
     };
@@ -87,22 +88,7 @@ pub fn validate_rules<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
     }
 
     // Validate phases
-    if ruleset.turn.phases.is_empty() {
-        result.add_error("At least one phase must be defined".to_string());
-    } else {
-        let mut phase_ids = HashSet::new();
-        for phase in &ruleset.turn.phases {
-            if phase.id.is_empty() {
-                result.add_error("Phase ID cannot be empty".to_string());
-            }
-            if !phase_ids.insert(&phase.id) {
-                result.add_error(format!("Duplicate phase ID: {}", phase.id));
-            }
-            if phase.steps.is_empty() {
-                result.add_warning(format!("Phase '{}' has no steps", phase.id));
-            }
-        }
-    }
+    check_turn_structure(&ruleset, &mut result);
 
     // Validate zones
     if ruleset.zones.is_empty() {
@@ -119,6 +105,26 @@ pub fn validate_rules<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
         }
     }
 
+    // Validate colors, and that inline cards only use declared ones
+    let mut color_ids = HashSet::new();
+    for color in &ruleset.colors {
+        if !color_ids.insert(color.id.as_str()) {
+            result.add_error(format!("Duplicate color ID: {}", color.id));
+        }
+    }
+    for card in &ruleset.cards {
+        for color in card.colors.iter().filter(|c| !color_ids.contains(c.as_str())) {
+            result.add_error(format!("Card '{}' references undefined color '{}'", card.id, color));
+        }
+        // Custom builtins live on a game engine, so a rules file can't use them
+        for error in crate::engine::cards::effect_errors(card, &ruleset, &Default::default()) {
+            result.add_error(error);
+        }
+        for warning in crate::engine::cards::param_warnings(card) {
+            result.add_warning(warning);
+        }
+    }
+
     // Validate starting life
     if ruleset.players.starting_life == 0 {
         result.add_warning("Starting life is 0".to_string());
@@ -127,6 +133,50 @@ pub fn validate_rules<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
     Ok(result)
 }
 
+/// Check the turn structure the engine walks: a game starts in the first
+/// step of the first phase, and a phase without steps is only passed through
+/// a placeholder "start" step that no step rules (draws, allowed actions)
+/// apply to
+fn check_turn_structure(ruleset: &Ruleset, result: &mut ValidationResult) {
+    let phases = &ruleset.turn.phases;
+    let Some(first) = phases.first() else {
+        result.add_error("At least one phase must be defined".to_string());
+        return;
+    };
+
+    let mut phase_ids = HashSet::new();
+    for phase in phases {
+        if phase.id.is_empty() {
+            result.add_error("Phase ID cannot be empty".to_string());
+        }
+        if !phase_ids.insert(&phase.id) {
+            result.add_error(format!("Duplicate phase ID: {}", phase.id));
+        }
+        if phase.steps.is_empty() {
+            result.add_warning(format!("Phase '{}' has no steps, so turns pass through it without any", phase.id));
+        }
+        let mut step_ids = HashSet::new();
+        for step in &phase.steps {
+            if step.id.is_empty() {
+                result.add_error(format!("Step ID in phase '{}' cannot be empty", phase.id));
+            }
+            if !step_ids.insert(&step.id) {
+                result.add_error(format!("Duplicate step ID in phase '{}': {}", phase.id, step.id));
+            }
+        }
+    }
+
+    if first.steps.is_empty() {
+        result.add_error(format!(
+            "Starting phase '{}' has no steps for a game to start in",
+            first.id
+        ));
+    }
+    if phases.iter().all(|p| p.steps.is_empty()) {
+        result.add_warning("No phase has steps, so turns have nowhere for players to act".to_string());
+    }
+}
+
 /// Validate a card TOML file
 pub fn validate_card<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
     let path = path.as_ref();
@@ -163,29 +213,33 @@ pub fn validate_card<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
         result.add_error("Card type cannot be empty".to_string());
     }
 
+    for warning in crate::engine::cards::param_warnings(&card) {
+        result.add_warning(warning);
+    }
+
     // Note: Card types are config-driven in Cardinal, so we don't validate against
     // a hardcoded list. The game designer defines what card types are valid.
 
     // Check for script file if script_path is specified
-    if let Some(script_path) = &card.script_path {
-        if !script_path.is_empty() {
-            // Check if script file exists relative to card file
-            if let Some(card_dir) = path.parent() {
-                let full_script_path = card_dir.join(script_path);
-                if !full_script_path.exists() {
-                    // Also try relative to card_dir parent (for cards/ subdirectory)
-                    let alt_script_path = card_dir.parent()
-                        .map(|p| p.join(script_path))
-                        .unwrap_or_else(|| full_script_path.clone());
-                    
-                    if !alt_script_path.exists() {
-                        result.add_warning(format!(
-                            "Script file not found: {} (checked {} and {})",
-                            script_path,
-                            full_script_path.display(),
-                            alt_script_path.display()
-                        ));
-                    }
+    if let Some(script_path) = &card.script_path
+        && !script_path.is_empty()
+    {
+        // Check if script file exists relative to card file
+        if let Some(card_dir) = path.parent() {
+            let full_script_path = card_dir.join(script_path);
+            if !full_script_path.exists() {
+                // Also try relative to card_dir parent (for cards/ subdirectory)
+                let alt_script_path = card_dir.parent()
+                    .map(|p| p.join(script_path))
+                    .unwrap_or_else(|| full_script_path.clone());
+                
+                if !alt_script_path.exists() {
+                    result.add_warning(format!(
+                        "Script file not found: {} (checked {} and {})",
+                        script_path,
+                        full_script_path.display(),
+                        alt_script_path.display()
+                    ));
                 }
             }
         }
@@ -237,6 +291,9 @@ pub fn validate_cards_dir<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
         if card.name.is_empty() {
             result.add_error(format!("Card with ID '{}' has empty name", card.id));
         }
+        for warning in crate::engine::cards::param_warnings(card) {
+            result.add_warning(warning);
+        }
     }
 
     Ok(result)
@@ -272,6 +329,10 @@ pub fn validate_cards_file<P: AsRef<Path>>(path: P) -> Result<ValidationResult>
         result.add_error(format!("Card ID validation failed: {}", e));
     }
 
+    for warning in cards.iter().flat_map(crate::engine::cards::param_warnings) {
+        result.add_warning(warning);
+    }
+
     Ok(result)
 }
 
@@ -304,8 +365,35 @@ pub fn validate_script<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
     Ok(result)
 }
 
-/// Validate a pack directory before building
+/// Size thresholds [`validate_pack_with_limits`] warns about
+///
+/// Packs past these are usually carrying build output or other files that
+/// slipped past the default exclusions.
+#[derive(Debug, Clone)]
+pub struct PackLimits {
+    /// Most files a pack should contain
+    pub max_files: usize,
+    /// Largest total uncompressed size of the packed files, in bytes
+    pub max_total_size: u64,
+}
+
+impl Default for PackLimits {
+    fn default() -> Self {
+        Self {
+            max_files: 1000,
+            max_total_size: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Validate a pack directory before building, using the default [`PackLimits`]
 pub fn validate_pack<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
+    validate_pack_with_limits(path, &PackLimits::default())
+}
+
+/// Validate a pack directory before building, warning when the files that
+/// would be packed exceed `limits`
+pub fn validate_pack_with_limits<P: AsRef<Path>>(path: P, limits: &PackLimits) -> Result<ValidationResult> {
     let path = path.as_ref();
     let mut result = ValidationResult::new();
 
@@ -386,6 +474,30 @@ pub fn validate_pack<P: AsRef<Path>>(path: P) -> Result<ValidationResult> {
         }
     }
 
+    // Check the files that would be packed against the size limits
+    let files = collect_files(path, &PackBuildOptions::default())?;
+    if files.len() > limits.max_files {
+        result.add_warning(format!(
+            "Pack contains {} files, more than the limit of {}",
+            files.len(),
+            limits.max_files
+        ));
+    }
+
+    let mut total_size = 0u64;
+    for file in &files {
+        let metadata = std::fs::metadata(path.join(file))
+            .with_context(|| format!("Failed to read file metadata: {}", file.display()))?;
+        total_size += metadata.len();
+    }
+    if total_size > limits.max_total_size {
+        result.add_warning(format!(
+            "Pack files total {} bytes, more than the limit of {}",
+            total_size,
+            limits.max_total_size
+        ));
+    }
+
     Ok(result)
 }
 
@@ -440,6 +552,50 @@ mod tests {
         assert!(!result.errors.is_empty());
     }
 
+    #[test]
+    fn test_validate_rules_reports_unexecutable_effect() {
+        let temp_dir = std::env::temp_dir().join("test_rules_effect_validation");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let rules = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../rules.toml")).unwrap();
+        let card = "\n[[cards]]\nid = \"1\"\nname = \"Oddity\"\ncard_type = \"spell\"\n\n\
+                    [[cards.abilities]]\ntrigger = \"on_play\"\neffect = \"teleport\"\n";
+        let rules_path = temp_dir.join("rules.toml");
+        fs::write(&rules_path, rules + card).unwrap();
+
+        let result = validate_rules(&rules_path).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.contains("'teleport'")), "{:?}", result.errors);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_stepless_turn_structure() {
+        let mut rules = crate::load_game_config(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../../rules.toml"), None,
+        ).unwrap();
+        let mut result = ValidationResult::new();
+        check_turn_structure(&rules, &mut result);
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        for phase in &mut rules.turn.phases {
+            phase.steps.clear();
+        }
+        let mut result = ValidationResult::new();
+        check_turn_structure(&rules, &mut result);
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|e| e.starts_with("Starting phase")), "{:?}", result.errors);
+        assert_eq!(
+            result.warnings.iter().filter(|w| w.contains("has no steps")).count(),
+            rules.turn.phases.len()
+        );
+        assert!(result.warnings.iter().any(|w| w.starts_with("No phase has steps")), "{:?}", result.warnings);
+    }
+
     #[test]
     fn test_validate_script_syntax() {
         // Create a temporary test script with syntax error
@@ -476,4 +632,59 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_validate_pack_file_count_limit() {
+        let temp_dir = std::env::temp_dir().join("test_pack_file_limit");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("cards")).unwrap();
+        fs::write(temp_dir.join("pack.toml"), "pack_id = \"limits\"\nversion = \"1.0.0\"\n").unwrap();
+        for i in 0..5 {
+            fs::write(temp_dir.join(format!("notes{}.txt", i)), "x").unwrap();
+        }
+
+        let over = PackLimits { max_files: 3, ..PackLimits::default() };
+        let result = validate_pack_with_limits(&temp_dir, &over).unwrap();
+        assert!(result.is_valid);
+        assert!(result.warnings.iter().any(|w| w.contains("more than the limit of 3")));
+
+        let under = PackLimits { max_files: 10, ..PackLimits::default() };
+        let result = validate_pack_with_limits(&temp_dir, &under).unwrap();
+        assert!(result.warnings.iter().all(|w| !w.contains("limit")));
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_validate_card_flags_params_the_effect_does_not_expect() {
+        let temp_dir = std::env::temp_dir().join("test_card_param_validation");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let card = |params: &str| format!(
+            "id = \"1\"\nname = \"Spark\"\ncard_type = \"spell\"\n\n\
+             [[abilities]]\ntrigger = \"on_play\"\neffect = \"damage\"\nparams = {{ {} }}\n",
+            params
+        );
+        let card_path = temp_dir.join("spark.toml");
+
+        fs::write(&card_path, card("amount = \"3\", target = \"opponent\"")).unwrap();
+        let result = validate_card(&card_path).unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        // Without an amount the damage silently defaults to 1
+        fs::write(&card_path, card("target = \"opponent\"")).unwrap();
+        let result = validate_card(&card_path).unwrap();
+        assert!(result.is_valid);
+        assert!(result.warnings.iter().any(|w| w.contains("missing required param 'amount'")), "{:?}", result.warnings);
+
+        fs::write(&card_path, card("amont = \"3\"")).unwrap();
+        let result = validate_card(&card_path).unwrap();
+        assert!(result.warnings.iter().any(|w| w.contains("unexpected param 'amont'")), "{:?}", result.warnings);
+        assert!(result.warnings.iter().any(|w| w.contains("missing required param 'amount'")), "{:?}", result.warnings);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }